@@ -3,7 +3,12 @@ use byteorder::{ReadBytesExt, BE};
 use std::io::Read;
 
 bitflags::bitflags! {
-    /// Access flags used by classes and class members.
+    /// Raw access flags used by classes and class members.
+    ///
+    /// Some bits have a different meaning depending on where they're used
+    /// (e.g. `0x0020` is `ACC_SUPER` for classes and `ACC_SYNCHRONIZED` for
+    /// methods), prefer context specific flags like [`MethodAccessFlags`]
+    /// when interpreting them.
     #[derive(Debug, Clone, Copy)]
     pub struct AccessFlags: u16 {
         /// Declared public; may be accessed from outside its package.
@@ -26,7 +31,7 @@ bitflags::bitflags! {
         /// the invokespecial instruction.
         const SUPER = 0x0020;
 
-        /// Declared final; no subclasses allowed.
+        /// Declared volatile; cannot be cached.
         const VOLATILE = 0x0040;
 
         /// Declared transient; not written or read by a
@@ -60,6 +65,210 @@ bitflags::bitflags! {
     }
 }
 
+bitflags::bitflags! {
+    /// Access flags of a class, interface or a nested class (as stored in
+    /// `InnerClasses` attribute).
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct ClassAccessFlags: u16 {
+        /// Declared public; may be accessed from outside its package.
+        const PUBLIC = 0x0001;
+
+        /// Marked private in source (nested classes only).
+        const PRIVATE = 0x0002;
+
+        /// Marked protected in source (nested classes only).
+        const PROTECTED = 0x0004;
+
+        /// Marked or implicitly static in source (nested classes only).
+        const STATIC = 0x0008;
+
+        /// Declared final; no subclasses allowed.
+        const FINAL = 0x0010;
+
+        /// Treat superclass methods specially when invoked by
+        /// the invokespecial instruction.
+        const SUPER = 0x0020;
+
+        /// Is an interface, not a class.
+        const INTERFACE = 0x0200;
+
+        /// Declared abstract; must not be instantiated.
+        const ABSTRACT = 0x0400;
+
+        /// Declared synthetic; not present in the source code.
+        const SYNTHETIC = 0x1000;
+
+        /// Declared as an annotation interface.
+        const ANNOTATION = 0x2000;
+
+        /// Declared as an enum class.
+        const ENUM = 0x4000;
+
+        /// Is a module, not a class or interface.
+        const MODULE = 0x8000;
+    }
+}
+
+bitflags::bitflags! {
+    /// Access flags of a field.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct FieldAccessFlags: u16 {
+        /// Declared public; may be accessed from outside its package.
+        const PUBLIC = 0x0001;
+
+        /// Declared private; accessible only within the
+        /// defining class and other classes belonging to the same nest.
+        const PRIVATE = 0x0002;
+
+        /// Declared protected; may be accessed within subclasses.
+        const PROTECTED = 0x0004;
+
+        /// Declared static.
+        const STATIC = 0x0008;
+
+        /// Declared final; never directly assigned to after object construction.
+        const FINAL = 0x0010;
+
+        /// Declared volatile; cannot be cached.
+        const VOLATILE = 0x0040;
+
+        /// Declared transient; not written or read by a
+        /// persistent object manager.
+        const TRANSIENT = 0x0080;
+
+        /// Declared synthetic; not present in the source code.
+        const SYNTHETIC = 0x1000;
+
+        /// Declared as an element of an enum class.
+        const ENUM = 0x4000;
+    }
+}
+
+bitflags::bitflags! {
+    /// Access flags of a method.
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct MethodAccessFlags: u16 {
+        /// Declared public; may be accessed from outside its package.
+        const PUBLIC = 0x0001;
+
+        /// Declared private; accessible only within the
+        /// defining class and other classes belonging to the same nest.
+        const PRIVATE = 0x0002;
+
+        /// Declared protected; may be accessed within subclasses.
+        const PROTECTED = 0x0004;
+
+        /// Declared static.
+        const STATIC = 0x0008;
+
+        /// Declared final; must not be overridden.
+        const FINAL = 0x0010;
+
+        /// Declared synchronized; invocation is wrapped by a monitor use.
+        const SYNCHRONIZED = 0x0020;
+
+        /// A bridge method, generated by the compiler.
+        const BRIDGE = 0x0040;
+
+        /// Declared with variable number of arguments.
+        const VARARGS = 0x0080;
+
+        /// Declared native; implemented in a language other
+        /// than the Java programming language.
+        const NATIVE = 0x0100;
+
+        /// Declared abstract; no implementation is provided.
+        const ABSTRACT = 0x0400;
+
+        /// In a class file whose major version number is at least 46 and at
+        /// most 60: Declared strictfp.
+        const STRICT = 0x0800;
+
+        /// Declared synthetic; not present in the source code.
+        const SYNTHETIC = 0x1000;
+    }
+}
+
+bitflags::bitflags! {
+    /// Access flags of a formal parameter (as stored in `MethodParameters`
+    /// attribute).
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct ParameterAccessFlags: u16 {
+        /// Declared final.
+        const FINAL = 0x0010;
+
+        /// Not explicitly or implicitly declared in source code.
+        const SYNTHETIC = 0x1000;
+
+        /// Implicitly declared in source code.
+        const MANDATED = 0x8000;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags of a module (as stored in `Module` attribute).
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct ModuleAccessFlags: u16 {
+        /// Module is open.
+        const OPEN = 0x0020;
+
+        /// Module is not explicitly or implicitly declared.
+        const SYNTHETIC = 0x1000;
+
+        /// Module is implicitly declared.
+        const MANDATED = 0x8000;
+    }
+}
+
+bitflags::bitflags! {
+    /// Flags of a module dependence (as stored in `requires` entries of
+    /// `Module` attribute).
+    #[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+    pub struct RequiresFlags: u16 {
+        /// Modules depending on this module also depend on the required one.
+        const TRANSITIVE = 0x0020;
+
+        /// Dependence is mandatory at compile time only.
+        const STATIC_PHASE = 0x0040;
+
+        /// Dependence is not explicitly or implicitly declared.
+        const SYNTHETIC = 0x1000;
+
+        /// Dependence is implicitly declared.
+        const MANDATED = 0x8000;
+    }
+}
+
+impl From<AccessFlags> for ClassAccessFlags {
+    fn from(value: AccessFlags) -> Self {
+        ClassAccessFlags::from_bits_truncate(value.bits())
+    }
+}
+
+impl From<AccessFlags> for FieldAccessFlags {
+    fn from(value: AccessFlags) -> Self {
+        FieldAccessFlags::from_bits_truncate(value.bits())
+    }
+}
+
+impl From<AccessFlags> for MethodAccessFlags {
+    fn from(value: AccessFlags) -> Self {
+        MethodAccessFlags::from_bits_truncate(value.bits())
+    }
+}
+
+impl From<AccessFlags> for ParameterAccessFlags {
+    fn from(value: AccessFlags) -> Self {
+        ParameterAccessFlags::from_bits_truncate(value.bits())
+    }
+}
+
+impl From<AccessFlags> for ModuleAccessFlags {
+    fn from(value: AccessFlags) -> Self {
+        ModuleAccessFlags::from_bits_truncate(value.bits())
+    }
+}
+
 impl AccessFlags {
     pub fn read_from<R: Read>(r: &mut R) -> Result<AccessFlags, AccessFlagError> {
         let found = r.read_u16::<BE>()?;
@@ -5,7 +5,11 @@ use std::{
     io::{Cursor, Read},
 };
 
-use crate::{error::AttributeError, ext::ReadByteVecExt, Constant, ConstantPool};
+use crate::{
+    error::{AttributeError, ClassPathError},
+    ext::ReadByteVecExt,
+    ClassPath, Constant, ConstantPool, JVMType, ModuleAccessFlags, RequiresFlags,
+};
 
 macro_rules! flat_entry {
     ($name:ident {$($entry:ident:$entry_t:ty,)+}) => {
@@ -68,8 +72,125 @@ flat_entry!(MethodParameter {
     access_flags: u16,
 });
 
+/// Annotation with its explicitly given element values.
+#[derive(Debug, Clone, PartialEq)]
 pub struct Annotation {
-    pub type_index: u16,
+    pub ty: ClassPath,
+    pub elements: Vec<(String, ElementValue)>,
+}
+
+impl Annotation {
+    pub fn read_from<R: Read>(
+        r: &mut R,
+        constant_pool: &ConstantPool,
+    ) -> Result<Annotation, AttributeError> {
+        let ty = match read_type(r, constant_pool)? {
+            JVMType::TClass(it) => it,
+            _ => return Err(AttributeError::InvalidData),
+        };
+
+        let element_count = r.read_u16::<BE>()? as usize;
+        let mut elements = Vec::with_capacity(element_count);
+        for _ in 0..element_count {
+            let name = constant_pool
+                .get_utf8(r.read_u16::<BE>()? as usize)?
+                .to_string();
+            elements.push((name, ElementValue::read_from(r, constant_pool)?));
+        }
+
+        Ok(Annotation { ty, elements })
+    }
+}
+
+/// Value of an annotation element.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ElementValue {
+    Boolean(bool),
+    Byte(i8),
+    /// UTF-16 code unit.
+    Char(u16),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    Enum {
+        ty: ClassPath,
+        name: String,
+    },
+    /// Class literal; `void` for `void.class`.
+    Class(JVMType),
+    Annotation(Annotation),
+    Array(Vec<ElementValue>),
+}
+
+impl ElementValue {
+    pub fn read_from<R: Read>(
+        r: &mut R,
+        constant_pool: &ConstantPool,
+    ) -> Result<ElementValue, AttributeError> {
+        let tag = r.read_u8()? as char;
+        let mut constant = || -> Result<&Constant, AttributeError> {
+            Ok(constant_pool.try_get(r.read_u16::<BE>()? as usize)?)
+        };
+        Ok(match tag {
+            'B' | 'C' | 'I' | 'S' | 'Z' => {
+                let Constant::Integer { value } = constant()? else {
+                    return Err(AttributeError::InvalidData);
+                };
+                match tag {
+                    'B' => ElementValue::Byte(*value as i8),
+                    'C' => ElementValue::Char(*value as u16),
+                    'S' => ElementValue::Short(*value as i16),
+                    'Z' => ElementValue::Boolean(*value != 0),
+                    _ => ElementValue::Int(*value),
+                }
+            }
+            'J' => match constant()? {
+                Constant::Long { value } => ElementValue::Long(*value),
+                _ => return Err(AttributeError::InvalidData),
+            },
+            'F' => match constant()? {
+                Constant::Float { value } => ElementValue::Float(value.0),
+                _ => return Err(AttributeError::InvalidData),
+            },
+            'D' => match constant()? {
+                Constant::Double { value } => ElementValue::Double(value.0),
+                _ => return Err(AttributeError::InvalidData),
+            },
+            's' => match constant()? {
+                Constant::Utf8 { value } => ElementValue::String(value.clone()),
+                _ => return Err(AttributeError::InvalidData),
+            },
+            'e' => {
+                let JVMType::TClass(ty) = read_type(r, constant_pool)? else {
+                    return Err(AttributeError::InvalidData);
+                };
+                let name = constant_pool
+                    .get_utf8(r.read_u16::<BE>()? as usize)?
+                    .to_string();
+                ElementValue::Enum { ty, name }
+            }
+            'c' => ElementValue::Class(read_type(r, constant_pool)?),
+            '@' => ElementValue::Annotation(Annotation::read_from(r, constant_pool)?),
+            '[' => {
+                let count = r.read_u16::<BE>()? as usize;
+                let mut values = Vec::with_capacity(count);
+                for _ in 0..count {
+                    values.push(ElementValue::read_from(r, constant_pool)?);
+                }
+                ElementValue::Array(values)
+            }
+            _ => return Err(AttributeError::InvalidData),
+        })
+    }
+}
+
+/// Reads a `Utf8` constant index of a field descriptor.
+fn read_type<R: Read>(r: &mut R, constant_pool: &ConstantPool) -> Result<JVMType, AttributeError> {
+    let descriptor = constant_pool.get_utf8(r.read_u16::<BE>()? as usize)?;
+    Ok(JVMType::from_string(descriptor.to_string())?)
 }
 
 pub trait Attribute: Into<AttributeValue> + Sized {
@@ -144,7 +265,7 @@ impl From<CodeData> for AttributeValue {
 
 #[derive(Debug, Clone)]
 pub struct ExceptionData {
-    pub exceptions: Vec<usize>,
+    pub exceptions: Vec<ClassPath>,
 }
 
 impl Attribute for ExceptionData {
@@ -153,13 +274,14 @@ impl Attribute for ExceptionData {
     #[inline]
     fn read_data<R: std::io::Read>(
         r: &mut R,
-        _constant_pool: &ConstantPool,
+        constant_pool: &ConstantPool,
     ) -> Result<Self, AttributeError> {
         let number_of_exceptions = r.read_u16::<BE>()? as usize;
         let mut exceptions = Vec::with_capacity(number_of_exceptions);
 
         for _ in 0..number_of_exceptions {
-            exceptions.push(r.read_u16::<BE>()? as usize);
+            let class_index = r.read_u16::<BE>()? as usize;
+            exceptions.push(ClassPath::from_class_index(constant_pool, class_index)?);
         }
 
         Ok(ExceptionData { exceptions })
@@ -297,6 +419,55 @@ impl From<LocalVariableTable> for AttributeValue {
     }
 }
 
+/// Annotations of a class, field or method, either retained at run time
+/// (`RuntimeVisibleAnnotations`) or only in the class file
+/// (`RuntimeInvisibleAnnotations`).
+#[derive(Debug, Clone)]
+pub struct AnnotationsData {
+    pub annotations: Vec<Annotation>,
+}
+
+impl AnnotationsData {
+    fn read_annotations<R: std::io::Read>(
+        r: &mut R,
+        constant_pool: &ConstantPool,
+    ) -> Result<Self, AttributeError> {
+        let count = r.read_u16::<BE>()? as usize;
+        let mut annotations = Vec::with_capacity(count);
+        for _ in 0..count {
+            annotations.push(Annotation::read_from(r, constant_pool)?);
+        }
+        Ok(AnnotationsData { annotations })
+    }
+}
+
+impl Attribute for AnnotationsData {
+    const NAME: &'static str = "RuntimeVisibleAnnotations";
+
+    fn read_data<R: std::io::Read>(
+        r: &mut R,
+        constant_pool: &ConstantPool,
+    ) -> Result<Self, AttributeError> {
+        AnnotationsData::read_annotations(r, constant_pool)
+    }
+}
+
+impl AsData<AnnotationsData> for AttributeValue {
+    fn as_data(&self) -> Result<&AnnotationsData, AttributeError> {
+        match self {
+            AttributeValue::RuntimeVisibleAnnotations(it)
+            | AttributeValue::RuntimeInvisibleAnnotations(it) => Ok(it),
+            _ => Err(AttributeError::InvalidData),
+        }
+    }
+}
+
+impl From<AnnotationsData> for AttributeValue {
+    fn from(value: AnnotationsData) -> Self {
+        AttributeValue::RuntimeVisibleAnnotations(value)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AnnotationDefaultData {
     pub default: Vec<u8>,
@@ -408,6 +579,61 @@ pub struct SignatureData {
     pub signature: String,
 }
 
+/// Type listed in the throws clause of a generic method signature.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ThrowsSignature {
+    Class(ClassPath),
+    TypeVariable(String),
+}
+
+/// Removes type arguments (`<...>`) from a class type signature.
+fn strip_type_arguments(signature: &str) -> String {
+    let mut depth = 0;
+    signature
+        .chars()
+        .filter(|c| {
+            match c {
+                '<' => depth += 1,
+                '>' => {
+                    depth -= 1;
+                    return false;
+                }
+                _ => {}
+            }
+            depth == 0
+        })
+        .collect()
+}
+
+impl SignatureData {
+    /// Returns types thrown by a method with this signature.
+    ///
+    /// Throws clauses are `^` prefixed entries after the method return type,
+    /// e.g. `<E:Ljava/lang/Exception;>()V^TE;^Ljava/io/IOException;`.
+    pub fn method_throws(&self) -> Result<Vec<ThrowsSignature>, ClassPathError> {
+        let Some((_, result)) = self.signature.split_once(')') else {
+            return Ok(vec![]);
+        };
+
+        result
+            .split('^')
+            .skip(1)
+            .map(|entry| {
+                let entry = entry.trim_end_matches(';');
+                if let Some(name) = entry.strip_prefix('T') {
+                    return Ok(ThrowsSignature::TypeVariable(name.to_string()));
+                }
+                let path = entry.strip_prefix('L').ok_or_else(|| {
+                    ClassPathError::NoPrefix(entry.chars().next().unwrap_or_default())
+                })?;
+                // nested classes are separated with '.' in signatures
+                let path = strip_type_arguments(path).replace('.', "$");
+                ClassPath::parse(path).map(ThrowsSignature::Class)
+            })
+            .collect()
+    }
+}
+
 impl Attribute for SignatureData {
     const NAME: &'static str = "Signature";
     fn read_data<R: std::io::Read>(
@@ -478,6 +704,306 @@ impl From<MethodParameterData> for AttributeValue {
     }
 }
 
+/// Bootstrap method of `invokedynamic` call sites and dynamic constants.
+#[derive(Debug, Clone)]
+pub struct BootstrapMethod {
+    /// Index of the `MethodHandle` constant of the bootstrap method.
+    pub method_ref: u16,
+    /// Indices of loadable constants passed as static arguments.
+    pub arguments: Vec<u16>,
+}
+
+#[derive(Debug, Clone)]
+pub struct BootstrapMethodsData {
+    pub methods: Vec<BootstrapMethod>,
+}
+
+impl Attribute for BootstrapMethodsData {
+    const NAME: &'static str = "BootstrapMethods";
+
+    fn read_data<R: std::io::Read>(
+        r: &mut R,
+        _constant_pool: &ConstantPool,
+    ) -> Result<Self, AttributeError> {
+        let length = r.read_u16::<BE>()? as usize;
+        let mut methods = Vec::with_capacity(length);
+        for _ in 0..length {
+            let method_ref = r.read_u16::<BE>()?;
+            let argument_count = r.read_u16::<BE>()? as usize;
+            let mut arguments = Vec::with_capacity(argument_count);
+            for _ in 0..argument_count {
+                arguments.push(r.read_u16::<BE>()?);
+            }
+            methods.push(BootstrapMethod {
+                method_ref,
+                arguments,
+            });
+        }
+
+        Ok(BootstrapMethodsData { methods })
+    }
+}
+
+impl AsData<BootstrapMethodsData> for AttributeValue {
+    fn as_data(&self) -> Result<&BootstrapMethodsData, AttributeError> {
+        match self {
+            AttributeValue::BootstrapMethods(it) => Ok(it),
+            _ => Err(AttributeError::InvalidData),
+        }
+    }
+}
+
+impl From<BootstrapMethodsData> for AttributeValue {
+    fn from(value: BootstrapMethodsData) -> Self {
+        AttributeValue::BootstrapMethods(value)
+    }
+}
+
+/// Reads a `Class` constant index and resolves the class path.
+fn read_class<R: std::io::Read>(
+    r: &mut R,
+    constant_pool: &ConstantPool,
+) -> Result<ClassPath, AttributeError> {
+    let index = r.read_u16::<BE>()? as usize;
+    match constant_pool.try_get(index)? {
+        Constant::Class { name_index } => Ok(ClassPath::parse(
+            constant_pool.get_utf8(*name_index as usize)?,
+        )?),
+        _ => Err(AttributeError::InvalidData),
+    }
+}
+
+/// Reads an optional `Utf8` constant index; `0` stands for no value.
+fn read_optional_utf8<R: std::io::Read>(
+    r: &mut R,
+    constant_pool: &ConstantPool,
+) -> Result<Option<String>, AttributeError> {
+    match r.read_u16::<BE>()? as usize {
+        0 => Ok(None),
+        index => Ok(Some(constant_pool.get_utf8(index)?.to_string())),
+    }
+}
+
+/// Dependence of a module on another one (`requires`).
+#[derive(Debug, Clone)]
+pub struct ModuleRequires {
+    pub module: String,
+    pub flags: RequiresFlags,
+    /// Version of the required module at compile time.
+    pub version: Option<String>,
+}
+
+/// Package exported or opened by a module (`exports`, `opens`).
+#[derive(Debug, Clone)]
+pub struct ModulePackage {
+    /// Internal package name (`java/lang`).
+    pub package: String,
+    pub flags: ModuleAccessFlags,
+    /// Modules the package is exported or opened to; empty if it's
+    /// available to all modules.
+    pub to: Vec<String>,
+}
+
+/// Service implementations provided by a module (`provides ... with`).
+#[derive(Debug, Clone)]
+pub struct ModuleProvides {
+    pub service: ClassPath,
+    pub with: Vec<ClassPath>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleData {
+    pub name: String,
+    pub flags: ModuleAccessFlags,
+    pub version: Option<String>,
+    pub requires: Vec<ModuleRequires>,
+    pub exports: Vec<ModulePackage>,
+    pub opens: Vec<ModulePackage>,
+    /// Services the module consumes.
+    pub uses: Vec<ClassPath>,
+    pub provides: Vec<ModuleProvides>,
+}
+
+impl ModulePackage {
+    fn read_from<R: std::io::Read>(
+        r: &mut R,
+        constant_pool: &ConstantPool,
+    ) -> Result<Self, AttributeError> {
+        let package = constant_pool
+            .get_package_name(r.read_u16::<BE>()? as usize)?
+            .to_string();
+        let flags = ModuleAccessFlags::from_bits_truncate(r.read_u16::<BE>()?);
+        let count = r.read_u16::<BE>()? as usize;
+        let mut to = Vec::with_capacity(count);
+        for _ in 0..count {
+            to.push(
+                constant_pool
+                    .get_module_name(r.read_u16::<BE>()? as usize)?
+                    .to_string(),
+            );
+        }
+        Ok(ModulePackage { package, flags, to })
+    }
+}
+
+impl Attribute for ModuleData {
+    const NAME: &'static str = "Module";
+
+    fn read_data<R: std::io::Read>(
+        r: &mut R,
+        constant_pool: &ConstantPool,
+    ) -> Result<Self, AttributeError> {
+        let name = constant_pool
+            .get_module_name(r.read_u16::<BE>()? as usize)?
+            .to_string();
+        let flags = ModuleAccessFlags::from_bits_truncate(r.read_u16::<BE>()?);
+        let version = read_optional_utf8(r, constant_pool)?;
+
+        let count = r.read_u16::<BE>()? as usize;
+        let mut requires = Vec::with_capacity(count);
+        for _ in 0..count {
+            requires.push(ModuleRequires {
+                module: constant_pool
+                    .get_module_name(r.read_u16::<BE>()? as usize)?
+                    .to_string(),
+                flags: RequiresFlags::from_bits_truncate(r.read_u16::<BE>()?),
+                version: read_optional_utf8(r, constant_pool)?,
+            });
+        }
+
+        let count = r.read_u16::<BE>()? as usize;
+        let mut exports = Vec::with_capacity(count);
+        for _ in 0..count {
+            exports.push(ModulePackage::read_from(r, constant_pool)?);
+        }
+
+        let count = r.read_u16::<BE>()? as usize;
+        let mut opens = Vec::with_capacity(count);
+        for _ in 0..count {
+            opens.push(ModulePackage::read_from(r, constant_pool)?);
+        }
+
+        let count = r.read_u16::<BE>()? as usize;
+        let mut uses = Vec::with_capacity(count);
+        for _ in 0..count {
+            uses.push(read_class(r, constant_pool)?);
+        }
+
+        let count = r.read_u16::<BE>()? as usize;
+        let mut provides = Vec::with_capacity(count);
+        for _ in 0..count {
+            let service = read_class(r, constant_pool)?;
+            let with_count = r.read_u16::<BE>()? as usize;
+            let mut with = Vec::with_capacity(with_count);
+            for _ in 0..with_count {
+                with.push(read_class(r, constant_pool)?);
+            }
+            provides.push(ModuleProvides { service, with });
+        }
+
+        Ok(ModuleData {
+            name,
+            flags,
+            version,
+            requires,
+            exports,
+            opens,
+            uses,
+            provides,
+        })
+    }
+}
+
+impl AsData<ModuleData> for AttributeValue {
+    fn as_data(&self) -> Result<&ModuleData, AttributeError> {
+        match self {
+            AttributeValue::Module(it) => Ok(it),
+            _ => Err(AttributeError::InvalidData),
+        }
+    }
+}
+
+impl From<ModuleData> for AttributeValue {
+    fn from(value: ModuleData) -> Self {
+        AttributeValue::Module(value)
+    }
+}
+
+/// Packages of a module, including ones that aren't exported or opened.
+#[derive(Debug, Clone)]
+pub struct ModulePackagesData {
+    /// Internal package names (`java/lang`).
+    pub packages: Vec<String>,
+}
+
+impl Attribute for ModulePackagesData {
+    const NAME: &'static str = "ModulePackages";
+
+    fn read_data<R: std::io::Read>(
+        r: &mut R,
+        constant_pool: &ConstantPool,
+    ) -> Result<Self, AttributeError> {
+        let count = r.read_u16::<BE>()? as usize;
+        let mut packages = Vec::with_capacity(count);
+        for _ in 0..count {
+            packages.push(
+                constant_pool
+                    .get_package_name(r.read_u16::<BE>()? as usize)?
+                    .to_string(),
+            );
+        }
+        Ok(ModulePackagesData { packages })
+    }
+}
+
+impl AsData<ModulePackagesData> for AttributeValue {
+    fn as_data(&self) -> Result<&ModulePackagesData, AttributeError> {
+        match self {
+            AttributeValue::ModulePackages(it) => Ok(it),
+            _ => Err(AttributeError::InvalidData),
+        }
+    }
+}
+
+impl From<ModulePackagesData> for AttributeValue {
+    fn from(value: ModulePackagesData) -> Self {
+        AttributeValue::ModulePackages(value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ModuleMainClassData {
+    pub main_class: ClassPath,
+}
+
+impl Attribute for ModuleMainClassData {
+    const NAME: &'static str = "ModuleMainClass";
+
+    fn read_data<R: std::io::Read>(
+        r: &mut R,
+        constant_pool: &ConstantPool,
+    ) -> Result<Self, AttributeError> {
+        Ok(ModuleMainClassData {
+            main_class: read_class(r, constant_pool)?,
+        })
+    }
+}
+
+impl AsData<ModuleMainClassData> for AttributeValue {
+    fn as_data(&self) -> Result<&ModuleMainClassData, AttributeError> {
+        match self {
+            AttributeValue::ModuleMainClass(it) => Ok(it),
+            _ => Err(AttributeError::InvalidData),
+        }
+    }
+}
+
+impl From<ModuleMainClassData> for AttributeValue {
+    fn from(value: ModuleMainClassData) -> Self {
+        AttributeValue::ModuleMainClass(value)
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum AttributeValue {
     Unknown { name: String, data: Vec<u8> },
@@ -493,20 +1019,20 @@ pub enum AttributeValue {
     AnnotationDefault(AnnotationDefaultData),
     EnclosingMethod(EnclosingMethodData),
     LocalVariableTypeTable(LocalVariableTypeTable),
-    RuntimeVisibleAnnotations,
-    RuntimeInvisibleAnnotations,
+    RuntimeVisibleAnnotations(AnnotationsData),
+    RuntimeInvisibleAnnotations(AnnotationsData),
     RuntimeVisibleParameterAnnotations,
     RuntimeInvisibleParameterAnnotations,
     Signature(SignatureData),
     SourceDebugExtension,
     StackMapTable,
-    BootstrapMethods,
+    BootstrapMethods(BootstrapMethodsData),
     MethodParameters(MethodParameterData),
     RuntimeInvisibleTypeAnnotations,
     RuntimeVisibleTypeAnnotations,
-    Module,
-    ModuleMainClass,
-    ModulePackages,
+    Module(ModuleData),
+    ModuleMainClass(ModuleMainClassData),
+    ModulePackages(ModulePackagesData),
     NestHost,
     NestMembers,
 }
@@ -584,10 +1110,28 @@ impl AttributeValue {
             "LocalVariableTypeTable" => AttributeValue::LocalVariableTypeTable(
                 LocalVariableTypeTable::read_data(&mut r, constant_pool)?,
             ),
+            "BootstrapMethods" => AttributeValue::BootstrapMethods(
+                BootstrapMethodsData::read_data(&mut r, constant_pool)?,
+            ),
             "MethodParameters" => AttributeValue::MethodParameters(MethodParameterData::read_data(
                 &mut r,
                 constant_pool,
             )?),
+            "RuntimeVisibleAnnotations" => AttributeValue::RuntimeVisibleAnnotations(
+                AnnotationsData::read_data(&mut r, constant_pool)?,
+            ),
+            "RuntimeInvisibleAnnotations" => AttributeValue::RuntimeInvisibleAnnotations(
+                AnnotationsData::read_data(&mut r, constant_pool)?,
+            ),
+            "Module" => AttributeValue::Module(ModuleData::read_data(&mut r, constant_pool)?),
+            "ModulePackages" => AttributeValue::ModulePackages(ModulePackagesData::read_data(
+                &mut r,
+                constant_pool,
+            )?),
+            "ModuleMainClass" => AttributeValue::ModuleMainClass(ModuleMainClassData::read_data(
+                &mut r,
+                constant_pool,
+            )?),
             other => AttributeValue::Unknown {
                 name: other.to_string(),
                 data: data.to_vec(),
@@ -595,3 +1139,75 @@ impl AttributeValue {
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn module_pool() -> ConstantPool {
+        let mut pool = ConstantPool::new();
+        let utf8 = |value: &str| Constant::Utf8 {
+            value: value.to_string(),
+        };
+        pool.insert(utf8("com.example"));
+        pool.insert(Constant::Module { name_index: 1 });
+        pool.insert(utf8("java.base"));
+        pool.insert(Constant::Module { name_index: 3 });
+        pool.insert(utf8("com/example/api"));
+        pool.insert(Constant::Package { name_index: 5 });
+        pool.insert(utf8("com/example/Service"));
+        pool.insert(Constant::Class { name_index: 7 });
+        pool.insert(utf8("com/example/impl/Provider"));
+        pool.insert(Constant::Class { name_index: 9 });
+        pool.insert(utf8("17"));
+        pool
+    }
+
+    #[test]
+    fn module_attributes() {
+        let pool = module_pool();
+        #[rustfmt::skip]
+        let module: &[u16] = &[
+            // name, flags (open), no version
+            2, 0x0020, 0,
+            // requires java.base (mandated) at version 17
+            1, 4, 0x8000, 11,
+            // exports com.example.api to com.example
+            1, 6, 0, 1, 2,
+            // no opens
+            0,
+            // uses Service
+            1, 8,
+            // provides Service with Provider
+            1, 8, 1, 10,
+        ];
+        let bytes: Vec<u8> = module.iter().flat_map(|it| it.to_be_bytes()).collect();
+        let module = ModuleData::read_data(&mut bytes.as_slice(), &pool).unwrap();
+
+        assert_eq!(module.name, "com.example");
+        assert_eq!(module.flags, ModuleAccessFlags::OPEN);
+        assert_eq!(module.version, None);
+        assert_eq!(module.requires.len(), 1);
+        assert_eq!(module.requires[0].module, "java.base");
+        assert_eq!(module.requires[0].flags, RequiresFlags::MANDATED);
+        assert_eq!(module.requires[0].version.as_deref(), Some("17"));
+        assert_eq!(module.exports.len(), 1);
+        assert_eq!(module.exports[0].package, "com/example/api");
+        assert_eq!(module.exports[0].to, ["com.example"]);
+        assert!(module.opens.is_empty());
+        assert_eq!(module.uses[0].full_path(), "com.example.Service");
+        assert_eq!(
+            module.provides[0].service.full_path(),
+            "com.example.Service"
+        );
+        assert_eq!(
+            module.provides[0].with[0].full_path(),
+            "com.example.impl.Provider"
+        );
+
+        let packages = ModulePackagesData::read_data(&mut [0, 1, 0, 6].as_slice(), &pool).unwrap();
+        assert_eq!(packages.packages, ["com/example/api"]);
+        let main = ModuleMainClassData::read_data(&mut [0, 10].as_slice(), &pool).unwrap();
+        assert_eq!(main.main_class.full_path(), "com.example.impl.Provider");
+    }
+}
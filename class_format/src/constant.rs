@@ -330,4 +330,37 @@ impl ConstantPool {
         let name_i = constant_match!(self.get(class_index), Constant::Class { name_index })?;
         constant_match!(self.get(*name_i as usize), Constant::Utf8 { value }).cloned()
     }
+
+    pub fn get_utf8(&self, index: usize) -> Result<&str, ConstantPoolError> {
+        constant_match!(self.try_get(index)?, Constant::Utf8 { value }).map(String::as_str)
+    }
+
+    /// Returns name of a `Module` constant at `index`.
+    pub fn get_module_name(&self, index: usize) -> Result<&str, ConstantPoolError> {
+        let name_index = constant_match!(self.try_get(index)?, Constant::Module { name_index })?;
+        self.get_utf8(*name_index as usize)
+    }
+
+    /// Returns internal name (`java/lang`) of a `Package` constant at
+    /// `index`.
+    pub fn get_package_name(&self, index: usize) -> Result<&str, ConstantPoolError> {
+        let name_index = constant_match!(self.try_get(index)?, Constant::Package { name_index })?;
+        self.get_utf8(*name_index as usize)
+    }
+
+    /// Returns name and descriptor strings referenced by a `NameAndType`
+    /// constant at `index`.
+    pub fn get_name_and_type(&self, index: usize) -> Result<(&str, &str), ConstantPoolError> {
+        let (name_index, descriptor_index) = constant_match!(
+            self.try_get(index)?,
+            Constant::NameAndType {
+                name_index,
+                descriptor_index
+            }
+        )?;
+        Ok((
+            self.get_utf8(*name_index as usize)?,
+            self.get_utf8(*descriptor_index as usize)?,
+        ))
+    }
 }
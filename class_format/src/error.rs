@@ -131,6 +131,10 @@ pub enum AttributeError {
     InvalidData,
     #[error(transparent)]
     ConstantPool(#[from] ConstantPoolError),
+    #[error(transparent)]
+    ClassPath(#[from] ClassPathError),
+    #[error(transparent)]
+    JVMType(#[from] JVMTypeError),
 
     #[error(transparent)]
     IOError(#[from] std::io::Error),
@@ -147,6 +151,8 @@ pub enum ClassPathError {
         identifier: String,
         reason: &'static str,
     },
+    #[error("class path has no class name")]
+    MissingName,
 
     #[error("constant pool error: {0}")]
     ConstantPool(#[from] ConstantPoolError),
@@ -8,7 +8,10 @@ use std::fs::File;
 use std::io::{BufReader, Cursor, Read};
 use std::path::Path;
 
-pub use crate::access_flags::AccessFlags;
+pub use crate::access_flags::{
+    AccessFlags, ClassAccessFlags, FieldAccessFlags, MethodAccessFlags, ModuleAccessFlags,
+    ParameterAccessFlags, RequiresFlags,
+};
 pub use crate::attribute::Attribute;
 pub use crate::constant::{Constant, ConstantPool, ConstantTag};
 pub use crate::member::Member;
@@ -56,6 +59,10 @@ impl Default for ClassPath {
     }
 }
 
+fn is_identifier_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
 pub enum ClassPathParseState {
     Init { with_prefix: bool },
 }
@@ -67,13 +74,13 @@ impl ClassPath {
         let mut last = '\0';
         let mut token = String::with_capacity(8);
         while let Some(curr) = r.read_u8().ok().map(|it| it as char) {
-            if !curr.is_alphanumeric() && curr != '/' {
+            if !is_identifier_char(curr) && curr != '/' {
                 last = curr;
                 break;
             }
 
             if curr != '/' {
-                // curr.is_alphanumeric() is true, append char to token
+                // curr is an identifier character, append it to token
                 token.push(curr);
                 // advance
                 continue;
@@ -109,8 +116,10 @@ impl ClassPath {
             path_tokens.push(token);
         }
 
-        let (package, name) = path_tokens.split_at(path_tokens.len() - 1);
-        let name = name[0].clone();
+        let Some((name, package)) = path_tokens.split_last() else {
+            return Err(ClassPathError::MissingName);
+        };
+        let name = name.clone();
 
         let mut inner_classes = Vec::with_capacity(2);
         if last == '$' {
@@ -121,7 +130,7 @@ impl ClassPath {
                         inner_classes.push(inner);
                         inner = String::with_capacity(8);
                     }
-                    c if c.is_alphanumeric() => {
+                    c if is_identifier_char(c) => {
                         inner.push(c);
                     }
                     _ => {
@@ -158,8 +167,27 @@ impl ClassPath {
         self.package.join(".")
     }
 
+    /// Binary name of the class with `/` separated packages, as used in
+    /// class files (e.g. `java/util/Map$Entry`).
+    pub fn internal_name(&self) -> String {
+        let mut builder = self.package.join("/");
+        if !builder.is_empty() {
+            builder += "/";
+        }
+        builder += self.name.as_str();
+        for inner_c in &self.inner_classes {
+            builder += "$";
+            builder += inner_c;
+        }
+
+        builder
+    }
+
     pub fn jar_path(&self) -> String {
         let mut builder = self.package.join("/");
+        if !builder.is_empty() {
+            builder += "/";
+        }
         builder += self.name.as_str();
         if !self.inner_classes.is_empty() {
             builder += "$";
@@ -184,6 +212,11 @@ impl ClassPath {
         builder
     }
 
+    /// Name of the class without its package and enclosing classes.
+    pub fn simple_name(&self) -> &str {
+        self.inner_classes.last().unwrap_or(&self.name)
+    }
+
     pub fn is_in_java_lang(&self) -> bool {
         if self.package.len() != 2 {
             return false;
@@ -196,7 +229,7 @@ impl ClassPath {
         self.is_in_java_lang() && self.inner_classes.is_empty() && self.name == "Object"
     }
 
-    pub(crate) fn java_lang_class(name: impl ToString) -> Self {
+    pub fn java_lang_class(name: impl ToString) -> Self {
         ClassPath {
             package: vec!["java".to_string(), "lang".to_string()],
             inner_classes: vec![],
@@ -224,10 +257,12 @@ impl Display for ClassPath {
 pub struct Class {
     pub compiler_info: CompilerInfo,
 
-    pub access_flags: AccessFlags,
+    pub access_flags: ClassAccessFlags,
 
     pub constant_pool: ConstantPool,
 
+    /// Constant pool index of the `Class` constant naming the class.
+    pub this_class: usize,
     pub class_name: ClassPath,
     pub super_name: Option<ClassPath>,
     pub interfaces: Vec<ClassPath>,
@@ -245,6 +280,34 @@ impl Class {
         Class::read_from(&mut r)
     }
 
+    /// Whether the class was generated by the compiler, either flagged
+    /// `SYNTHETIC` or carrying a `Synthetic` attribute (before Java 5).
+    pub fn is_synthetic(&self) -> bool {
+        self.access_flags.contains(ClassAccessFlags::SYNTHETIC)
+            || self.attributes.contains_key("Synthetic")
+    }
+
+    /// Whether the class is a compiled `module-info.java`.
+    pub fn is_module_info(&self) -> bool {
+        self.access_flags.contains(ClassAccessFlags::MODULE)
+    }
+
+    /// Whether the class is the synthetic interface a `package-info.java` is
+    /// compiled into.
+    ///
+    /// The raw name is checked, as class paths end at the `-`.
+    pub fn is_package_info(&self) -> bool {
+        let raw_name = constant_match!(
+            self.constant_pool.get(self.this_class),
+            Constant::Class { name_index } => { *name_index as usize }
+        )
+        .and_then(|it| self.constant_pool.get_utf8(it));
+        raw_name.is_ok_and(|it| it == "package-info" || it.ends_with("/package-info"))
+            && self
+                .access_flags
+                .contains(ClassAccessFlags::INTERFACE | ClassAccessFlags::SYNTHETIC)
+    }
+
     pub fn read(bytes: impl AsRef<[u8]>) -> Result<Class, ClassReadError> {
         let mut r = Cursor::new(bytes.as_ref());
         Class::read_from(&mut r)
@@ -287,7 +350,7 @@ impl Class {
         }
 
         log::trace!("Class::read_from(impl Read)::access_flags");
-        let access_flags = AccessFlags::read_from(r)?;
+        let access_flags = AccessFlags::read_from(r)?.into();
 
         let class_const_index = r.read_u16::<BE>()? as usize;
         log::trace!(
@@ -355,6 +418,7 @@ impl Class {
 
             constant_pool,
 
+            this_class: class_const_index,
             class_name,
             super_name,
             interfaces,
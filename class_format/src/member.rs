@@ -1,4 +1,4 @@
-use crate::access_flags::AccessFlags;
+use crate::access_flags::{AccessFlags, FieldAccessFlags, MethodAccessFlags};
 use crate::attribute::AttributeValue;
 use crate::constant::{Constant, ConstantPool};
 use crate::error::{ConstantPoolError, MemberError};
@@ -53,6 +53,29 @@ impl Member {
         })
     }
 
+    /// Access flags interpreted as flags of a field.
+    pub fn field_flags(&self) -> FieldAccessFlags {
+        self.access_flags.into()
+    }
+
+    /// Access flags interpreted as flags of a method.
+    pub fn method_flags(&self) -> MethodAccessFlags {
+        self.access_flags.into()
+    }
+
+    /// Whether the member was generated by the compiler, either flagged
+    /// `SYNTHETIC` or carrying a `Synthetic` attribute (before Java 5).
+    pub fn is_synthetic(&self) -> bool {
+        self.access_flags.contains(AccessFlags::SYNTHETIC)
+            || self.attributes.contains_key("Synthetic")
+    }
+
+    /// Whether the method is a bridge generated for an overriding method
+    /// with a covariant return type or erased generic parameters.
+    pub fn is_bridge(&self) -> bool {
+        self.method_flags().contains(MethodAccessFlags::BRIDGE)
+    }
+
     pub fn is_constructor(&self) -> bool {
         self.name == "<init>"
     }
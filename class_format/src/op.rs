@@ -43,6 +43,8 @@ macro_rules! impl_ops {
     }};
 }
 
+// Variable length operands of `lookupswitch`, `tableswitch` and `wide` aren't
+// counted; see `Instruction::size_at`.
 //   Instruction      Code  ArgC
 #[rustfmt::skip]
 impl_ops![
@@ -196,6 +198,8 @@ impl_ops![
     (isub,            0x64, 0),
     (iushr,           0x7c, 0),
     (ixor,            0x82, 0),
+    (jsr,             0xa8, 2),
+    (jsr_w,           0xc9, 4),
     (l_2d,            0x8a, 0),
     (l_2f,            0x89, 0),
     (l_2i,            0x88, 0),
@@ -241,11 +245,14 @@ impl_ops![
     (pop_2,           0x58, 0),
     (putfield,        0xb5, 2),
     (putstatic,       0xb3, 2),
+    (ret,             0xa9, 1),
     (return,          0xb1, 0),
     (saload,          0x35, 0),
     (sastore,         0x56, 0),
     (sipush,          0x11, 2),
-    (swap,            0x5f, 0)
+    (swap,            0x5f, 0),
+    (tableswitch,     0xaa, 0),
+    (wide,            0xc4, 0)
 ];
 
 impl Display for Op {
@@ -367,6 +374,59 @@ impl Instruction {
         InstructionIterator::new(code).collect()
     }
 
+    /// Collects instructions along with their offsets in `code`.
+    pub fn collect_with_offsets(code: &[u8]) -> Vec<(usize, &Instruction)> {
+        let mut iter = InstructionIterator::new(code);
+        let mut result = Vec::new();
+        loop {
+            let offset = iter.pos;
+            match iter.next() {
+                Some(it) => result.push((offset, it)),
+                None => return result,
+            }
+        }
+    }
+
+    /// Size in bytes of the instruction at `pos` in method `code`, including
+    /// variable length operands.
+    pub fn size_at(code: &[u8], pos: usize) -> Result<usize, OpReadError> {
+        let instruction = Instruction::from_slice(&code[pos..])?;
+        let read_i32 = |at: usize| -> Result<i32, OpReadError> {
+            let bytes = code.get(at..at + 4).ok_or(OpReadError::MissingArgs {
+                op: instruction.op,
+                expected: at + 4 - pos,
+                available: code.len() - pos - 1,
+            })?;
+            Ok(i32::from_be_bytes(bytes.try_into().expect("4 bytes")))
+        };
+
+        Ok(match instruction.op {
+            Op::Tableswitch => {
+                let table = switch_table_start(pos);
+                let low = read_i32(table + 4)? as i64;
+                let high = read_i32(table + 8)? as i64;
+                table - pos + 12 + 4 * (high - low + 1).max(0) as usize
+            }
+            Op::Lookupswitch => {
+                let table = switch_table_start(pos);
+                let pairs = read_i32(table + 4)?.max(0) as usize;
+                table - pos + 8 + 8 * pairs
+            }
+            Op::Wide => match code.get(pos + 1).map(|it| Op::try_from(*it)) {
+                Some(Ok(Op::Iinc)) => 6,
+                Some(_) => 4,
+                None => {
+                    return Err(OpReadError::MissingArgs {
+                        op: Op::Wide,
+                        expected: 3,
+                        available: 0,
+                    })
+                }
+            },
+            op => 1 + op.argc(),
+        })
+    }
+
     #[inline]
     pub fn op(&self) -> Op {
         self.op
@@ -403,8 +463,93 @@ impl<'a> Iterator for InstructionIterator<'a> {
         }
 
         let i = Instruction::from_slice(&self.bytecode[self.pos..]).expect("invalid instruction");
-        self.pos += 1 + i.op.argc();
+        self.pos += Instruction::size_at(self.bytecode, self.pos).expect("invalid instruction");
 
         return Some(i);
     }
 }
+
+/// Offset of the table of a switch instruction at `pos`, which is aligned to 4
+/// bytes from the start of method code.
+fn switch_table_start(pos: usize) -> usize {
+    (pos + 4) & !3
+}
+
+/// Jump table of a `tableswitch` or `lookupswitch` instruction.
+///
+/// Jump offsets are relative to the offset of the switch instruction.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SwitchTable {
+    pub default: i32,
+    /// Matched values and their jump offsets, in order of values.
+    pub cases: Vec<(i32, i32)>,
+}
+
+impl SwitchTable {
+    /// Reads the table of the switch instruction at `pos` in method `code`.
+    ///
+    /// Returns `None` if the instruction isn't a switch or the table is
+    /// truncated.
+    pub fn read(code: &[u8], pos: usize) -> Option<SwitchTable> {
+        let table = switch_table_start(pos);
+        let read_i32 = |at: usize| -> Option<i32> {
+            Some(i32::from_be_bytes(code.get(at..at + 4)?.try_into().ok()?))
+        };
+
+        let default = read_i32(table)?;
+        let cases = match Op::try_from(*code.get(pos)?).ok()? {
+            Op::Tableswitch => {
+                let low = read_i32(table + 4)?;
+                let high = read_i32(table + 8)?;
+                (low..=high)
+                    .enumerate()
+                    .map(|(i, value)| Some((value, read_i32(table + 12 + 4 * i)?)))
+                    .collect::<Option<Vec<_>>>()?
+            }
+            Op::Lookupswitch => {
+                let pairs = read_i32(table + 4)?.max(0) as usize;
+                (0..pairs)
+                    .map(|i| {
+                        let at = table + 8 + 8 * i;
+                        Some((read_i32(at)?, read_i32(at + 4)?))
+                    })
+                    .collect::<Option<Vec<_>>>()?
+            }
+            _ => return None,
+        };
+
+        Some(SwitchTable { default, cases })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switch_sizes() {
+        // iload_0; tableswitch (padded by 2 bytes) 1..=2
+        let mut code = vec![0x1a, 0xaa, 0, 0];
+        for value in [20, 1, 2, 30, 40] {
+            code.extend_from_slice(&i32::to_be_bytes(value));
+        }
+        code.push(0xb1);
+
+        assert_eq!(Instruction::size_at(&code, 1).unwrap(), 23);
+        assert_eq!(
+            SwitchTable::read(&code, 1),
+            Some(SwitchTable {
+                default: 20,
+                cases: vec![(1, 30), (2, 40)],
+            })
+        );
+        let ops: Vec<_> = Instruction::collect_with_offsets(&code)
+            .into_iter()
+            .map(|(offset, it)| (offset, it.op()))
+            .collect();
+        assert_eq!(
+            ops,
+            [(0, Op::Iload0), (1, Op::Tableswitch), (24, Op::Return)]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use crate::error::JVMTypeError;
 use crate::ClassPath;
 use byteorder::ReadBytesExt;
+use std::fmt::{Display, Formatter, Write};
 use std::io::{Cursor, Read};
 use std::str::FromStr;
 
@@ -34,6 +35,22 @@ impl JVMPrimitive {
     }
 }
 
+impl From<JVMPrimitive> for char {
+    fn from(value: JVMPrimitive) -> Self {
+        match value {
+            JVMPrimitive::TByte => 'B',
+            JVMPrimitive::TChar => 'C',
+            JVMPrimitive::TDouble => 'D',
+            JVMPrimitive::TFloat => 'F',
+            JVMPrimitive::TInt => 'I',
+            JVMPrimitive::TLong => 'J',
+            JVMPrimitive::TShort => 'S',
+            JVMPrimitive::TBoolean => 'Z',
+            JVMPrimitive::TVoid => 'V',
+        }
+    }
+}
+
 impl TryFrom<char> for JVMPrimitive {
     type Error = JVMTypeError;
 
@@ -89,6 +106,23 @@ impl From<ClassPath> for JVMType {
     }
 }
 
+/// Formats the type as a field descriptor (e.g. `[Ljava/lang/String;`).
+impl Display for JVMType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JVMType::TPrimitive(it) => f.write_char((*it).into()),
+            JVMType::TClass(it) => write!(f, "L{};", it.internal_name()),
+            JVMType::TPrimitiveArray { depth, inner } => {
+                f.write_str(&"[".repeat(*depth))?;
+                f.write_char((*inner).into())
+            }
+            JVMType::TClassArray { depth, inner } => {
+                write!(f, "{}L{};", "[".repeat(*depth), inner.internal_name())
+            }
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct TypeSpecifier(pub JVMType);
 
@@ -163,6 +197,45 @@ impl JVMType {
         }
     }
 
+    /// Type of arrays with elements of this type.
+    pub fn array_of(&self) -> JVMType {
+        match self {
+            JVMType::TPrimitive(inner) => JVMType::TPrimitiveArray {
+                depth: 1,
+                inner: *inner,
+            },
+            JVMType::TClass(inner) => JVMType::TClassArray {
+                depth: 1,
+                inner: inner.clone(),
+            },
+            JVMType::TPrimitiveArray { depth, inner } => JVMType::TPrimitiveArray {
+                depth: depth + 1,
+                inner: *inner,
+            },
+            JVMType::TClassArray { depth, inner } => JVMType::TClassArray {
+                depth: depth + 1,
+                inner: inner.clone(),
+            },
+        }
+    }
+
+    /// Type of elements of this array type, `None` if this isn't an array.
+    pub fn element_type(&self) -> Option<JVMType> {
+        Some(match self {
+            JVMType::TPrimitiveArray { depth: 1, inner } => JVMType::TPrimitive(*inner),
+            JVMType::TPrimitiveArray { depth, inner } => JVMType::TPrimitiveArray {
+                depth: depth - 1,
+                inner: *inner,
+            },
+            JVMType::TClassArray { depth: 1, inner } => JVMType::TClass(inner.clone()),
+            JVMType::TClassArray { depth, inner } => JVMType::TClassArray {
+                depth: depth - 1,
+                inner: inner.clone(),
+            },
+            _ => return None,
+        })
+    }
+
     pub fn strip_arrays(&self) -> JVMType {
         match self {
             JVMType::TClassArray { inner, .. } => JVMType::TClass(inner.clone()),
@@ -189,7 +262,7 @@ impl FromStr for JVMType {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Descriptor {
     pub value: JVMType,
     pub arguments: Vec<JVMType>,
@@ -229,6 +302,18 @@ impl Descriptor {
     }
 }
 
+/// Formats the descriptor as it's stored in class files (e.g. `(IJ)V`).
+impl Display for Descriptor {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_char('(')?;
+        for argument in &self.arguments {
+            argument.fmt(f)?;
+        }
+        f.write_char(')')?;
+        self.value.fmt(f)
+    }
+}
+
 impl FromStr for Descriptor {
     type Err = JVMTypeError;
 
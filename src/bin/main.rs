@@ -1,11 +1,16 @@
 use std::fs::File;
 use std::io::BufWriter;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use jaded::file::jar::{ArchiveLayout, Jar};
+use jaded::gen::java::method::SkippedMethod;
+use jaded::gen::java::nested::{is_local_class, NestedClasses};
 use jaded::gen::java::JavaBackend;
 use jaded::gen::GenerateCode;
 use jaded::gen::GeneratorBuilder;
+use jaded::ir::accessor::Accessors;
+use jaded::ir::switch::{is_switch_map_class, SwitchMaps};
 use jaded::settings::Settings;
 use jvm_class_format::Class;
 
@@ -15,13 +20,125 @@ use tracing::Level;
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Arguments {
+    /// Class file or archive (jar, WAR or EAR) to decompile.
     pub input: PathBuf,
+    /// Output source file; for jars, a directory or a sources jar (e.g.
+    /// `foo-1.0-sources.jar`).
     pub output: PathBuf,
 
     #[command(flatten)]
     pub settings: Settings,
 }
 
+/// Reads classes of the nest of `input` (`Outer.class` and `Outer$*.class`)
+/// next to it, without `input` itself.
+fn sibling_classes(input: &Path) -> Vec<Class> {
+    let (Some(dir), Some(stem)) = (input.parent(), input.file_stem()) else {
+        return vec![];
+    };
+    let stem = stem.to_string_lossy();
+    let outer = stem.split('$').next().unwrap_or_default();
+    let Ok(entries) = std::fs::read_dir(if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    }) else {
+        return vec![];
+    };
+
+    let mut classes = Vec::new();
+    for entry in entries.filter_map(|it| it.ok()) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let Some(class_name) = name.strip_suffix(".class") else {
+            continue;
+        };
+        let nested = class_name == outer
+            || class_name
+                .strip_prefix(outer)
+                .is_some_and(|it| it.starts_with('$'));
+        if !nested || class_name == stem {
+            continue;
+        }
+        match Class::open(entry.path()) {
+            Ok(class) => classes.push(class),
+            Err(err) => tracing::warn!("unable to read {}: {}", name, err),
+        }
+    }
+    classes
+}
+
+fn is_jar(path: &Path) -> bool {
+    path.extension().is_some_and(|it| it == "jar")
+}
+
+/// Returns `true` for jars and archives with nested jars (WARs and EARs).
+fn is_archive(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|it| it == "jar" || it == "war" || it == "ear")
+}
+
+/// Lists methods whose bodies were left as assembly.
+fn report_skipped(methods: &[SkippedMethod]) {
+    if methods.is_empty() {
+        return;
+    }
+    tracing::warn!("{} methods were left as assembly:", methods.len());
+    for method in methods {
+        tracing::warn!(
+            "- {}.{}{}: {}",
+            method.class,
+            method.name,
+            method.descriptor,
+            method.reason
+        );
+    }
+}
+
+fn decompile_jar(args: &Arguments) {
+    let jar = Jar::open(&args.input)
+        .expect("can't open jar")
+        .with_release(args.settings.jar_release.unwrap_or_default());
+    if jar.layout() != ArchiveLayout::Plain {
+        tracing::info!(
+            "{:?} layout with {} nested libraries",
+            jar.layout(),
+            jar.libraries().count()
+        );
+    }
+    if jar.is_multi_release() {
+        tracing::info!("multi-release jar with overlays for {:?}", jar.releases());
+    }
+    let lang = GeneratorBuilder::java().settings(&args.settings).build();
+    let batch = args.settings.batch();
+    let report = if is_jar(&args.output) {
+        jar.decompile_to_jar(&args.output, &lang, &batch)
+    } else {
+        jar.decompile_to(&args.output, &lang, &batch)
+    }
+    .expect("unable to decompile jar");
+
+    tracing::info!(
+        "wrote {} sources and {} resources; {} entries failed",
+        report.sources.len(),
+        report.resources.len(),
+        report.failures.len()
+    );
+    tracing::info!(
+        "generated {} classes in {:.2?} on {} threads ({:.2?} of class time)",
+        report.stats.timings.len(),
+        report.stats.wall_time,
+        batch.threads(),
+        report.stats.class_time()
+    );
+    for timing in report.stats.slowest(5) {
+        tracing::debug!("{} took {:.2?}", timing.class, timing.elapsed);
+    }
+    report_skipped(&report.stats.skipped_methods);
+    if !report.failures.is_empty() {
+        std::process::exit(1);
+    }
+}
+
 fn main() {
     #[cfg(debug_assertions)]
     let log_level = Level::DEBUG;
@@ -32,16 +149,65 @@ fn main() {
 
     let args = Arguments::parse();
 
-    let class = Class::open(args.input).expect("can't open class");
+    if is_archive(&args.input) {
+        decompile_jar(&args);
+        return;
+    }
 
-    let lang = GeneratorBuilder::java().build();
-    let out = File::create(args.output).expect("unable to create output file");
+    let class = Class::open(&args.input).expect("can't open class");
+    if is_switch_map_class(&class) {
+        tracing::info!("{} only holds enum switch maps; skipping", class.class_name);
+        return;
+    }
+
+    let mut switch_maps = SwitchMaps::default();
+    let mut accessors = Accessors::default();
+    let mut nested_classes = NestedClasses::default();
+    // accessors of the class itself are hidden only if they're inlined
+    switch_maps.add_class(&class);
+    accessors.add_class(&class);
+    let mut local_classes = Vec::new();
+    for sibling in sibling_classes(&args.input) {
+        if is_local_class(&sibling.class_name) && !sibling.is_synthetic() {
+            local_classes.push(sibling.class_name.clone());
+        }
+        switch_maps.add_class(&sibling);
+        accessors.add_class(&sibling);
+        nested_classes.add_class(sibling);
+    }
+    // bodies of enum constants are written with the constants
+    for name in local_classes {
+        if !nested_classes.is_enum_body(&name) {
+            tracing::warn!(
+                "local or anonymous class {} isn't supported",
+                name.internal_name()
+            );
+        }
+    }
+
+    let lang = GeneratorBuilder::java()
+        .settings(&args.settings)
+        .switch_maps(switch_maps)
+        .accessors(accessors)
+        .nested_classes(nested_classes)
+        .build();
+    let out = File::create(&args.output).expect("unable to create output file");
 
     let mut w = BufWriter::new(out);
 
-    JavaBackend
+    let req = JavaBackend
         .write_value(&lang, &(), &class, &mut w)
         .expect("unable to generate class code");
 
     w.flush().expect("unable to flush");
+    report_skipped(&req.skipped_methods);
+
+    let mut line_map_path = args.output.into_os_string();
+    line_map_path.push(".linemap");
+    let line_map = File::create(line_map_path).expect("unable to create line map file");
+    let mut w = BufWriter::new(line_map);
+    req.line_map
+        .write_to(&mut w)
+        .expect("unable to write line map");
+    w.flush().expect("unable to flush");
 }
@@ -1,3 +1,5 @@
+use jvm_class_format::error::{ClassPathError, ClassReadError, ConstantPoolError, JVMTypeError};
+use jvm_class_format::{ClassPath, ConstantTag, Op};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -15,3 +17,55 @@ pub enum ManifestParseError {
         inner: std::io::Error,
     },
 }
+
+#[derive(Error, Debug)]
+pub enum DecompileError {
+    #[error("unable to read class: {0}")]
+    Class(#[from] ClassReadError),
+    #[error("class generation panicked: {0}")]
+    Panic(String),
+    #[error("outer class {0} is missing")]
+    MissingOuterClass(ClassPath),
+    #[error("local or anonymous class {0} isn't supported")]
+    UnsupportedNestedClass(ClassPath),
+    #[error("entry path points outside of the output directory")]
+    UnsafePath,
+
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+}
+
+/// Limit of [`MethodLimits`](crate::ir::MethodLimits) a method exceeded, or
+/// code that kept it from being decompiled as a whole.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum LimitExceeded {
+    #[error("method has {count} instructions, more than the limit of {limit}")]
+    Instructions { count: usize, limit: usize },
+    #[error("decompilation took longer than {0:?}")]
+    Deadline(std::time::Duration),
+    #[error("instruction '{op}' at offset {offset} couldn't be decompiled")]
+    Unsupported { offset: usize, op: Op },
+    #[error("local or anonymous class {} isn't supported", .0.internal_name())]
+    LocalClass(ClassPath),
+}
+
+#[derive(Error, Debug)]
+pub enum InstructionError {
+    #[error("instruction '{0}' isn't supported")]
+    Unsupported(Op),
+    #[error("operand stack underflow")]
+    StackUnderflow,
+    #[error("unexpected constant kind: {0:?}")]
+    UnsupportedConstant(ConstantTag),
+    #[error("missing bootstrap method #{0}")]
+    MissingBootstrapMethod(usize),
+
+    #[error(transparent)]
+    ConstantPool(#[from] ConstantPoolError),
+    #[error(transparent)]
+    ClassPath(#[from] ClassPathError),
+    #[error(transparent)]
+    Type(#[from] JVMTypeError),
+}
@@ -0,0 +1,307 @@
+//! Decompilation of many classes on a pool of worker threads.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{mpsc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use jvm_class_format::{Class, ClassPath};
+
+use crate::error::DecompileError;
+use crate::gen::java::lines::LineMap;
+use crate::gen::java::method::SkippedMethod;
+use crate::gen::java::{JavaBackend, JavaContext};
+use crate::gen::GenerateCode;
+
+/// Generated source code of a class.
+#[derive(Debug, Clone, Default)]
+pub struct ClassSource {
+    pub source: Vec<u8>,
+    /// Mappings of source lines to the bytecode they were generated from.
+    pub line_map: LineMap,
+    /// Methods left as assembly because they exceeded limits.
+    pub skipped_methods: Vec<SkippedMethod>,
+}
+
+/// Generates source code of `class`.
+///
+/// Panics of the generator are caught, so a single class can't stop
+/// decompilation of the others.
+pub fn decompile_class(lang: &JavaContext, class: &Class) -> Result<ClassSource, DecompileError> {
+    let mut output = Vec::with_capacity(4096);
+    let req = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        JavaBackend.write_value(lang, &(), class, &mut output)
+    }))
+    .map_err(|panic| {
+        let message = panic
+            .downcast_ref::<&str>()
+            .map(|it| it.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_default();
+        DecompileError::Panic(message)
+    })??;
+    Ok(ClassSource {
+        source: output,
+        line_map: req.line_map,
+        skipped_methods: req.skipped_methods,
+    })
+}
+
+/// Time spent generating a single class.
+#[derive(Debug, Clone)]
+pub struct ClassTiming {
+    pub class: ClassPath,
+    pub elapsed: Duration,
+    pub failed: bool,
+}
+
+/// Timing and failure statistics of a batch.
+#[derive(Debug, Clone, Default)]
+pub struct BatchStats {
+    /// Timings of classes, in the order they were given.
+    pub timings: Vec<ClassTiming>,
+    /// Time from the start of the batch until the last class was done.
+    pub wall_time: Duration,
+    /// Methods left as assembly because they exceeded limits.
+    pub skipped_methods: Vec<SkippedMethod>,
+}
+
+impl BatchStats {
+    /// Number of classes that couldn't be generated.
+    pub fn failed(&self) -> usize {
+        self.timings.iter().filter(|it| it.failed).count()
+    }
+
+    /// Time spent generating classes, summed over all workers.
+    pub fn class_time(&self) -> Duration {
+        self.timings.iter().map(|it| it.elapsed).sum()
+    }
+
+    /// Adds statistics of another batch run after this one.
+    pub fn merge(&mut self, other: BatchStats) {
+        self.timings.extend(other.timings);
+        self.wall_time += other.wall_time;
+        self.skipped_methods.extend(other.skipped_methods);
+    }
+
+    /// At most `count` classes which took the longest, slowest first.
+    pub fn slowest(&self, count: usize) -> Vec<&ClassTiming> {
+        let mut result: Vec<_> = self.timings.iter().collect();
+        result.sort_by_key(|it| Reverse(it.elapsed));
+        result.truncate(count);
+        result
+    }
+}
+
+/// Decompiles classes in parallel on a bounded number of threads.
+///
+/// Workers share the class list and generator context, which are only read,
+/// and results are handed back in the order classes were given, so the
+/// output doesn't depend on scheduling.
+#[derive(Debug, Clone, Copy)]
+pub struct BatchDecompiler {
+    threads: NonZeroUsize,
+}
+
+impl Default for BatchDecompiler {
+    fn default() -> Self {
+        BatchDecompiler {
+            threads: std::thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+        }
+    }
+}
+
+impl BatchDecompiler {
+    /// Creates a decompiler using `threads` workers; `0` uses all cores.
+    pub fn new(threads: usize) -> BatchDecompiler {
+        match NonZeroUsize::new(threads) {
+            Some(threads) => BatchDecompiler { threads },
+            None => BatchDecompiler::default(),
+        }
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads.get()
+    }
+
+    /// Decompiles `classes`, calling `handle` with each result on the
+    /// current thread in the order of `classes`.
+    pub fn for_each<F>(&self, lang: &JavaContext, classes: &[Class], mut handle: F) -> BatchStats
+    where
+        F: FnMut(&Class, Result<ClassSource, DecompileError>),
+    {
+        let started = Instant::now();
+        let mut timings = Vec::with_capacity(classes.len());
+        let mut skipped_methods = Vec::new();
+        run_ordered(
+            self.threads(),
+            classes,
+            |class| {
+                let started = Instant::now();
+                let result = decompile_class(lang, class);
+                (result, started.elapsed())
+            },
+            |index, (result, elapsed)| {
+                let class = &classes[index];
+                timings.push(ClassTiming {
+                    class: class.class_name.clone(),
+                    elapsed,
+                    failed: result.is_err(),
+                });
+                if let Ok(source) = &result {
+                    skipped_methods.extend(source.skipped_methods.iter().cloned());
+                }
+                handle(class, result);
+            },
+        );
+
+        BatchStats {
+            timings,
+            wall_time: started.elapsed(),
+            skipped_methods,
+        }
+    }
+}
+
+/// Applies `work` to `items` on up to `threads` scoped threads and passes
+/// results to `handle` on the current thread, ordered by item index.
+///
+/// Results finished ahead of their turn wait in a buffer; workers don't start
+/// items `threads` or more past the first unhandled one, so the buffer holds
+/// fewer results than there are workers.
+fn run_ordered<T, R, W, H>(threads: usize, items: &[T], work: W, mut handle: H)
+where
+    T: Sync,
+    R: Send,
+    W: Fn(&T) -> R + Sync,
+    H: FnMut(usize, R),
+{
+    let workers = threads.min(items.len());
+    if workers <= 1 {
+        for (index, item) in items.iter().enumerate() {
+            handle(index, work(item));
+        }
+        return;
+    }
+
+    let next = AtomicUsize::new(0);
+    let progress = Progress {
+        handled: Mutex::new(Some(0)),
+        changed: Condvar::new(),
+    };
+    let (sender, receiver) = mpsc::sync_channel(workers);
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            let sender = sender.clone();
+            let (next, progress, work) = (&next, &progress, &work);
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else {
+                    break;
+                };
+                if !progress.wait_for(index, workers) {
+                    break;
+                }
+                if sender.send((index, work(item))).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(sender);
+
+        let _stop = StopOnDrop(&progress);
+        let mut pending = BTreeMap::new();
+        let mut expected = 0;
+        for (index, result) in receiver {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&expected) {
+                handle(expected, result);
+                expected += 1;
+            }
+            progress.set(Some(expected));
+        }
+    });
+}
+
+/// Number of results [`run_ordered`] handled, shared with its workers.
+struct Progress {
+    /// `None` once results aren't received anymore.
+    handled: Mutex<Option<usize>>,
+    changed: Condvar,
+}
+
+impl Progress {
+    fn set(&self, handled: Option<usize>) {
+        *self.handled.lock().unwrap_or_else(|it| it.into_inner()) = handled;
+        self.changed.notify_all();
+    }
+
+    /// Blocks until item `index` is less than `window` past the first
+    /// unhandled one; returns `false` if results aren't received anymore.
+    fn wait_for(&self, index: usize, window: usize) -> bool {
+        let handled = self.handled.lock().unwrap_or_else(|it| it.into_inner());
+        let handled = self
+            .changed
+            .wait_while(handled, |it| it.is_some_and(|it| index >= it + window))
+            .unwrap_or_else(|it| it.into_inner());
+        handled.is_some()
+    }
+}
+
+/// Releases waiting workers when the receiving thread stops, even by a
+/// panic of the handler.
+struct StopOnDrop<'a>(&'a Progress);
+
+impl Drop for StopOnDrop<'_> {
+    fn drop(&mut self) {
+        self.0.set(None);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn results_keep_item_order() {
+        let items: Vec<u64> = (0..32).collect();
+        let mut seen = Vec::new();
+        run_ordered(
+            4,
+            &items,
+            |it| {
+                // later items finish first
+                std::thread::sleep(Duration::from_millis(32 - it));
+                it * 2
+            },
+            |index, result| seen.push((index, result)),
+        );
+        let expected: Vec<_> = items.iter().map(|it| (*it as usize, it * 2)).collect();
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn workers_wait_for_slow_items() {
+        let items: Vec<u64> = (0..16).collect();
+        let started = AtomicUsize::new(0);
+        run_ordered(
+            2,
+            &items,
+            |it| {
+                started.fetch_max(*it as usize, Ordering::Relaxed);
+                if *it == 0 {
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+            },
+            |index, _| {
+                if index == 0 {
+                    // the other worker stopped after the next item
+                    assert_eq!(started.load(Ordering::Relaxed), 1);
+                }
+            },
+        );
+    }
+}
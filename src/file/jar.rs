@@ -1,162 +1,1254 @@
+use crate::error::DecompileError;
+use crate::file::batch::{BatchDecompiler, BatchStats, ClassSource};
 use crate::file::manifest::Manifest;
-use jvm_class_format::Class;
+use crate::file::output::{DirectorySink, JarSink, PrefixedSink, SourceSink, MANIFEST_PATH};
+use crate::gen::java::classpath::ClassIndex;
+use crate::gen::java::nested::NestedClasses;
+use crate::gen::java::JavaContext;
+use crate::ir::accessor::Accessors;
+use crate::ir::switch::SwitchMaps;
+use jvm_class_format::{Class, ClassAccessFlags, ClassPath};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::io::{Cursor, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use zip::read::ZipFile;
+use zip::result::ZipError;
+use zip::CompressionMethod;
 use zip::ZipArchive;
 
+/// Entry which couldn't be decompiled or copied.
 #[derive(Debug)]
-pub struct Jar {
+pub struct EntryFailure {
+    /// Name of the entry in the archive.
+    pub entry: String,
+    pub error: DecompileError,
+}
+
+/// Outcome of [`Jar::decompile_to`].
+#[derive(Debug, Default)]
+pub struct DecompileReport {
+    /// Written source files, relative to the output root.
+    pub sources: Vec<PathBuf>,
+    /// Copied entries other than classes, relative to the output root.
+    pub resources: Vec<PathBuf>,
+    pub failures: Vec<EntryFailure>,
+    /// Generation time of each top level class.
+    pub stats: BatchStats,
+}
+
+impl DecompileReport {
+    fn fail(&mut self, entry: String, error: DecompileError) {
+        tracing::error!("unable to decompile '{}': {}", entry, error);
+        self.failures.push(EntryFailure { entry, error });
+    }
+
+    /// Adds the report of nested archive `name`, which was written into a
+    /// directory of the same name.
+    fn include(&mut self, name: &str, other: DecompileReport) {
+        let root = Path::new(name);
+        self.sources
+            .extend(other.sources.iter().map(|it| root.join(it)));
+        self.resources
+            .extend(other.resources.iter().map(|it| root.join(it)));
+        self.failures
+            .extend(other.failures.into_iter().map(|it| EntryFailure {
+                entry: format!("{}!/{}", name, it.entry),
+                error: it.error,
+            }));
+        self.stats.merge(other.stats);
+    }
+}
+
+/// Path of the source file of a top level class, relative to the source root.
+pub fn source_path(class: &Class) -> PathBuf {
+    if class.is_module_info() {
+        return PathBuf::from("module-info.java");
+    }
+    let mut result: PathBuf = class.class_name.package.iter().collect();
+    if class.is_package_info() {
+        result.push("package-info.java");
+    } else {
+        result.push(format!("{}.java", class.class_name.name));
+    }
+    result
+}
+
+/// Writes generated source to `path`, and its line map (if any) next to it
+/// as `<path>.linemap`, like for single classes.
+fn write_source(
+    sink: &mut dyn SourceSink,
+    path: &Path,
+    source: &ClassSource,
+) -> Result<(), DecompileError> {
+    sink.write_entry(path, &source.source)?;
+    if source.line_map.0.is_empty() {
+        return Ok(());
+    }
+    let mut line_map = Vec::new();
+    source.line_map.write_to(&mut line_map)?;
+    let mut line_map_path = path.as_os_str().to_owned();
+    line_map_path.push(".linemap");
+    sink.write_entry(Path::new(&line_map_path), &line_map)
+}
+
+/// Copies an archive entry into `sink` at `path`, returning the path unless
+/// the sink leaves it out.
+fn copy_entry(
+    entry: &mut ZipFile,
     path: PathBuf,
+    sink: &mut dyn SourceSink,
+) -> Result<Option<PathBuf>, DecompileError> {
+    if entry.enclosed_name().is_none() {
+        return Err(DecompileError::UnsafePath);
+    }
+    if !sink.keeps_resource(&path) {
+        return Ok(None);
+    }
+    let mut contents = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut contents)?;
+    sink.write_entry(&path, &contents)?;
+    Ok(Some(path))
+}
+
+/// Prefix of entries overlaid for specific Java releases in multi-release
+/// jars.
+pub const VERSIONS_PREFIX: &str = "META-INF/versions/";
+
+/// Splits a `META-INF/versions/N/name` entry into the release it's for and
+/// the name of the entry it overlays.
+pub fn versioned_name(entry: &str) -> Option<(u16, &str)> {
+    let (release, name) = entry.strip_prefix(VERSIONS_PREFIX)?.split_once('/')?;
+    // overlays start with Java 9
+    let release = release.parse().ok().filter(|it| *it >= 9)?;
+    Some((release, name))
+}
+
+/// Variant of classes read from multi-release jars.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReleaseSelection {
+    /// Base entries, without `META-INF/versions` overlays.
+    #[default]
+    Base,
+    /// Entries a runtime of the Java release sees.
+    Release(u16),
+    /// Base entries, along with each overlay decompiled separately into
+    /// `META-INF/versions/N`.
+    All,
+}
+
+impl ReleaseSelection {
+    fn release(self) -> Option<u16> {
+        match self {
+            ReleaseSelection::Release(it) => Some(it),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ReleaseSelection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base" => Ok(ReleaseSelection::Base),
+            "all" => Ok(ReleaseSelection::All),
+            _ => s
+                .parse()
+                .map(ReleaseSelection::Release)
+                .map_err(|_| format!("expected a Java release, 'base' or 'all'; got '{}'", s)),
+        }
+    }
+}
+
+/// Arrangement of application classes and libraries in an archive.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ArchiveLayout {
+    /// Classes at the root of the archive.
+    #[default]
+    Plain,
+    /// Spring Boot executable jar; classes in `BOOT-INF/classes` and
+    /// libraries in `BOOT-INF/lib`.
+    SpringBoot,
+    /// Web application archive; classes in `WEB-INF/classes` and libraries in
+    /// `WEB-INF/lib`.
+    War,
+    /// Enterprise application archive; modules are archives at the root and
+    /// libraries shared by them are in `lib`.
+    Ear,
+}
+
+impl ArchiveLayout {
+    /// Guesses the layout from names of archive entries.
+    pub fn detect(entries: &[String]) -> ArchiveLayout {
+        let has_prefix = |prefix: &str| entries.iter().any(|it| it.starts_with(prefix));
+        if has_prefix("BOOT-INF/") {
+            ArchiveLayout::SpringBoot
+        } else if has_prefix("WEB-INF/") {
+            ArchiveLayout::War
+        } else if entries
+            .iter()
+            .any(|it| it == "META-INF/application.xml" || !it.contains('/') && it.ends_with(".war"))
+        {
+            ArchiveLayout::Ear
+        } else {
+            ArchiveLayout::Plain
+        }
+    }
+
+    /// Directory application classes and their resources are in.
+    pub fn class_root(self) -> &'static str {
+        match self {
+            ArchiveLayout::Plain | ArchiveLayout::Ear => "",
+            ArchiveLayout::SpringBoot => "BOOT-INF/classes/",
+            ArchiveLayout::War => "WEB-INF/classes/",
+        }
+    }
+
+    /// Returns `true` if `entry` is a nested library archive.
+    pub fn is_library(self, entry: &str) -> bool {
+        let directories: &[&str] = match self {
+            ArchiveLayout::Plain => return false,
+            ArchiveLayout::SpringBoot => &["BOOT-INF/lib/"],
+            ArchiveLayout::War => &["WEB-INF/lib/", "WEB-INF/lib-provided/"],
+            ArchiveLayout::Ear => &["lib/"],
+        };
+        entry.ends_with(".jar")
+            && directories
+                .iter()
+                .any(|it| entry.strip_prefix(it).is_some_and(|it| !it.contains('/')))
+    }
+
+    /// Returns `true` if `entry` is a nested application module (of an EAR).
+    pub fn is_module(self, entry: &str) -> bool {
+        self == ArchiveLayout::Ear
+            && !entry.contains('/')
+            && [".jar", ".war", ".rar"]
+                .iter()
+                .any(|it| entry.ends_with(it))
+    }
+}
+
+/// Part of a file holding an archive entry stored without compression, read
+/// as if it was a file on its own.
+struct EntryWindow {
+    file: File,
+    start: u64,
+    len: u64,
+    position: u64,
+}
+
+impl EntryWindow {
+    fn new(file: File, start: u64, len: u64) -> Self {
+        EntryWindow {
+            file,
+            start,
+            len,
+            position: 0,
+        }
+    }
+}
+
+impl Read for EntryWindow {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let remaining = self.len.saturating_sub(self.position);
+        let limit = buf
+            .len()
+            .min(usize::try_from(remaining).unwrap_or(usize::MAX));
+        self.file
+            .seek(SeekFrom::Start(self.start + self.position))?;
+        let read = self.file.read(&mut buf[..limit])?;
+        self.position += read as u64;
+        Ok(read)
+    }
+}
+
+impl Seek for EntryWindow {
+    fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(it) => Some(it),
+            SeekFrom::End(it) => self.len.checked_add_signed(it),
+            SeekFrom::Current(it) => self.position.checked_add_signed(it),
+        };
+        self.position = position.ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "seek before the start of an entry",
+            )
+        })?;
+        Ok(self.position)
+    }
+}
+
+/// Entry as seen by a Java release.
+#[derive(Debug, Clone, Copy)]
+struct ResolvedEntry<'a> {
+    /// Name relative to the class root, without the overlay directory.
+    name: &'a str,
+    index: usize,
+    /// Release of the overlay the entry comes from; `None` for base entries.
+    release: Option<u16>,
+}
+
+/// Seekable source of archive contents.
+pub trait ArchiveReader: Read + Seek + Send {}
+
+impl<T: Read + Seek + Send> ArchiveReader for T {}
+
+/// Jar archive, kept open for reading classes and resources.
+pub struct Jar {
+    path: Option<PathBuf>,
+    archive: RefCell<ZipArchive<Box<dyn ArchiveReader>>>,
+    /// Entry names in archive order.
+    entries: Vec<String>,
+    /// Whether entries in `META-INF/versions` overlay base entries.
+    multi_release: bool,
+    release: ReleaseSelection,
+    layout: ArchiveLayout,
 
     pub manifest: Option<Manifest>,
     pub main_class: Option<String>,
+}
 
-    file_count: usize,
+impl std::fmt::Debug for Jar {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Jar")
+            .field("path", &self.path)
+            .field("entries", &self.entries.len())
+            .field("multi_release", &self.multi_release)
+            .field("release", &self.release)
+            .field("layout", &self.layout)
+            .field("manifest", &self.manifest)
+            .field("main_class", &self.main_class)
+            .finish()
+    }
 }
 
 impl Jar {
     pub fn open<T: AsRef<Path>>(path: T) -> Result<Jar, std::io::Error> {
-        let file = File::open(path.as_ref())?;
-        let mut archive = ZipArchive::new(file)?;
+        let mut result = Jar::from_reader(File::open(path.as_ref())?)?;
+        result.path = Some(path.as_ref().to_path_buf());
+        // modules of EARs aren't necessarily listed in `application.xml`
+        if path.as_ref().extension().is_some_and(|it| it == "ear") {
+            result.layout = ArchiveLayout::Ear;
+        }
+        Ok(result)
+    }
+
+    /// Reads a jar from `reader`, e.g. a [`Cursor`](std::io::Cursor) over
+    /// jar contents held in memory.
+    pub fn from_reader(reader: impl ArchiveReader + 'static) -> Result<Jar, std::io::Error> {
+        let reader: Box<dyn ArchiveReader> = Box::new(reader);
+        let mut archive = ZipArchive::new(reader)?;
+
+        let mut entries = Vec::with_capacity(archive.len());
+        for index in 0..archive.len() {
+            entries.push(archive.by_index_raw(index)?.name().to_string());
+        }
 
-        let (manifest, main_class) = {
-            let mut mf_file = archive.by_name("META-INF/MANIFEST.MF")?;
-            let manifest = match Manifest::read_from(&mut mf_file) {
+        // plain library jars often come without a manifest
+        let manifest = match archive.by_name(MANIFEST_PATH) {
+            Ok(mut mf_file) => match Manifest::read_from(&mut mf_file) {
                 Ok(m) => Some(m),
                 Err(err) => {
                     tracing::warn!("manifest parsing error: {}", err);
                     None
                 }
-            };
-            let main_class = match &manifest {
-                Some(m) => m.get("Main-Class").map(|s| s.to_string()),
-                None => None,
-            };
-            (manifest, main_class)
+            },
+            Err(ZipError::FileNotFound) => None,
+            Err(err) => return Err(err.into()),
         };
+        let main_class = manifest
+            .as_ref()
+            .and_then(|it| it.get("Main-Class"))
+            .map(|it| it.to_string());
+        let multi_release = manifest
+            .as_ref()
+            .and_then(|it| it.get("Multi-Release"))
+            .is_some_and(|it| it.trim().eq_ignore_ascii_case("true"));
 
         Ok(Jar {
-            path: path.as_ref().to_path_buf(),
+            path: None,
+            archive: RefCell::new(archive),
+            layout: ArchiveLayout::detect(&entries),
+            entries,
+            multi_release,
+            release: ReleaseSelection::default(),
 
             manifest,
             main_class,
+        })
+    }
+
+    /// Path the jar was opened from; `None` for jars read from memory.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Names of all entries, in archive order.
+    pub fn entry_names(&self) -> impl Iterator<Item = &str> {
+        self.entries.iter().map(String::as_str)
+    }
+
+    pub fn layout(&self) -> ArchiveLayout {
+        self.layout
+    }
+
+    /// Names of nested library archives (e.g. `BOOT-INF/lib/*.jar`).
+    pub fn libraries(&self) -> impl Iterator<Item = &str> {
+        self.entry_names().filter(|it| self.layout.is_library(it))
+    }
+
+    /// Names of nested application modules of an EAR.
+    pub fn modules(&self) -> impl Iterator<Item = &str> {
+        self.entry_names().filter(|it| self.layout.is_module(it))
+    }
+
+    /// Reads nested archive `entry` (e.g. a library or a module) into memory.
+    pub fn open_nested(&self, entry: &str) -> Result<Jar, DecompileError> {
+        let mut contents = Vec::new();
+        self.archive
+            .borrow_mut()
+            .by_name(entry)?
+            .read_to_end(&mut contents)?;
+        Ok(Jar::from_reader(Cursor::new(contents))?)
+    }
+
+    /// Name of `entry` relative to the class root; `None` for nested archives
+    /// and classes outside of the class root, which aren't part of the
+    /// application.
+    fn application_name<'a>(&self, entry: &'a str) -> Option<&'a str> {
+        if self.layout.is_library(entry) || self.layout.is_module(entry) {
+            return None;
+        }
+        match entry.strip_prefix(self.layout.class_root()) {
+            Some(name) => Some(name),
+            None if entry.ends_with(".class") => None,
+            None => Some(entry),
+        }
+    }
+
+    /// Whether the manifest declares the jar as multi-release.
+    pub fn is_multi_release(&self) -> bool {
+        self.multi_release
+    }
+
+    /// Selects the variant of classes and resources read from a
+    /// multi-release jar.
+    pub fn select_release(&mut self, release: ReleaseSelection) {
+        self.release = release;
+    }
+
+    pub fn with_release(mut self, release: ReleaseSelection) -> Self {
+        self.select_release(release);
+        self
+    }
 
-            file_count: archive.len(),
+    /// Java releases with overlays in `META-INF/versions`, in ascending
+    /// order; empty unless the jar is multi-release.
+    pub fn releases(&self) -> Vec<u16> {
+        if !self.multi_release {
+            return vec![];
+        }
+        let mut result: Vec<_> = self
+            .entries
+            .iter()
+            .filter_map(|it| versioned_name(it))
+            .map(|(release, _)| release)
+            .collect();
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+
+    /// Entries a runtime of `release` sees, in archive order; `None` only
+    /// sees base entries.
+    fn resolve(&self, release: Option<u16>) -> Vec<ResolvedEntry<'_>> {
+        let mut result: Vec<ResolvedEntry> = Vec::with_capacity(self.entries.len());
+        let mut positions: HashMap<&str, usize> = HashMap::with_capacity(self.entries.len());
+        for (index, entry) in self.entries.iter().enumerate() {
+            let Some(entry) = self.application_name(entry) else {
+                continue;
+            };
+            let resolved = match versioned_name(entry).filter(|_| self.multi_release) {
+                Some((version, name)) if release.is_some_and(|it| version <= it) => ResolvedEntry {
+                    name,
+                    index,
+                    release: Some(version),
+                },
+                Some(_) => continue,
+                None => ResolvedEntry {
+                    name: entry,
+                    index,
+                    release: None,
+                },
+            };
+            if resolved.name.is_empty() {
+                continue;
+            }
+            match positions.get(resolved.name) {
+                Some(&position) => {
+                    // the newest applicable overlay wins
+                    if resolved.release > result[position].release {
+                        result[position] = resolved;
+                    }
+                }
+                None => {
+                    positions.insert(resolved.name, result.len());
+                    result.push(resolved);
+                }
+            }
+        }
+        result
+    }
+
+    /// Variants of application entry `name` as `(release, entry name)`
+    /// pairs.
+    fn find_variants(&self, name: &str) -> impl Iterator<Item = (Option<u16>, &str)> + '_ {
+        let name = name.to_string();
+        self.entries.iter().filter_map(move |entry| {
+            let overlaid = self.application_name(entry)?;
+            match versioned_name(overlaid).filter(|_| self.multi_release) {
+                Some((release, overlaid)) => {
+                    (overlaid == name).then_some((Some(release), entry.as_str()))
+                }
+                None => (overlaid == name).then_some((None, entry.as_str())),
+            }
         })
     }
 
-    pub fn classes(&self) -> Classes {
-        Classes {
-            last: self.file_count - 1,
-            current: 0,
+    /// Name of the entry read for `name` with the selected release.
+    fn resolve_name(&self, name: &str) -> Option<String> {
+        if !self.multi_release && self.layout.class_root().is_empty() {
+            return self.application_name(name).map(str::to_string);
+        }
+        let release = self.release.release();
+        self.find_variants(name)
+            .filter(|(version, _)| {
+                version.is_none() || release.is_some_and(|it| *version <= Some(it))
+            })
+            .max_by_key(|(version, _)| *version)
+            .map(|(_, entry)| entry.to_string())
+    }
 
-            over: self,
+    /// Names of entries other than classes and directories as seen by the
+    /// selected release, in archive order.
+    pub fn resources(&self) -> impl Iterator<Item = &str> {
+        self.resolve(self.release.release())
+            .into_iter()
+            .map(|it| it.name)
+            .filter(|it| !it.ends_with('/') && !it.ends_with(".class"))
+    }
+
+    /// Variants of entry `name` as `(release, entry name)` pairs, starting
+    /// with the base entry (`None` release) if there is one.
+    pub fn variants(&self, name: &str) -> Vec<(Option<u16>, &str)> {
+        let mut result: Vec<_> = self.find_variants(name).collect();
+        result.sort_by_key(|(release, _)| *release);
+        result
+    }
+
+    /// Reads all variants of class `name`, starting with the base one.
+    pub fn class_variants(
+        &self,
+        name: &ClassPath,
+    ) -> Result<Vec<(Option<u16>, Class)>, DecompileError> {
+        let mut archive = self.archive.borrow_mut();
+        let mut result = Vec::new();
+        for (release, entry) in self.variants(&name.jar_path()) {
+            result.push((release, Class::read_from(&mut archive.by_name(entry)?)?));
         }
+        Ok(result)
     }
 
-    /*
-    pub fn decompile(&mut self) {
-        for file_number in 0..self.archive.len() {
-            let mut f = &self.archive.by_index(file_number)?;
+    /// Reads contents of entry `name` as seen by the selected release; `None`
+    /// if there's no such entry.
+    pub fn read_entry(&self, name: &str) -> Result<Option<Vec<u8>>, DecompileError> {
+        let Some(name) = self.resolve_name(name) else {
+            return Ok(None);
+        };
+        let mut archive = self.archive.borrow_mut();
+        let mut entry = match archive.by_name(&name) {
+            Ok(it) => it,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let mut contents = Vec::with_capacity(entry.size() as usize);
+        entry.read_to_end(&mut contents)?;
+        Ok(Some(contents))
+    }
+
+    /// Reads class `name` as seen by the selected release; `None` if the jar
+    /// doesn't contain it.
+    pub fn class(&self, name: &ClassPath) -> Result<Option<Class>, DecompileError> {
+        let Some(entry_name) = self.resolve_name(&name.jar_path()) else {
+            return Ok(None);
+        };
+        let mut archive = self.archive.borrow_mut();
+        let mut entry = match archive.by_name(&entry_name) {
+            Ok(it) => it,
+            Err(ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Some(Class::read_from(&mut entry)?))
+    }
 
-            if f.name().ends_with('/') { // Directory
-                // Do nothing
-            } else if f.name().ends_with(".class") {
-                let mut content = Vec::with_capacity(f.size() as usize);
-                f.read_to_end(&mut content)?;
+    /// Iterates over classes as seen by the selected release in archive
+    /// order, skipping ones that can't be read.
+    pub fn classes(&self) -> Classes<'_> {
+        Classes {
+            over: self,
+            entries: self
+                .resolve(self.release.release())
+                .into_iter()
+                .filter(|it| it.name.ends_with(".class"))
+                .map(|it| it.index)
+                .collect::<Vec<_>>()
+                .into_iter(),
+        }
+    }
 
-                let mut reader = Cursor::new(content);
+    /// Decompiles all classes into a source tree in `dir`, and copies other
+    /// entries next to them.
+    pub fn decompile_to(
+        &self,
+        dir: impl AsRef<Path>,
+        lang: &JavaContext,
+        batch: &BatchDecompiler,
+    ) -> Result<DecompileReport, DecompileError> {
+        self.decompile_into(&mut DirectorySink::new(dir), lang, batch)
+    }
 
-                let class = Class::read_from(&mut reader).unwrap();
+    /// Decompiles all classes into a sources jar at `path` (e.g.
+    /// `foo-1.0-sources.jar`), along with other entries.
+    pub fn decompile_to_jar(
+        &self,
+        path: impl AsRef<Path>,
+        lang: &JavaContext,
+        batch: &BatchDecompiler,
+    ) -> Result<DecompileReport, DecompileError> {
+        let mut sink = JarSink::create(path)?;
+        let report = self.decompile_into(&mut sink, lang, batch)?;
+        sink.finish()?;
+        Ok(report)
+    }
 
-                println!("{}:", f.name());
-                println!("CP: {:?}", class.constant_pool);
-                println!("Attributes: {:?}", class.attributes);
-                println!("Fields: {:?}", class.fields);
-                println!("Methods: {:?}", class.methods);
+    /// Decompiles all classes into a source tree written to `sink`, and
+    /// copies other entries next to them.
+    ///
+    /// Nested classes are written as members of their outer class. Entries
+    /// that can't be decompiled or copied are listed in the returned report
+    /// instead of stopping the process. Classes are generated by `batch`
+    /// workers, but written in archive order.
+    ///
+    /// Multi-release jars are written as seen by the selected release; with
+    /// [`ReleaseSelection::All`], overlays of each release are also written
+    /// into `META-INF/versions/N`.
+    ///
+    /// Only application classes are decompiled from Spring Boot jars and
+    /// WARs, while classes of nested libraries are used to resolve names.
+    /// Modules of EARs are decompiled into directories named after them.
+    pub fn decompile_into(
+        &self,
+        sink: &mut dyn SourceSink,
+        lang: &JavaContext,
+        batch: &BatchDecompiler,
+    ) -> Result<DecompileReport, DecompileError> {
+        let mut report = DecompileReport::default();
 
-                let mut output = File::create(format!("ref/decomp/{}.java", class.class_name.name))?;
+        let libraries = self.library_names(&mut report);
+        let lang = &if libraries.is_empty() {
+            lang.clone()
+        } else {
+            let mut class_index = (*lang.class_index).clone();
+            class_index.extend(&libraries);
+            JavaContext {
+                class_index: Arc::new(class_index),
+                ..lang.clone()
+            }
+        };
 
-                let jg = JavaGenerator {
-                    target_version: JavaVersion::Java16,
-                    header_message: None
-                };
+        let entries = self.resolve(self.release.release());
+        self.decompile_entries(&entries, None, sink, lang, batch, &mut report);
 
-                jg.generate(&class, &mut output);
-                output.flush()?;
-            } else {
-                println!("{}", f.name());
+        if self.release == ReleaseSelection::All {
+            for release in self.releases() {
+                let entries = self.resolve(Some(release));
+                self.decompile_entries(&entries, Some(release), sink, lang, batch, &mut report);
             }
         }
+
+        for module in self.modules() {
+            let nested = match self.open_nested(module) {
+                Ok(it) => it.with_release(self.release),
+                Err(err) => {
+                    report.fail(module.to_string(), err);
+                    continue;
+                }
+            };
+            tracing::info!("decompiling module {} ({:?})", module, nested.layout());
+            let mut module_sink = PrefixedSink::new(sink, module);
+            match nested.decompile_into(&mut module_sink, lang, batch) {
+                Ok(it) => report.include(module, it),
+                Err(err) => report.fail(module.to_string(), err),
+            }
+        }
+
+        Ok(report)
     }
-     */
-}
 
-pub struct Classes<'a> {
-    pub over: &'a Jar,
-    pub last: usize,
+    /// Indexes names of classes in nested libraries; ones that can't be read
+    /// are reported.
+    ///
+    /// Only names are indexed, library classes aren't read, so methods they
+    /// declare aren't known when removing implicit code of calls to them.
+    fn library_names(&self, report: &mut DecompileReport) -> ClassIndex {
+        let mut result = ClassIndex::default();
+        for name in self.libraries() {
+            match self.nested_entry_names(name) {
+                Ok(entries) => {
+                    for entry in &entries {
+                        result.add_entry(entry);
+                    }
+                }
+                Err(err) => report.fail(name.to_string(), err),
+            }
+        }
+        result
+    }
 
-    pub current: usize,
-}
+    /// Names of entries in nested archive `entry`.
+    ///
+    /// Archives stored without compression in a jar opened from a file (like
+    /// libraries of Spring Boot jars) only have their central directory read,
+    /// others are read into memory.
+    fn nested_entry_names(&self, entry: &str) -> Result<Vec<String>, DecompileError> {
+        let (stored, start, len) = {
+            let mut archive = self.archive.borrow_mut();
+            let nested = archive.by_name(entry)?;
+            (
+                nested.compression() == CompressionMethod::Stored,
+                nested.data_start(),
+                nested.size(),
+            )
+        };
+        match &self.path {
+            Some(path) if stored => {
+                let window = EntryWindow::new(File::open(path)?, start, len);
+                let archive = ZipArchive::new(window)?;
+                Ok(archive.file_names().map(str::to_string).collect())
+            }
+            _ => Ok(self.open_nested(entry)?.entries),
+        }
+    }
 
-impl<'a> Iterator for Classes<'a> {
-    type Item = Class;
+    /// Decompiles `entries` of a single release into `sink`.
+    ///
+    /// With `overlay`, only entries from that release's overlay are written,
+    /// into its versioned directory, while the rest are only used for lookup
+    /// of nested classes and accessors.
+    fn decompile_entries(
+        &self,
+        entries: &[ResolvedEntry],
+        overlay: Option<u16>,
+        sink: &mut dyn SourceSink,
+        lang: &JavaContext,
+        batch: &BatchDecompiler,
+        report: &mut DecompileReport,
+    ) {
+        let root = match overlay {
+            Some(release) => PathBuf::from(format!("{}{}", VERSIONS_PREFIX, release)),
+            None => PathBuf::new(),
+        };
+        let mut archive = self.archive.borrow_mut();
 
-    fn next(&mut self) -> Option<Class> {
-        if self.current > self.last {
-            return None;
+        let mut classes = Vec::with_capacity(entries.len());
+        // whether classes are written
+        let mut selected = Vec::with_capacity(entries.len());
+        for resolved in entries {
+            let written = overlay.is_none() || resolved.release == overlay;
+            let mut entry = match archive.by_index(resolved.index) {
+                Ok(it) => it,
+                Err(err) => {
+                    if written {
+                        report.fail(self.entries[resolved.index].clone(), err.into());
+                    }
+                    continue;
+                }
+            };
+            if entry.is_dir() {
+                continue;
+            }
+            let name = entry.name().to_string();
+            if name.ends_with(".class") {
+                match Class::read_from(&mut entry) {
+                    Ok(class) => {
+                        classes.push(class);
+                        selected.push(written);
+                    }
+                    Err(err) if written => report.fail(name, err.into()),
+                    Err(_) => {}
+                }
+            } else if written {
+                match copy_entry(&mut entry, root.join(resolved.name), sink) {
+                    Ok(Some(path)) => report.resources.push(path),
+                    Ok(None) => {}
+                    Err(err) => report.fail(name, err),
+                }
+            }
         }
+        drop(archive);
 
-        let file = match File::open(self.over.path.clone()) {
-            Ok(f) => f,
-            Err(err) => {
-                tracing::error!(
-                    "unable to open zip file '{}'; error: {}",
-                    self.over.path.to_string_lossy(),
-                    err
-                );
-                return None;
+        let mut switch_maps = SwitchMaps::default();
+        let mut accessors = Accessors::default();
+        let mut class_index = (*lang.class_index).clone();
+        let mut method_index = (*lang.method_index).clone();
+        for class in &classes {
+            switch_maps.add_class(class);
+            accessors.add_class(class);
+            // `module-info` and `package-info` aren't classes
+            if !class.is_module_info() && !class.is_package_info() {
+                class_index.add(&class.class_name);
+            }
+            method_index.add_class(class);
+        }
+        let top_level_names: HashSet<_> = classes
+            .iter()
+            .filter(|it| it.class_name.inner_classes.is_empty())
+            .map(|it| it.class_name.clone())
+            .collect();
+        // entry of a written nested class, for reporting it
+        let entry_name = |name: &ClassPath| {
+            let entry = name.internal_name() + ".class";
+            match overlay {
+                Some(release) => format!("{}{}/{}", VERSIONS_PREFIX, release, entry),
+                None => entry,
             }
         };
-        let mut archive = match ZipArchive::new(file) {
-            Ok(a) => a,
-            Err(err) => {
-                tracing::error!(
-                    "unable to open zip archive ('{}'): {}",
-                    self.over.path.to_string_lossy(),
-                    err
+        let top_level_of = |name: &ClassPath| ClassPath {
+            inner_classes: vec![],
+            ..name.clone()
+        };
+        // a release overlaying only nested classes still needs their outer
+        // class regenerated into the overlay for them to be written
+        let overlaid_outers: HashSet<_> = classes
+            .iter()
+            .zip(&selected)
+            .filter(|(it, written)| **written && !it.class_name.inner_classes.is_empty())
+            .map(|(it, _)| top_level_of(&it.class_name))
+            .collect();
+
+        let mut top_level = Vec::new();
+        let mut nested_classes = NestedClasses::default();
+        let mut written_nested = Vec::new();
+        for (class, written) in classes.into_iter().zip(selected) {
+            if class.class_name.inner_classes.is_empty() {
+                if written || overlaid_outers.contains(&class.class_name) {
+                    top_level.push(class);
+                }
+                continue;
+            }
+            if written {
+                let outer = top_level_of(&class.class_name);
+                if top_level_names.contains(&outer) {
+                    written_nested.push((class.class_name.clone(), class.access_flags));
+                } else {
+                    report.fail(
+                        entry_name(&class.class_name),
+                        DecompileError::MissingOuterClass(outer),
+                    );
+                }
+            }
+            nested_classes.add_class(class);
+        }
+        // local and anonymous classes aren't written within their outer
+        // class; synthetic ones (e.g. switch maps) are folded into it instead,
+        // and bodies of enum constants are written with the constants
+        for (name, flags) in written_nested {
+            if !flags.contains(ClassAccessFlags::SYNTHETIC)
+                && !nested_classes.is_member(&name)
+                && !nested_classes.is_enum_body(&name)
+            {
+                report.fail(
+                    entry_name(&name),
+                    DecompileError::UnsupportedNestedClass(name),
                 );
-                return None;
             }
+        }
+
+        let lang = JavaContext {
+            switch_maps: Arc::new(switch_maps),
+            accessors: Arc::new(accessors),
+            nested_classes: Arc::new(nested_classes),
+            class_index: Arc::new(class_index),
+            method_index: Arc::new(method_index),
+            ..lang.clone()
         };
+        let stats = batch.for_each(&lang, &top_level, |class, result| {
+            let path = root.join(source_path(class));
+            match result.and_then(|source| write_source(sink, &path, &source)) {
+                Ok(()) => report.sources.push(path),
+                Err(err) => report.fail(class.class_name.jar_path(), err),
+            }
+        });
+        report.stats.merge(stats);
+    }
+}
+
+pub struct Classes<'a> {
+    over: &'a Jar,
+    /// Indices of remaining class entries.
+    entries: std::vec::IntoIter<usize>,
+}
+
+impl Iterator for Classes<'_> {
+    type Item = Class;
 
-        loop {
-            let mut zip_file = match archive.by_index(self.current) {
-                Ok(a) => a,
+    fn next(&mut self) -> Option<Class> {
+        let mut archive = self.over.archive.borrow_mut();
+        for index in self.entries.by_ref() {
+            let mut zip_file = match archive.by_index(index) {
+                Ok(it) => it,
                 Err(err) => {
                     tracing::error!(
                         "unable to open ZIP file entry with index {}: {}",
-                        self.current,
+                        index,
                         err
                     );
-                    return None;
+                    continue;
                 }
             };
+            match Class::read_from(&mut zip_file) {
+                Ok(class) => return Some(class),
+                Err(err) => tracing::error!("unable to read class '{}': {}", zip_file.name(), err),
+            }
+        }
+        None
+    }
+}
 
-            self.current += 1;
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
 
-            let name = zip_file.name();
-            if name.ends_with(".class") {
-                let class = match Class::read_from(&mut zip_file) {
-                    Ok(c) => c,
-                    Err(err) => {
-                        tracing::error!("unable to read class '{}': {}", zip_file.name(), err);
-                        return None;
-                    }
-                };
+    use zip::write::FileOptions;
+    use zip::ZipWriter;
 
-                return Some(class);
-            }
+    use super::*;
+    use crate::gen::java::lines::{LineMap, LineMapping};
+
+    #[test]
+    fn library_jar_without_manifest() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.add_directory("com/example/", FileOptions::default())
+            .unwrap();
+        zip.start_file("com/example/messages.properties", FileOptions::default())
+            .unwrap();
+        zip.write_all(b"greeting=hi\n").unwrap();
+        let jar = Jar::from_reader(zip.finish().unwrap()).unwrap();
+
+        assert!(jar.manifest.is_none());
+        assert_eq!(
+            jar.entry_names().collect::<Vec<_>>(),
+            ["com/example/", "com/example/messages.properties"]
+        );
+        assert_eq!(
+            jar.resources().collect::<Vec<_>>(),
+            ["com/example/messages.properties"]
+        );
+        assert_eq!(
+            jar.read_entry("com/example/messages.properties").unwrap(),
+            Some(b"greeting=hi\n".to_vec())
+        );
+        let missing = ClassPath::parse("com/example/Missing").unwrap();
+        assert!(jar.class(&missing).unwrap().is_none());
+        assert_eq!(jar.classes().count(), 0);
+    }
+
+    #[test]
+    fn multi_release_overlays() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in [
+            (
+                MANIFEST_PATH,
+                "Manifest-Version: 1.0\r\nMulti-Release: true\r\n\r\n",
+            ),
+            ("conf.txt", "base"),
+            ("META-INF/versions/9/conf.txt", "9"),
+            ("META-INF/versions/11/conf.txt", "11"),
+            ("META-INF/versions/11/extra.txt", "extra"),
+        ] {
+            zip.start_file(name, FileOptions::default()).unwrap();
+            zip.write_all(contents.as_bytes()).unwrap();
         }
+        let mut jar = Jar::from_reader(zip.finish().unwrap()).unwrap();
+
+        assert!(jar.is_multi_release());
+        assert_eq!(jar.releases(), [9, 11]);
+        assert_eq!(
+            jar.variants("conf.txt"),
+            [
+                (None, "conf.txt"),
+                (Some(9), "META-INF/versions/9/conf.txt"),
+                (Some(11), "META-INF/versions/11/conf.txt"),
+            ]
+        );
+
+        let read = |jar: &Jar, name| jar.read_entry(name).unwrap().map(String::from_utf8);
+        assert_eq!(read(&jar, "conf.txt"), Some(Ok("base".to_string())));
+        assert_eq!(
+            jar.resources().collect::<Vec<_>>(),
+            [MANIFEST_PATH, "conf.txt"]
+        );
+
+        jar.select_release(ReleaseSelection::Release(10));
+        assert_eq!(read(&jar, "conf.txt"), Some(Ok("9".to_string())));
+        assert_eq!(read(&jar, "extra.txt"), None);
+
+        jar.select_release(ReleaseSelection::Release(17));
+        assert_eq!(read(&jar, "conf.txt"), Some(Ok("11".to_string())));
+        assert_eq!(
+            jar.resources().collect::<Vec<_>>(),
+            [MANIFEST_PATH, "conf.txt", "extra.txt"]
+        );
+    }
+
+    /// Bytes of an empty class file declaring class `name`.
+    fn class_file(name: &str, major: u16) -> Vec<u8> {
+        let mut result = vec![0xCA, 0xFE, 0xBA, 0xBE, 0, 0];
+        result.extend(major.to_be_bytes());
+        // constant pool of the class and superclass names
+        result.extend(5u16.to_be_bytes());
+        for name in [name, "java/lang/Object"] {
+            result.push(1);
+            result.extend((name.len() as u16).to_be_bytes());
+            result.extend(name.as_bytes());
+        }
+        result.extend([7, 0, 1, 7, 0, 2]);
+        // flags, this, super, then no interfaces, fields, methods or attributes
+        result.extend([0, 0x20, 0, 3, 0, 4]);
+        result.extend([0; 8]);
+        result
+    }
+
+    #[test]
+    fn class_lookup() {
+        let name = ClassPath::parse("com/example/Foo").unwrap();
+        let jar = Jar::from_reader(Cursor::new(archive(&[(
+            "com/example/Foo.class",
+            &class_file("com/example/Foo", 52),
+        )])))
+        .unwrap();
+
+        let class = jar.class(&name).unwrap().expect("class wasn't found");
+        assert_eq!(class.class_name, name);
+    }
+
+    #[test]
+    fn package_info_detection() {
+        let interface = |name: &str| {
+            let mut bytes = class_file(name, 52);
+            // interface, abstract and synthetic, like compiled `package-info`
+            let flags = bytes.len() - 14;
+            bytes[flags..flags + 2].copy_from_slice(&0x1600u16.to_be_bytes());
+            Class::read(bytes).unwrap()
+        };
+
+        let package_info = interface("com/example/package-info");
+        assert!(package_info.is_package_info());
+        assert_eq!(
+            source_path(&package_info),
+            Path::new("com/example/package-info.java")
+        );
+        // class paths end at the `-`, but the raw name doesn't
+        assert!(!interface("com/example/package").is_package_info());
+    }
+
+    #[test]
+    fn class_variant_lookup() {
+        let name = ClassPath::parse("com/example/Foo").unwrap();
+        let manifest = b"Manifest-Version: 1.0\r\nMulti-Release: true\r\n\r\n";
+        let jar = Jar::from_reader(Cursor::new(archive(&[
+            (MANIFEST_PATH, manifest),
+            ("com/example/Foo.class", &class_file("com/example/Foo", 52)),
+            (
+                "META-INF/versions/11/com/example/Foo.class",
+                &class_file("com/example/Foo", 55),
+            ),
+        ])))
+        .unwrap();
+
+        let variants = jar.class_variants(&name).unwrap();
+        let versions: Vec<_> = variants
+            .iter()
+            .map(|(release, class)| (*release, class.compiler_info.major))
+            .collect();
+        assert_eq!(versions, [(None, 52), (Some(11), 55)]);
+        assert!(variants.iter().all(|(_, class)| class.class_name == name));
+    }
+
+    /// Sink keeping written entries in memory.
+    #[derive(Default)]
+    struct MemorySink(Vec<(PathBuf, Vec<u8>)>);
+
+    impl SourceSink for MemorySink {
+        fn write_entry(&mut self, path: &Path, contents: &[u8]) -> Result<(), DecompileError> {
+            self.0.push((path.to_path_buf(), contents.to_vec()));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn line_maps_next_to_sources() {
+        let mut sink = MemorySink::default();
+        let source = ClassSource {
+            source: b"class Foo {}\n".to_vec(),
+            line_map: LineMap(vec![LineMapping {
+                output_line: 3,
+                method: "run()V".to_string(),
+                offset: 4,
+                original_line: Some(7),
+                header_lines: 0,
+            }]),
+            skipped_methods: vec![],
+        };
+        write_source(&mut sink, Path::new("com/example/Foo.java"), &source).unwrap();
+        write_source(&mut sink, Path::new("Bar.java"), &ClassSource::default()).unwrap();
+
+        assert_eq!(
+            sink.0,
+            [
+                ("com/example/Foo.java".into(), b"class Foo {}\n".to_vec()),
+                (
+                    "com/example/Foo.java.linemap".into(),
+                    b"3\trun()V\t4\t7\n".to_vec()
+                ),
+                ("Bar.java".into(), vec![]),
+            ]
+        );
+    }
+
+    #[test]
+    fn anonymous_classes_are_reported() {
+        let jar = Jar::from_reader(Cursor::new(archive(&[
+            ("com/example/Foo.class", &class_file("com/example/Foo", 52)),
+            (
+                "com/example/Foo$1.class",
+                &class_file("com/example/Foo$1", 52),
+            ),
+        ])))
+        .unwrap();
+        let mut sink = MemorySink::default();
+        let lang = crate::gen::GeneratorBuilder::java().build();
+        let report = jar
+            .decompile_into(&mut sink, &lang, &BatchDecompiler::new(1))
+            .unwrap();
+
+        let written: Vec<_> = sink.0.iter().map(|(path, _)| path).collect();
+        assert_eq!(written, [Path::new("com/example/Foo.java")]);
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(report.failures[0].entry, "com/example/Foo$1.class");
+        assert!(matches!(
+            report.failures[0].error,
+            DecompileError::UnsupportedNestedClass(_)
+        ));
+    }
+
+    #[test]
+    fn overlaid_nested_classes_regenerate_outer() {
+        let manifest = b"Manifest-Version: 1.0\r\nMulti-Release: true\r\n\r\n";
+        let jar = Jar::from_reader(Cursor::new(archive(&[
+            (MANIFEST_PATH, manifest),
+            ("com/example/Foo.class", &class_file("com/example/Foo", 52)),
+            (
+                "META-INF/versions/11/com/example/Foo$1.class",
+                &class_file("com/example/Foo$1", 55),
+            ),
+        ])))
+        .unwrap()
+        .with_release(ReleaseSelection::All);
+        let mut sink = MemorySink::default();
+        let lang = crate::gen::GeneratorBuilder::java().build();
+        let report = jar
+            .decompile_into(&mut sink, &lang, &BatchDecompiler::new(1))
+            .unwrap();
+
+        assert_eq!(
+            report.sources,
+            [
+                Path::new("com/example/Foo.java"),
+                Path::new("META-INF/versions/11/com/example/Foo.java"),
+            ]
+        );
+        // the overlaid class is reported rather than dropped
+        assert_eq!(report.failures.len(), 1);
+        assert_eq!(
+            report.failures[0].entry,
+            "META-INF/versions/11/com/example/Foo$1.class"
+        );
+    }
+
+    fn archive(entries: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        for (name, contents) in entries {
+            zip.start_file(*name, FileOptions::default()).unwrap();
+            zip.write_all(contents).unwrap();
+        }
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn spring_boot_layout() {
+        let library = archive(&[("org/lib/String.class", b"")]);
+        let jar = Jar::from_reader(Cursor::new(archive(&[
+            ("org/springframework/boot/loader/Launcher.class", b""),
+            ("BOOT-INF/classes/app.properties", b"x=1"),
+            ("BOOT-INF/lib/lib.jar", &library),
+        ])))
+        .unwrap();
+
+        assert_eq!(jar.layout(), ArchiveLayout::SpringBoot);
+        assert_eq!(
+            jar.libraries().collect::<Vec<_>>(),
+            ["BOOT-INF/lib/lib.jar"]
+        );
+        // loader classes and libraries aren't part of the application
+        assert_eq!(jar.resources().collect::<Vec<_>>(), ["app.properties"]);
+        assert_eq!(jar.classes().count(), 0);
+        assert_eq!(
+            jar.read_entry("app.properties").unwrap(),
+            Some(b"x=1".to_vec())
+        );
+
+        let index = jar.library_names(&mut DecompileReport::default());
+        assert!(index.contains("org.lib", "String"));
+    }
+
+    #[test]
+    fn stored_library_names() {
+        let library = archive(&[("org/lib/Util.class", b"")]);
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        zip.start_file("BOOT-INF/classes/app.properties", FileOptions::default())
+            .unwrap();
+        let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+        zip.start_file("BOOT-INF/lib/lib.jar", stored).unwrap();
+        zip.write_all(&library).unwrap();
+        // executable jars start with a launch script
+        let mut contents = b"#!/bin/sh\n".to_vec();
+        contents.extend(zip.finish().unwrap().into_inner());
+
+        let path = std::env::temp_dir().join(format!("jaded-{}-boot.jar", std::process::id()));
+        std::fs::write(&path, contents).unwrap();
+        let mut report = DecompileReport::default();
+        let index = Jar::open(&path).map(|it| it.library_names(&mut report));
+        std::fs::remove_file(&path).unwrap();
+
+        let index = index.unwrap();
+        assert!(report.failures.is_empty());
+        assert!(index.contains("org.lib", "Util"));
     }
 }
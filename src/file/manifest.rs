@@ -31,17 +31,20 @@ impl Manifest {
             let l = line.unwrap();
 
             if l.is_empty() {
-                if main_section.is_none() {
-                    main_section = Some(section.clone());
-                } else {
-                    entries.insert(
-                        section
+                // sections are separated by blank lines
+                if !section.is_empty() {
+                    if main_section.is_none() {
+                        main_section = Some(std::mem::take(&mut section));
+                    } else {
+                        let name = section
                             .get("Name")
                             .ok_or(ManifestParseError::InvalidEntry)?
-                            .clone(),
-                        Attributes(section.clone()),
-                    );
+                            .clone();
+                        entries.insert(name, Attributes(std::mem::take(&mut section)));
+                    }
                 }
+                last_key = None;
+                continue;
             }
 
             match l.chars().next() {
@@ -79,13 +82,13 @@ impl Manifest {
 
         if main_section.is_none() {
             main_section = Some(section);
-        } else {
+        } else if !section.is_empty() {
             entries.insert(
                 section
                     .get("Name")
                     .ok_or(ManifestParseError::InvalidEntry)?
                     .clone(),
-                Attributes(section.clone()),
+                Attributes(section),
             );
         }
 
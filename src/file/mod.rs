@@ -1,2 +1,4 @@
+pub mod batch;
 pub mod jar;
 pub mod manifest;
+pub mod output;
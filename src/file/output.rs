@@ -0,0 +1,189 @@
+//! Destinations of decompiled source trees.
+
+use std::fs::File;
+use std::io::{Seek, Write};
+use std::path::{Component, Path, PathBuf};
+
+use zip::write::FileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+use crate::error::DecompileError;
+
+/// Path of the manifest in jars.
+pub const MANIFEST_PATH: &str = "META-INF/MANIFEST.MF";
+
+/// Destination of generated sources and copied resources.
+pub trait SourceSink {
+    /// Writes an entry at `path`, relative to the output root.
+    fn write_entry(&mut self, path: &Path, contents: &[u8]) -> Result<(), DecompileError>;
+
+    /// Whether a resource at `path` should be copied from the input.
+    fn keeps_resource(&self, _path: &Path) -> bool {
+        true
+    }
+}
+
+/// Writes entries as files in a directory.
+#[derive(Debug, Clone)]
+pub struct DirectorySink {
+    root: PathBuf,
+}
+
+impl DirectorySink {
+    pub fn new(root: impl AsRef<Path>) -> DirectorySink {
+        DirectorySink {
+            root: root.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SourceSink for DirectorySink {
+    fn write_entry(&mut self, path: &Path, contents: &[u8]) -> Result<(), DecompileError> {
+        let path = self.root.join(path);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        File::create(path)?.write_all(contents)?;
+        Ok(())
+    }
+}
+
+/// Writes entries into a directory of another sink; used for nested
+/// archives.
+pub struct PrefixedSink<'a> {
+    inner: &'a mut dyn SourceSink,
+    prefix: PathBuf,
+}
+
+impl<'a> PrefixedSink<'a> {
+    pub fn new(inner: &'a mut dyn SourceSink, prefix: impl AsRef<Path>) -> PrefixedSink<'a> {
+        PrefixedSink {
+            inner,
+            prefix: prefix.as_ref().to_path_buf(),
+        }
+    }
+}
+
+impl SourceSink for PrefixedSink<'_> {
+    fn write_entry(&mut self, path: &Path, contents: &[u8]) -> Result<(), DecompileError> {
+        self.inner.write_entry(&self.prefix.join(path), contents)
+    }
+
+    fn keeps_resource(&self, path: &Path) -> bool {
+        self.inner.keeps_resource(&self.prefix.join(path))
+    }
+}
+
+/// Writes entries into a jar (e.g. `foo-1.0-sources.jar`) with a generated
+/// manifest.
+///
+/// Entries get a fixed timestamp and permissions, so the same sources always
+/// produce the same archive.
+pub struct JarSink<W: Write + Seek> {
+    zip: ZipWriter<W>,
+}
+
+impl JarSink<File> {
+    pub fn create(path: impl AsRef<Path>) -> Result<JarSink<File>, DecompileError> {
+        JarSink::new(File::create(path)?)
+    }
+}
+
+impl<W: Write + Seek> JarSink<W> {
+    pub fn new(w: W) -> Result<JarSink<W>, DecompileError> {
+        let mut result = JarSink {
+            zip: ZipWriter::new(w),
+        };
+        let manifest = format!(
+            "Manifest-Version: 1.0\r\nCreated-By: {} {}\r\n\r\n",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        );
+        result.write_entry(Path::new(MANIFEST_PATH), manifest.as_bytes())?;
+        Ok(result)
+    }
+
+    /// Writes the central directory, returning the underlying writer.
+    pub fn finish(mut self) -> Result<W, DecompileError> {
+        Ok(self.zip.finish()?)
+    }
+}
+
+impl<W: Write + Seek> SourceSink for JarSink<W> {
+    fn write_entry(&mut self, path: &Path, contents: &[u8]) -> Result<(), DecompileError> {
+        let options = FileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .last_modified_time(DateTime::default())
+            .unix_permissions(0o644);
+        self.zip.start_file(entry_name(path)?, options)?;
+        self.zip.write_all(contents)?;
+        Ok(())
+    }
+
+    /// The manifest is generated and signatures don't apply to sources.
+    fn keeps_resource(&self, path: &Path) -> bool {
+        let Ok(name) = entry_name(path) else {
+            return false;
+        };
+        let signature = name.strip_prefix("META-INF/").is_some_and(|it| {
+            !it.contains('/')
+                && [".SF", ".RSA", ".DSA", ".EC"]
+                    .iter()
+                    .any(|ext| it.ends_with(ext))
+        });
+        name != MANIFEST_PATH && !signature
+    }
+}
+
+/// Name of a zip entry at relative `path`, with `/` separators.
+fn entry_name(path: &Path) -> Result<String, DecompileError> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(it) => parts.push(it.to_string_lossy()),
+            _ => return Err(DecompileError::UnsafePath),
+        }
+    }
+    Ok(parts.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    fn sources_jar() -> Vec<u8> {
+        let mut sink = JarSink::new(Cursor::new(Vec::new())).unwrap();
+        sink.write_entry(Path::new("com/example/Unit.java"), b"class Unit {}\n")
+            .unwrap();
+        sink.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn sources_jar_is_deterministic() {
+        let jar = sources_jar();
+        assert_eq!(jar, sources_jar());
+
+        let mut archive = zip::ZipArchive::new(Cursor::new(jar)).unwrap();
+        let names: Vec<_> = (0..archive.len())
+            .map(|i| archive.by_index(i).unwrap().name().to_string())
+            .collect();
+        assert_eq!(names, [MANIFEST_PATH, "com/example/Unit.java"]);
+        let entry = archive.by_name("com/example/Unit.java").unwrap();
+        let modified = entry.last_modified();
+        assert_eq!(
+            (modified.year(), modified.month(), modified.day()),
+            (1980, 1, 1)
+        );
+    }
+
+    #[test]
+    fn sources_jar_leaves_out_manifest_and_signatures() {
+        let sink = JarSink::new(Cursor::new(Vec::new())).unwrap();
+        assert!(!sink.keeps_resource(Path::new(MANIFEST_PATH)));
+        assert!(!sink.keeps_resource(Path::new("META-INF/SIGNER.SF")));
+        assert!(sink.keeps_resource(Path::new("META-INF/services/foo.Bar")));
+        assert!(sink.keeps_resource(Path::new("conf/app.properties")));
+    }
+}
@@ -0,0 +1,78 @@
+//! Generation of annotations.
+
+use std::io::Write;
+
+use crate::gen::{
+    java::{
+        ast::{Annotation, AnnotationValue, Type},
+        code::ExprContext,
+        JavaBackend, JavaContext, JavaScopeRequirements, LanguageFeature,
+    },
+    GenerateCode,
+};
+
+impl GenerateCode<Annotation> for JavaBackend {
+    fn write_value<W: Write>(
+        &self,
+        lang: &JavaContext,
+        _: &(),
+        annotation: &Annotation,
+        w: &mut W,
+    ) -> Result<Self::ScopeRequirements, std::io::Error> {
+        let mut req = JavaScopeRequirements::default();
+        req.require(LanguageFeature::Annotations);
+
+        w.write_all(b"@")?;
+        req.include(self.write_value(lang, &(), &Type::Class(annotation.ty.clone()), w)?);
+
+        match annotation.elements.as_slice() {
+            [] => {}
+            // single element annotation (`@Foo(1)`)
+            [(name, value)] if name == "value" => {
+                w.write_all(b"(")?;
+                req.include(self.write_value(lang, &(), value, w)?);
+                w.write_all(b")")?;
+            }
+            elements => {
+                w.write_all(b"(")?;
+                for (i, (name, value)) in elements.iter().enumerate() {
+                    if i > 0 {
+                        w.write_all(b", ")?;
+                    }
+                    write!(w, "{} = ", name)?;
+                    req.include(self.write_value(lang, &(), value, w)?);
+                }
+                w.write_all(b")")?;
+            }
+        }
+
+        Ok(req)
+    }
+}
+
+impl GenerateCode<AnnotationValue> for JavaBackend {
+    fn write_value<W: Write>(
+        &self,
+        lang: &JavaContext,
+        _: &(),
+        value: &AnnotationValue,
+        w: &mut W,
+    ) -> Result<Self::ScopeRequirements, std::io::Error> {
+        match value {
+            AnnotationValue::Expr(it) => self.write_value(lang, &ExprContext::default(), it, w),
+            AnnotationValue::Annotation(it) => self.write_value(lang, &(), it, w),
+            AnnotationValue::Array(values) => {
+                let mut req = JavaScopeRequirements::default();
+                w.write_all(b"{")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        w.write_all(b", ")?;
+                    }
+                    req.include(self.write_value(lang, &(), value, w)?);
+                }
+                w.write_all(b"}")?;
+                Ok(req)
+            }
+        }
+    }
+}
@@ -0,0 +1,612 @@
+//! Typed Java syntax tree.
+//!
+//! Classes are lowered from IR into this tree (see [`lower`](super::lower)),
+//! optionally rewritten by [`RewritePass`](super::rewrite::RewritePass)es and
+//! then printed by the [`GenerateCode`](crate::gen::GenerateCode)
+//! implementations of [`JavaBackend`](super::JavaBackend).
+
+use jvm_class_format::{ClassPath, JVMPrimitive, JVMType};
+
+use crate::gen::java::method::SkippedMethod;
+use crate::ir::value::{self, Literal};
+
+/// Bytecode a node was generated from; used for line mapping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    /// Name and descriptor of the method, e.g. `run(I)V`.
+    pub method: String,
+    /// Offset of the first instruction in method bytecode.
+    pub offset: usize,
+    /// Line in the original source, if the class contains line numbers.
+    pub original_line: Option<u16>,
+}
+
+/// A single source file.
+#[derive(Debug, Clone, Default)]
+pub struct CompilationUnit {
+    /// Package name in source form (e.g. `java.util`); empty for the default
+    /// package.
+    pub package: String,
+    pub types: Vec<TypeDecl>,
+    /// Module declared by a `module-info.java` unit.
+    pub module: Option<ModuleDecl>,
+    /// Annotations of the package declaration (`package-info.java`).
+    pub package_annotations: Vec<Annotation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub ty: ClassPath,
+    pub elements: Vec<(String, AnnotationValue)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnnotationValue {
+    /// Constant, enum constant or class literal.
+    Expr(Expr),
+    Annotation(Annotation),
+    /// Array of values (`{a, b}`).
+    Array(Vec<AnnotationValue>),
+}
+
+/// Module declaration (`module-info.java`).
+#[derive(Debug, Clone)]
+pub struct ModuleDecl {
+    /// Whether all packages are open for reflection (`open module`).
+    pub open: bool,
+    pub name: String,
+    pub directives: Vec<ModuleDirective>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModuleDirective {
+    Requires {
+        module: String,
+        transitive: bool,
+        is_static: bool,
+    },
+    /// Packages are in source form (`java.lang`); empty `to` means all
+    /// modules.
+    Exports {
+        package: String,
+        to: Vec<String>,
+    },
+    Opens {
+        package: String,
+        to: Vec<String>,
+    },
+    Uses(ClassPath),
+    Provides {
+        service: ClassPath,
+        with: Vec<ClassPath>,
+    },
+}
+
+impl ModuleDirective {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            ModuleDirective::Requires { .. } => "requires",
+            ModuleDirective::Exports { .. } => "exports",
+            ModuleDirective::Opens { .. } => "opens",
+            ModuleDirective::Uses(_) => "uses",
+            ModuleDirective::Provides { .. } => "provides",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modifier {
+    Public,
+    Protected,
+    Private,
+    Abstract,
+    Default,
+    Static,
+    Final,
+    Transient,
+    Volatile,
+    Synchronized,
+    Native,
+    Strictfp,
+}
+
+impl Modifier {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            Modifier::Public => "public",
+            Modifier::Protected => "protected",
+            Modifier::Private => "private",
+            Modifier::Abstract => "abstract",
+            Modifier::Default => "default",
+            Modifier::Static => "static",
+            Modifier::Final => "final",
+            Modifier::Transient => "transient",
+            Modifier::Volatile => "volatile",
+            Modifier::Synchronized => "synchronized",
+            Modifier::Native => "native",
+            Modifier::Strictfp => "strictfp",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypeKind {
+    Class,
+    Interface,
+    Enum,
+    Annotation,
+}
+
+impl TypeKind {
+    pub fn keyword(self) -> &'static str {
+        match self {
+            TypeKind::Class => "class",
+            TypeKind::Interface => "interface",
+            TypeKind::Enum => "enum",
+            TypeKind::Annotation => "@interface",
+        }
+    }
+}
+
+/// Class, interface, enum or annotation declaration.
+#[derive(Debug, Clone)]
+pub struct TypeDecl {
+    pub modifiers: Vec<Modifier>,
+    pub kind: TypeKind,
+    pub name: ClassPath,
+    pub extends: Option<Type>,
+    pub implements: Vec<Type>,
+    /// Simple names of member classes; they shadow imported classes.
+    pub member_classes: Vec<String>,
+    /// Constants of an enum, declared before other members.
+    pub enum_constants: Vec<EnumConstant>,
+    pub members: Vec<MemberDecl>,
+    /// Methods left as assembly because they exceeded
+    /// [`MethodLimits`](crate::ir::MethodLimits).
+    pub skipped_methods: Vec<SkippedMethod>,
+}
+
+impl TypeDecl {
+    /// Skipped methods of this type and its member types.
+    pub fn all_skipped_methods(&self) -> Vec<SkippedMethod> {
+        let mut result = self.skipped_methods.clone();
+        for member in &self.members {
+            if let MemberDecl::Type(it) = member {
+                result.extend(it.all_skipped_methods());
+            }
+        }
+        result
+    }
+}
+
+/// Enum constant, e.g. `RED(0xFF0000)`.
+#[derive(Debug, Clone)]
+pub struct EnumConstant {
+    pub name: String,
+    /// Constructor arguments, without the implicit name and ordinal.
+    pub args: Vec<Expr>,
+    /// Members of the constant's class body, e.g. `RED { ... }`.
+    pub body: Option<Vec<MemberDecl>>,
+    /// Origin of the constant creation in `<clinit>`.
+    pub origin: Option<Origin>,
+}
+
+#[derive(Debug, Clone)]
+pub enum MemberDecl {
+    Field(FieldDecl),
+    Method(MethodDecl),
+    Initializer(Initializer),
+    /// Member class.
+    Type(TypeDecl),
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldDecl {
+    pub modifiers: Vec<Modifier>,
+    pub ty: Type,
+    pub name: String,
+    pub initializer: Option<Expr>,
+    /// Origin of an initializer hoisted out of a method.
+    pub origin: Option<Origin>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub ty: Type,
+    pub name: String,
+}
+
+/// Method or constructor declaration.
+#[derive(Debug, Clone)]
+pub struct MethodDecl {
+    /// Comment written above the declaration.
+    pub comment: Option<String>,
+    pub modifiers: Vec<Modifier>,
+    /// Return type; `None` for constructors.
+    pub return_type: Option<Type>,
+    pub name: String,
+    pub params: Vec<Param>,
+    /// Whether the last parameter is variable arity (`String... args`).
+    pub varargs: bool,
+    pub throws: Vec<Type>,
+    /// Body; `None` for abstract and native methods.
+    pub body: Option<Block>,
+}
+
+impl MethodDecl {
+    pub fn is_constructor(&self) -> bool {
+        self.return_type.is_none()
+    }
+}
+
+/// Static or instance initializer block.
+#[derive(Debug, Clone)]
+pub struct Initializer {
+    pub is_static: bool,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Type {
+    Primitive(JVMPrimitive),
+    Class(ClassPath),
+    /// Type variable (e.g. `T`).
+    Variable(String),
+    Array(Box<Type>),
+}
+
+impl From<&JVMType> for Type {
+    fn from(value: &JVMType) -> Self {
+        let array = |mut ty: Type, depth: usize| {
+            for _ in 0..depth {
+                ty = Type::Array(Box::new(ty));
+            }
+            ty
+        };
+        match value {
+            JVMType::TPrimitive(it) => Type::Primitive(*it),
+            JVMType::TClass(it) => Type::Class(it.clone()),
+            JVMType::TPrimitiveArray { depth, inner } => array(Type::Primitive(*inner), *depth),
+            JVMType::TClassArray { depth, inner } => array(Type::Class(inner.clone()), *depth),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Block {
+    pub statements: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Stmt {
+    pub origin: Option<Origin>,
+    pub kind: StmtKind,
+}
+
+impl From<StmtKind> for Stmt {
+    fn from(kind: StmtKind) -> Self {
+        Stmt { origin: None, kind }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StmtKind {
+    /// Expression evaluated for its side effects.
+    Expr(Expr),
+    LocalVar {
+        ty: Type,
+        name: String,
+        value: Option<Expr>,
+    },
+    Return(Option<Expr>),
+    Throw(Expr),
+    If {
+        condition: Expr,
+        then: Block,
+        otherwise: Option<Box<Stmt>>,
+    },
+    Switch {
+        selector: Expr,
+        cases: Vec<SwitchCase>,
+    },
+    Break,
+    /// Result of the enclosing `switch` expression.
+    Yield(Expr),
+    Assert {
+        condition: Expr,
+        message: Option<Expr>,
+    },
+    Block(Block),
+    /// Line comment.
+    Comment(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaseLabel {
+    /// Constant expression or enum constant name.
+    Expr(Expr),
+    Null,
+    /// Type pattern (`case String s`).
+    Pattern {
+        ty: Type,
+        binding: Option<String>,
+    },
+    Default,
+}
+
+/// Statements following one or more `case` labels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchCase {
+    pub labels: Vec<CaseLabel>,
+    pub body: Block,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    Neg,
+    Not,
+    BitNot,
+    /// `++x`
+    PreIncrement,
+    /// `--x`
+    PreDecrement,
+    /// `x++`
+    PostIncrement,
+    /// `x--`
+    PostDecrement,
+}
+
+impl UnaryOp {
+    /// Returns `true` for operators written after their operand.
+    pub fn is_postfix(self) -> bool {
+        matches!(self, UnaryOp::PostIncrement | UnaryOp::PostDecrement)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Mul,
+    Div,
+    Rem,
+    Add,
+    Sub,
+    Shl,
+    Shr,
+    UShr,
+    Lt,
+    Gt,
+    Le,
+    Ge,
+    Eq,
+    Ne,
+    BitAnd,
+    BitXor,
+    BitOr,
+    And,
+    Or,
+}
+
+impl BinaryOp {
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::UShr => ">>>",
+            BinaryOp::Lt => "<",
+            BinaryOp::Gt => ">",
+            BinaryOp::Le => "<=",
+            BinaryOp::Ge => ">=",
+            BinaryOp::Eq => "==",
+            BinaryOp::Ne => "!=",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::BitXor => "^",
+            BinaryOp::BitOr => "|",
+            BinaryOp::And => "&&",
+            BinaryOp::Or => "||",
+        }
+    }
+
+    /// Java operator precedence; higher values bind tighter.
+    pub fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 12,
+            BinaryOp::Add | BinaryOp::Sub => 11,
+            BinaryOp::Shl | BinaryOp::Shr | BinaryOp::UShr => 10,
+            BinaryOp::Lt | BinaryOp::Gt | BinaryOp::Le | BinaryOp::Ge => PRECEDENCE_RELATIONAL,
+            BinaryOp::Eq | BinaryOp::Ne => 8,
+            BinaryOp::BitAnd => 7,
+            BinaryOp::BitXor => 6,
+            BinaryOp::BitOr => 5,
+            BinaryOp::And => 4,
+            BinaryOp::Or => 3,
+        }
+    }
+
+    pub fn is_bitwise(self) -> bool {
+        matches!(self, BinaryOp::BitAnd | BinaryOp::BitXor | BinaryOp::BitOr)
+    }
+}
+
+impl From<value::BinaryOp> for BinaryOp {
+    fn from(value: value::BinaryOp) -> Self {
+        match value {
+            value::BinaryOp::Add => BinaryOp::Add,
+            value::BinaryOp::Sub => BinaryOp::Sub,
+            value::BinaryOp::Mul => BinaryOp::Mul,
+            value::BinaryOp::Div => BinaryOp::Div,
+            value::BinaryOp::Rem => BinaryOp::Rem,
+            value::BinaryOp::Shl => BinaryOp::Shl,
+            value::BinaryOp::Shr => BinaryOp::Shr,
+            value::BinaryOp::UShr => BinaryOp::UShr,
+            value::BinaryOp::And => BinaryOp::BitAnd,
+            value::BinaryOp::Or => BinaryOp::BitOr,
+            value::BinaryOp::Xor => BinaryOp::BitXor,
+        }
+    }
+}
+
+impl From<value::CompareOp> for BinaryOp {
+    fn from(value: value::CompareOp) -> Self {
+        match value {
+            value::CompareOp::Eq => BinaryOp::Eq,
+            value::CompareOp::Ne => BinaryOp::Ne,
+            value::CompareOp::Lt => BinaryOp::Lt,
+            value::CompareOp::Ge => BinaryOp::Ge,
+            value::CompareOp::Gt => BinaryOp::Gt,
+            value::CompareOp::Le => BinaryOp::Le,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Delegate {
+    This,
+    Super,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Literal(Literal),
+    /// Local variable or parameter.
+    Name(String),
+    This,
+    /// `super` as a member qualifier.
+    Super,
+    /// Type as a static member qualifier.
+    TypeName(Type),
+    Field {
+        /// `None` for unqualified access.
+        target: Option<Box<Expr>>,
+        name: String,
+    },
+    Call {
+        /// `None` for unqualified calls.
+        target: Option<Box<Expr>>,
+        name: String,
+        args: Vec<Expr>,
+    },
+    /// Explicit `this(...)` or `super(...)` constructor invocation.
+    ConstructorCall {
+        delegate: Delegate,
+        args: Vec<Expr>,
+    },
+    New {
+        ty: Type,
+        args: Vec<Expr>,
+    },
+    Unary {
+        op: UnaryOp,
+        operand: Box<Expr>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr>,
+        right: Box<Expr>,
+    },
+    Cast {
+        ty: Type,
+        value: Box<Expr>,
+    },
+    Index {
+        array: Box<Expr>,
+        index: Box<Expr>,
+    },
+    /// Array creation with either dimension `length` (`new int[n]`) or
+    /// `initializer` elements (`new int[] {1, 2}`).
+    NewArray {
+        /// Type of the created array.
+        ty: Type,
+        length: Option<Box<Expr>>,
+        initializer: Option<Vec<Expr>>,
+    },
+    /// `instanceof` check, optionally binding the checked value
+    /// (`value instanceof String s`).
+    InstanceOf {
+        value: Box<Expr>,
+        ty: Type,
+        binding: Option<String>,
+    },
+    Conditional {
+        condition: Box<Expr>,
+        then: Box<Expr>,
+        otherwise: Box<Expr>,
+    },
+    /// `switch` expression; cases end with `yield` or `throw`.
+    Switch {
+        selector: Box<Expr>,
+        cases: Vec<SwitchCase>,
+    },
+    Assign {
+        target: Box<Expr>,
+        value: Box<Expr>,
+    },
+}
+
+pub const PRECEDENCE_ASSIGN: u8 = 1;
+pub const PRECEDENCE_CONDITIONAL: u8 = 2;
+pub const PRECEDENCE_RELATIONAL: u8 = 9;
+pub const PRECEDENCE_CAST: u8 = 13;
+pub const PRECEDENCE_UNARY: u8 = 14;
+pub const PRECEDENCE_POSTFIX: u8 = 15;
+pub const PRECEDENCE_PRIMARY: u8 = 16;
+
+impl Expr {
+    /// Java operator precedence; higher values bind tighter.
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Expr::Binary { op, .. } => op.precedence(),
+            Expr::Unary { op, .. } if op.is_postfix() => PRECEDENCE_POSTFIX,
+            Expr::Unary { .. } => PRECEDENCE_UNARY,
+            Expr::Cast { .. } => PRECEDENCE_CAST,
+            Expr::Assign { .. } => PRECEDENCE_ASSIGN,
+            Expr::Conditional { .. } => PRECEDENCE_CONDITIONAL,
+            Expr::InstanceOf { .. } => PRECEDENCE_RELATIONAL,
+            Expr::Switch { .. } => PRECEDENCE_UNARY,
+            Expr::Literal(Literal::Int(it)) if *it < 0 => PRECEDENCE_UNARY,
+            Expr::Literal(Literal::Long(it)) if *it < 0 => PRECEDENCE_UNARY,
+            Expr::Literal(Literal::Float(it)) if it.is_sign_negative() => PRECEDENCE_UNARY,
+            Expr::Literal(Literal::Double(it)) if it.is_sign_negative() => PRECEDENCE_UNARY,
+            _ => PRECEDENCE_PRIMARY,
+        }
+    }
+
+    /// Returns `true` if the written expression starts with `+` or `-`,
+    /// which can't follow a cast to a reference type.
+    pub fn starts_with_sign(&self) -> bool {
+        match self {
+            Expr::Unary { op, .. } => matches!(
+                op,
+                UnaryOp::Neg | UnaryOp::PreIncrement | UnaryOp::PreDecrement
+            ),
+            Expr::Literal(_) => self.precedence() == PRECEDENCE_UNARY,
+            _ => false,
+        }
+    }
+
+    pub fn unary(op: UnaryOp, operand: Expr) -> Expr {
+        Expr::Unary {
+            op,
+            operand: Box::new(operand),
+        }
+    }
+
+    pub fn binary(op: BinaryOp, left: Expr, right: Expr) -> Expr {
+        Expr::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn assign(target: Expr, value: Expr) -> Expr {
+        Expr::Assign {
+            target: Box::new(target),
+            value: Box::new(value),
+        }
+    }
+}
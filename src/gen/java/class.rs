@@ -1,180 +1,727 @@
-use jvm_class_format::{AccessFlags, Class};
-use std::io::{Cursor, Write};
+use jvm_class_format::{
+    attribute::{AsData, CodeData, InnerClass, InnerClassData, LineNumberTable},
+    Class, ClassAccessFlags, ClassPath, FieldAccessFlags, Member,
+};
+use std::io::Write;
 
 use crate::gen::{
-    indent::Indented,
-    java::{field::FieldContext, method::ClassContext},
-    java::{JavaBackend, JavaContext, JavaScopeRequirements},
-    GenerateCode,
+    java::{
+        ast::{CompilationUnit, MemberDecl, MethodDecl, Modifier, TypeDecl, TypeKind},
+        code::ExprContext,
+        imports::ImportPlan,
+        lines::{line_count, preserve_lines, LineMapping},
+        lower::lower_class,
+        nested::NestedClasses,
+        write_modifiers, JavaBackend, JavaContext, JavaScopeRequirements, JavaVersion,
+        LanguageFeature,
+    },
+    GenerateCode, GeneratorBackend, MemberOrder,
 };
 
-pub fn class_signature(access_flags: AccessFlags) -> String {
-    let mut parts = Vec::with_capacity(4);
+/// Modifiers of a type declaration.
+pub fn class_modifiers(access_flags: ClassAccessFlags) -> Vec<Modifier> {
+    let mut result = Vec::with_capacity(4);
 
     // visibility is one of following
-    if access_flags.contains(AccessFlags::PUBLIC) {
-        parts.push("public");
-    } else if access_flags.contains(AccessFlags::PROTECTED) {
-        parts.push("protected");
-    } else if access_flags.contains(AccessFlags::PRIVATE) {
-        parts.push("private");
+    if access_flags.contains(ClassAccessFlags::PUBLIC) {
+        result.push(Modifier::Public);
+    } else if access_flags.contains(ClassAccessFlags::PROTECTED) {
+        result.push(Modifier::Protected);
+    } else if access_flags.contains(ClassAccessFlags::PRIVATE) {
+        result.push(Modifier::Private);
     }
 
     // inner classes can be static
-    if access_flags.contains(AccessFlags::STATIC) {
-        parts.push("static");
+    if access_flags.contains(ClassAccessFlags::STATIC) {
+        result.push(Modifier::Static);
     }
 
-    // a class can be abstract
-    if access_flags.contains(AccessFlags::ABSTRACT) {
-        parts.push("abstract");
+    // a class can be abstract, interfaces always are
+    if access_flags.contains(ClassAccessFlags::ABSTRACT)
+        && !access_flags.contains(ClassAccessFlags::INTERFACE)
+    {
+        result.push(Modifier::Abstract);
     }
 
     // class inheritance can be prevented
-    if access_flags.contains(AccessFlags::FINAL) {
-        parts.push("final");
+    if access_flags.contains(ClassAccessFlags::FINAL) {
+        result.push(Modifier::Final);
     }
 
-    // class type
-    if access_flags.contains(AccessFlags::ENUM) {
-        parts.push("enum");
-    } else if access_flags.contains(AccessFlags::INTERFACE) {
-        parts.push("interface");
-    } else if access_flags.contains(AccessFlags::ANNOTATION) {
-        parts.push("@interface");
+    result
+}
+
+pub fn type_kind(access_flags: ClassAccessFlags) -> TypeKind {
+    if access_flags.contains(ClassAccessFlags::ENUM) {
+        TypeKind::Enum
+    } else if access_flags.contains(ClassAccessFlags::ANNOTATION) {
+        TypeKind::Annotation
+    } else if access_flags.contains(ClassAccessFlags::INTERFACE) {
+        TypeKind::Interface
     } else {
-        parts.push("class");
+        TypeKind::Class
     }
+}
 
-    parts.join(" ")
+/// Returns indices of `fields` in the order they should be written.
+pub fn field_order(fields: &[Member], order: MemberOrder) -> Vec<usize> {
+    let mut result: Vec<usize> = (0..fields.len()).collect();
+    if order == MemberOrder::Alphabetical {
+        result.sort_by_key(|i| {
+            let field = &fields[*i];
+            let instance = !field.field_flags().contains(FieldAccessFlags::STATIC);
+            (instance, &field.name)
+        });
+    }
+    result
 }
 
-impl GenerateCode<Class> for JavaBackend {
+fn first_line(method: &Member) -> Option<u16> {
+    let code: &CodeData = method.attributes.get("Code")?.as_data().ok()?;
+    let lines: &LineNumberTable = code.attributes.get("LineNumberTable")?.as_data().ok()?;
+    lines.table.iter().map(|it| it.line_number).min()
+}
+
+/// Returns indices of `methods` in the order they should be written.
+pub fn method_order(methods: &[Member], order: MemberOrder) -> Vec<usize> {
+    let mut result: Vec<usize> = (0..methods.len()).collect();
+    match order {
+        MemberOrder::ClassFile => {}
+        MemberOrder::LineNumber => {
+            // methods without line numbers stay after their predecessor
+            let mut last = 0;
+            let lines: Vec<u16> = methods
+                .iter()
+                .map(|it| {
+                    last = first_line(it).unwrap_or(last);
+                    last
+                })
+                .collect();
+            result.sort_by_key(|i| lines[*i]);
+        }
+        MemberOrder::Alphabetical => {
+            result.sort_by_key(|i| {
+                let method = &methods[*i];
+                let kind = match method.name.as_str() {
+                    "<clinit>" => 0,
+                    "<init>" => 1,
+                    _ => 2,
+                };
+                (kind, &method.name)
+            });
+        }
+    }
+    result
+}
+
+fn inner_class_entries(class: &Class) -> &[InnerClass] {
+    class
+        .attributes
+        .get("InnerClasses")
+        .and_then(|it| AsData::<InnerClassData>::as_data(it).ok())
+        .map(|it| it.classes.as_slice())
+        .unwrap_or_default()
+}
+
+/// Member classes declared by the class, with their access flags.
+pub fn member_classes(class: &Class) -> Vec<(ClassPath, ClassAccessFlags)> {
+    inner_class_entries(class)
+        .iter()
+        .filter(|it| it.outer_class_info_index != 0 && it.inner_name_index != 0)
+        .filter(|it| {
+            ClassPath::from_class_index(&class.constant_pool, it.outer_class_info_index as usize)
+                .is_ok_and(|outer| outer == class.class_name)
+        })
+        .filter_map(|it| {
+            let name = ClassPath::from_class_index(
+                &class.constant_pool,
+                it.inner_class_info_index as usize,
+            )
+            .ok()?;
+            Some((
+                name,
+                ClassAccessFlags::from_bits_truncate(it.inner_class_access_flags),
+            ))
+        })
+        .collect()
+}
+
+/// Returns indices of member classes `names` in the order they should be
+/// written; class file order is the one of the `InnerClasses` attribute.
+pub fn member_class_order(
+    names: &[ClassPath],
+    classes: &NestedClasses,
+    order: MemberOrder,
+) -> Vec<usize> {
+    let mut result: Vec<usize> = (0..names.len()).collect();
+    match order {
+        MemberOrder::ClassFile => {}
+        MemberOrder::LineNumber => {
+            // classes without line numbers stay after their predecessor
+            let mut last = 0;
+            let lines: Vec<u16> = names
+                .iter()
+                .map(|name| {
+                    last = classes
+                        .get(name)
+                        .and_then(|it| it.methods.iter().filter_map(first_line).min())
+                        .unwrap_or(last);
+                    last
+                })
+                .collect();
+            result.sort_by_key(|i| lines[*i]);
+        }
+        MemberOrder::Alphabetical => result.sort_by_key(|i| names[*i].simple_name()),
+    }
+    result
+}
+
+/// Simple names of member classes declared by the class.
+pub fn member_class_names(class: &Class) -> Vec<String> {
+    member_classes(class)
+        .iter()
+        .map(|(name, _)| name.simple_name().to_string())
+        .collect()
+}
+
+/// Access flags of nested class `name` as declared in source, read from the
+/// `InnerClasses` attribute of `class`.
+pub fn nested_class_flags(class: &Class, name: &ClassPath) -> Option<ClassAccessFlags> {
+    inner_class_entries(class)
+        .iter()
+        .find(|it| {
+            ClassPath::from_class_index(&class.constant_pool, it.inner_class_info_index as usize)
+                .is_ok_and(|it| it == *name)
+        })
+        .map(|it| ClassAccessFlags::from_bits_truncate(it.inner_class_access_flags))
+}
+
+/// Returns `true` if the `InnerClasses` attribute of `class` records nested
+/// class `name` as a member; local and anonymous classes have no outer class.
+pub fn is_member_class(class: &Class, name: &ClassPath) -> bool {
+    inner_class_entries(class).iter().any(|it| {
+        it.outer_class_info_index != 0
+            && it.inner_name_index != 0
+            && ClassPath::from_class_index(&class.constant_pool, it.inner_class_info_index as usize)
+                .is_ok_and(|it| it == *name)
+    })
+}
+
+/// Records features needed by a method declared in an interface.
+fn interface_method_features(method: &MethodDecl, req: &mut JavaScopeRequirements) {
+    for modifier in &method.modifiers {
+        match modifier {
+            Modifier::Default => req.require(LanguageFeature::DefaultMethods),
+            Modifier::Static => req.require(LanguageFeature::StaticInterfaceMethods),
+            Modifier::Private => req.require(LanguageFeature::PrivateInterfaceMethods),
+            _ => {}
+        }
+    }
+}
+
+impl GenerateCode<TypeDecl> for JavaBackend {
     fn write_value<W: std::io::Write>(
         &self,
         lang: &JavaContext,
         _: &(),
-        class: &Class,
+        decl: &TypeDecl,
         w: &mut W,
     ) -> Result<Self::ScopeRequirements, std::io::Error> {
         let mut req = JavaScopeRequirements::default();
+        match decl.kind {
+            TypeKind::Enum => req.require(LanguageFeature::Enums),
+            TypeKind::Annotation => req.require(LanguageFeature::Annotations),
+            _ => {}
+        }
 
-        tracing::debug!("Generating class: {}", class.class_name);
+        write_modifiers(w, &decl.modifiers)?;
+        write!(w, "{} {}", decl.kind.keyword(), decl.name.simple_name())?;
 
-        // TODO: don't clone constant_pool, pass it as a reference
-        let lang = JavaContext {
-            constant_pool: Some(class.constant_pool.clone()),
-            ..(*lang).clone()
-        };
+        if let Some(extends) = &decl.extends {
+            w.write_all(b" extends ")?;
+            req.include(self.write_value(lang, &(), extends, w)?);
+        }
 
-        if lang.header_message.is_some() {
-            let lines: Vec<&str> = lang.header_message.as_ref().unwrap().split('\n').collect();
+        for (i, interface) in decl.implements.iter().enumerate() {
+            w.write_all(if i == 0 { b" implements " } else { b", " })?;
+            req.include(self.write_value(lang, &(), interface, w)?);
+        }
 
-            writeln!(w, "/*")?;
-            for l in lines {
-                writeln!(w, " * {}", l)?;
+        // lines of the declaration before its contents
+        let header_lines = 1 + lang.style.brace_lines();
+
+        let mut contents = Vec::with_capacity(512);
+        for (i, constant) in decl.enum_constants.iter().enumerate() {
+            if let Some(origin) = &constant.origin {
+                let line = header_lines + line_count(&contents) + 1;
+                req.line_map.0.push(LineMapping::new(line, origin));
             }
-            writeln!(w, " */")?;
+            let constant_line = header_lines + line_count(&contents);
+            contents.write_all(constant.name.as_bytes())?;
+            if !constant.args.is_empty() {
+                req.include(self.write_arguments(
+                    lang,
+                    &ExprContext::default(),
+                    &constant.args,
+                    &mut contents,
+                )?);
+            }
+            if let Some(members) = &constant.body {
+                let mut body = Vec::with_capacity(256);
+                let mut body_req = self.write_members(
+                    lang,
+                    TypeKind::Class,
+                    members,
+                    constant_line + 1 + lang.style.brace_lines(),
+                    &mut body,
+                )?;
+                req.line_map
+                    .include(std::mem::take(&mut body_req.line_map), 0);
+                req.include(body_req);
+                lang.style.write_block(&mut contents, &body, true)?;
+                // the separator follows the closing brace
+                contents.pop();
+            }
+            let last = i + 1 == decl.enum_constants.len();
+            contents.write_all(if last { b";\n" } else { b",\n" })?;
         }
+        // other members of an enum follow its constants
+        if decl.kind == TypeKind::Enum && !decl.members.is_empty() {
+            if decl.enum_constants.is_empty() {
+                contents.write_all(b";\n")?;
+            }
+            contents.write_all(b"\n")?;
+        }
+
+        let mut members_req =
+            self.write_members(lang, decl.kind, &decl.members, header_lines, &mut contents)?;
+        req.line_map
+            .include(std::mem::take(&mut members_req.line_map), 0);
+        req.include(members_req);
+
+        lang.style.write_block(w, &contents, true)?;
+        w.flush()?;
+
+        Ok(req)
+    }
+}
+
+impl JavaBackend {
+    /// Writes members of a type of `kind` to `contents`, which starts at line
+    /// `first_line` of the declaration (counted from 0).
+    fn write_members(
+        &self,
+        lang: &JavaContext,
+        kind: TypeKind,
+        members: &[MemberDecl],
+        first_line: usize,
+        contents: &mut Vec<u8>,
+    ) -> Result<JavaScopeRequirements, std::io::Error> {
+        let mut req = JavaScopeRequirements::default();
+        let mut previous: Option<&MemberDecl> = None;
+        for member in members {
+            let mut member_buffer = Vec::with_capacity(256);
+            let mut member_req = match member {
+                MemberDecl::Field(it) => self.write_value(lang, &(), it, &mut member_buffer)?,
+                MemberDecl::Method(it) => {
+                    let mut method_req = self.write_value(lang, &(), it, &mut member_buffer)?;
+                    if kind == TypeKind::Interface {
+                        interface_method_features(it, &mut method_req);
+                    }
+                    method_req
+                }
+                MemberDecl::Initializer(it) => {
+                    self.write_value(lang, &(), it, &mut member_buffer)?
+                }
+                MemberDecl::Type(it) => self.write_value(lang, &(), it, &mut member_buffer)?,
+            };
 
-        if !class.class_name.inner_classes.is_empty() {
-            todo!("handle inner classes")
+            // consecutive fields are grouped, everything else is separated
+            let fields = matches!(
+                (previous, member),
+                (Some(MemberDecl::Field(_)), MemberDecl::Field(_))
+            );
+            if previous.is_some() && !fields {
+                contents.write_all(b"\n")?;
+            }
+            req.line_map.include(
+                std::mem::take(&mut member_req.line_map),
+                first_line + line_count(contents),
+            );
+            req.include(member_req);
+            contents.write_all(&member_buffer)?;
+            previous = Some(member);
         }
+        Ok(req)
+    }
 
-        let package_path = class.class_name.package_path();
+    /// Writes an annotated package declaration, followed by imports of the
+    /// annotation types.
+    fn write_package_info<W: std::io::Write>(
+        &self,
+        lang: &JavaContext,
+        unit: &CompilationUnit,
+        w: &mut W,
+    ) -> Result<JavaScopeRequirements, std::io::Error> {
+        let annotations = |lang: &JavaContext| {
+            let mut req = JavaScopeRequirements::default();
+            let mut output = Vec::with_capacity(128);
+            for annotation in &unit.package_annotations {
+                req.include(self.write_value(lang, &(), annotation, &mut output)?);
+                output.write_all(b"\n")?;
+            }
+            Ok::<_, std::io::Error>((req, output))
+        };
 
-        if !package_path.is_empty() {
-            write!(w, "package {};\n\n", class.class_name.package_path())?;
+        // names are only known once all referenced classes are
+        let referenced = annotations(lang)?.0.imports;
+        let current = ClassPath {
+            package: unit.package.split('.').map(str::to_string).collect(),
+            inner_classes: vec![],
+            name: "package-info".to_string(),
+        };
+        let plan = ImportPlan::new(
+            &current,
+            &[],
+            &referenced,
+            lang.import_wildcard_threshold,
+            &lang.class_index,
+        );
+        let lang = JavaContext {
+            imports: Some(plan),
+            ..lang.clone()
+        };
+
+        let (req, output) = annotations(&lang)?;
+        w.write_all(&output)?;
+        writeln!(w, "package {};", unit.package)?;
+        let mut imports = Vec::new();
+        if let Some(plan) = &lang.imports {
+            plan.write_imports(&mut imports)?;
+        }
+        if !imports.is_empty() {
+            // no declarations follow the imports
+            imports.pop();
+            w.write_all(b"\n")?;
+            w.write_all(&imports)?;
         }
 
-        let delayed = {
-            let mut result = Vec::with_capacity(512);
-            let mut w: Cursor<&mut Vec<u8>> = Cursor::new(&mut result);
+        Ok(req)
+    }
+}
+
+impl GenerateCode<CompilationUnit> for JavaBackend {
+    fn write_value<W: std::io::Write>(
+        &self,
+        lang: &JavaContext,
+        _: &(),
+        unit: &CompilationUnit,
+        w: &mut W,
+    ) -> Result<Self::ScopeRequirements, std::io::Error> {
+        let mut req = JavaScopeRequirements::default();
 
-            w.write_all(class_signature(class.access_flags).as_bytes())?;
+        // line mappings need the whole output
+        let target = w;
+        let mut output = Vec::with_capacity(1024);
+        let w = &mut output;
 
-            let class_name = class.class_name.clone();
-            w.write_all(b" ")?;
-            w.write_all(class_name.name.as_bytes())?;
+        if let Some(header_message) = &lang.header_message {
+            let lines: Vec<&str> = header_message.split('\n').collect();
 
-            if class.super_name.is_some() && !class.super_name.as_ref().unwrap().is_object() {
-                let super_name = class.super_name.as_ref().unwrap();
-                w.write_all(b" extends ")?;
-                w.write_all(super_name.name.as_bytes())?;
-                req.imports.insert(super_name.clone());
+            writeln!(w, "/*")?;
+            for l in lines {
+                writeln!(w, " * {}", l)?;
             }
+            writeln!(w, " */")?;
+        }
 
-            if !class.interfaces.is_empty() {
-                w.write_all(b" implements ")?;
+        // a package declaration on its own is a `package-info.java`
+        if unit.types.is_empty() && !unit.package.is_empty() {
+            req.include(self.write_package_info(lang, unit, w)?);
+        } else if !unit.package.is_empty() {
+            write!(w, "package {};\n\n", unit.package)?;
+        }
 
-                for (i, interface) in class.interfaces.iter().enumerate() {
-                    req.imports.insert(interface.clone());
-                    w.write_all(interface.name.as_bytes())?;
+        if let Some(module) = &unit.module {
+            req.include(self.write_value(lang, &(), module, w)?);
+        }
 
-                    if i != class.interfaces.len() - 1 {
-                        w.write_all(b", ")?;
-                    }
-                }
+        for (i, decl) in unit.types.iter().enumerate() {
+            if i > 0 {
+                w.write_all(b"\n")?;
             }
-            w.write_all(b" {")?;
 
-            // TODO: generate enum entries
+            // names are only known once all referenced classes are, so the
+            // declaration is generated twice
+            let referenced = self
+                .write_value(lang, &(), decl, &mut std::io::sink())?
+                .imports;
+            let plan = ImportPlan::new(
+                &decl.name,
+                &decl.member_classes,
+                &referenced,
+                lang.import_wildcard_threshold,
+                &lang.class_index,
+            );
+            plan.write_imports(w)?;
+
+            let lang = JavaContext {
+                imports: Some(plan),
+                ..lang.clone()
+            };
+            let declaration_line = line_count(w);
+            let mut declaration_req = self.write_value(&lang, &(), decl, w)?;
+            req.line_map.include(
+                std::mem::take(&mut declaration_req.line_map),
+                declaration_line,
+            );
+            req.include(declaration_req);
+        }
 
-            let contents = {
-                let mut content_buffer = Vec::with_capacity(512);
-                let mut w: Cursor<&mut Vec<u8>> = Cursor::new(&mut content_buffer);
+        if lang.preserve_line_numbers {
+            output = preserve_lines(&output, &mut req.line_map);
+        }
+        target.write_all(&lang.style.convert_line_endings(output))?;
 
-                tracing::debug!("- Generating fields for {}", class_name);
+        Ok(req)
+    }
+}
 
-                let mut class_indent = Indented::new(&mut w, lang.indentation, 1, b"{", b"}");
+impl GenerateCode<Class> for JavaBackend {
+    fn write_value<W: std::io::Write>(
+        &self,
+        lang: &JavaContext,
+        _: &(),
+        class: &Class,
+        w: &mut W,
+    ) -> Result<Self::ScopeRequirements, std::io::Error> {
+        tracing::debug!("Generating class: {}", class.class_name);
 
-                for field in &class.fields {
-                    let field_requirements =
-                        self.write_value(&lang, &FieldContext, field, &mut class_indent)?;
-                    req.add_import(field_requirements.imports);
-                }
+        let lang = &JavaContext {
+            target_version: Some(
+                lang.target_version
+                    .unwrap_or_else(|| JavaVersion::from_class_major(class.compiler_info.major)),
+            ),
+            ..lang.clone()
+        };
 
-                tracing::debug!("- Generating methods for {}", class_name);
+        let mut unit = lower_class(lang, self.verbosity(), class);
+        for pass in &lang.rewrite_passes {
+            tracing::trace!("- Applying rewrite pass: {}", pass.name());
+            pass.rewrite(&mut unit);
+        }
+        let mut req = self.write_value(lang, &(), &unit, w)?;
+        req.skipped_methods = unit
+            .types
+            .iter()
+            .flat_map(|it| it.all_skipped_methods())
+            .collect();
+        if req.language_level > lang.target() {
+            tracing::warn!(
+                "{} can't be expressed in {}, it requires {}",
+                class.class_name,
+                lang.target(),
+                req.language_level
+            );
+        }
 
-                for method in &class.methods {
-                    let method_ctx = ClassContext {
-                        class_name: class.class_name.clone(),
-                        ..Default::default()
-                    };
-                    let method_requirements =
-                        self.write_value(&lang, &method_ctx, method, &mut class_indent)?;
-                    req.add_import(method_requirements.imports);
-                }
+        tracing::debug!("- Done.");
 
-                content_buffer
+        Ok(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use jvm_class_format::{
+        attribute::{AttributeValue, LineNumber},
+        AccessFlags, JVMPrimitive,
+    };
+
+    use super::*;
+    use crate::gen::java::ast::Type;
+
+    fn method(name: &str, line: Option<u16>) -> Member {
+        let mut attributes = HashMap::new();
+        if let Some(line_number) = line {
+            let lines = LineNumberTable {
+                table: vec![LineNumber {
+                    start_pc: 0,
+                    line_number,
+                }],
+            };
+            let code = CodeData {
+                max_stack: 0,
+                max_locals: 0,
+                code: vec![],
+                exception_table: vec![],
+                attributes: HashMap::from([("LineNumberTable".to_string(), lines.into())]),
             };
+            attributes.insert("Code".to_string(), AttributeValue::Code(code));
+        }
+        Member {
+            access_flags: AccessFlags::empty(),
+            name: name.to_string(),
+            descriptor: "()V".parse().unwrap(),
+            attributes,
+        }
+    }
 
-            if !contents.is_empty() {
-                w.write_all(b"\n")?;
-            }
-            w.write_all(&contents)?;
-            w.write_all(b"}\n")?;
-            w.flush()?;
+    #[test]
+    fn member_ordering() {
+        let methods = [
+            method("<init>", Some(1)),
+            method("zeta", Some(10)),
+            method("alpha", Some(20)),
+            method("beta", None),
+            method("<clinit>", Some(5)),
+        ];
+
+        assert_eq!(
+            method_order(&methods, MemberOrder::ClassFile),
+            [0, 1, 2, 3, 4]
+        );
+        assert_eq!(
+            method_order(&methods, MemberOrder::LineNumber),
+            [0, 4, 1, 2, 3]
+        );
+        assert_eq!(
+            method_order(&methods, MemberOrder::Alphabetical),
+            [4, 0, 2, 3, 1]
+        );
+    }
 
-            result
+    #[test]
+    fn interface_language_level() {
+        let method = MethodDecl {
+            comment: None,
+            modifiers: vec![Modifier::Default],
+            return_type: Some(Type::Primitive(JVMPrimitive::TVoid)),
+            name: "run".to_string(),
+            params: vec![],
+            varargs: false,
+            throws: vec![],
+            body: Some(Default::default()),
+        };
+        let decl = TypeDecl {
+            modifiers: vec![],
+            kind: TypeKind::Interface,
+            name: ClassPath::parse("Unit").unwrap(),
+            extends: None,
+            implements: vec![],
+            member_classes: vec![],
+            enum_constants: vec![],
+            members: vec![MemberDecl::Method(method)],
+            skipped_methods: vec![],
         };
 
-        let mut has_imports = false;
-        for import in req.imports.drain() {
-            has_imports = true;
-            w.write_all(b"import ")?;
-            w.write_all(import.full_path().as_bytes())?;
-            w.write_all(b";\n")?;
-        }
+        let lang = crate::gen::GeneratorBuilder::java().build();
+        let (code, req) = JavaBackend.generate(&lang, &(), &decl).unwrap();
+        assert_eq!(code, "interface Unit {\n  default void run() {\n  }\n}\n");
+        assert_eq!(req.language_level, JavaVersion::Java8);
+    }
 
-        if has_imports {
-            w.write_all(b"\n")?;
-        }
+    #[test]
+    fn package_info_imports_annotation_types() {
+        use crate::gen::java::ast::{Annotation, AnnotationValue, CompilationUnit, Expr};
+
+        let unit = CompilationUnit {
+            package: "com.example".to_string(),
+            package_annotations: vec![
+                Annotation {
+                    ty: ClassPath::parse("javax/annotation/ParametersAreNonnullByDefault").unwrap(),
+                    elements: vec![],
+                },
+                Annotation {
+                    ty: ClassPath::parse("com/example/Tag").unwrap(),
+                    elements: vec![(
+                        "value".to_string(),
+                        AnnotationValue::Array(vec![AnnotationValue::Expr(Expr::Field {
+                            target: Some(Box::new(Expr::TypeName(Type::Class(
+                                ClassPath::parse("java/util/concurrent/TimeUnit").unwrap(),
+                            )))),
+                            name: "DAYS".to_string(),
+                        })]),
+                    )],
+                },
+            ],
+            ..Default::default()
+        };
 
-        w.write_all(&delayed)?;
+        let lang = JavaContext {
+            header_message: None,
+            ..crate::gen::GeneratorBuilder::java().build()
+        };
+        let (code, _) = JavaBackend.generate(&lang, &(), &unit).unwrap();
+        assert_eq!(
+            code,
+            "@ParametersAreNonnullByDefault\n\
+             @Tag({TimeUnit.DAYS})\n\
+             package com.example;\n\
+             \n\
+             import java.util.concurrent.TimeUnit;\n\
+             \n\
+             import javax.annotation.ParametersAreNonnullByDefault;\n"
+        );
+    }
 
-        tracing::debug!("- Done.");
+    #[test]
+    fn preserved_lines_keep_declarations_together() {
+        use crate::gen::java::ast::{Block, CompilationUnit, Expr, Origin, Stmt, StmtKind};
+        use crate::gen::writer::BraceStyle;
+
+        let statement = Stmt {
+            origin: Some(Origin {
+                method: "run()V".to_string(),
+                offset: 0,
+                original_line: Some(12),
+            }),
+            kind: StmtKind::Expr(Expr::Call {
+                target: None,
+                name: "run".to_string(),
+                args: vec![],
+            }),
+        };
+        let method = MethodDecl {
+            comment: Some("left as is".to_string()),
+            modifiers: vec![],
+            return_type: Some(Type::Primitive(JVMPrimitive::TVoid)),
+            name: "run".to_string(),
+            params: vec![],
+            varargs: false,
+            throws: vec![],
+            body: Some(Block {
+                statements: vec![statement],
+            }),
+        };
+        let unit = CompilationUnit {
+            types: vec![TypeDecl {
+                modifiers: vec![],
+                kind: TypeKind::Class,
+                name: ClassPath::parse("Unit").unwrap(),
+                extends: None,
+                implements: vec![],
+                member_classes: vec![],
+                enum_constants: vec![],
+                members: vec![MemberDecl::Method(method)],
+                skipped_methods: vec![],
+            }],
+            ..Default::default()
+        };
 
-        Ok(req)
+        let mut lang = JavaContext {
+            header_message: None,
+            preserve_line_numbers: true,
+            ..crate::gen::GeneratorBuilder::java().build()
+        };
+        lang.style.brace_style = BraceStyle::NextLine;
+        let (code, req) = JavaBackend.generate(&lang, &(), &unit).unwrap();
+        assert_eq!(
+            code,
+            "class Unit\n{\n\n\n\n\n\n  // left as is\n\n  void run()\n  {\n    run();\n  }\n}\n"
+        );
+        assert_eq!(req.line_map.0[0].output_line, 12);
     }
 }
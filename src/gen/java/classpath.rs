@@ -0,0 +1,63 @@
+//! Classes known to exist next to the generated ones, such as libraries of
+//! an application.
+
+use std::collections::{HashMap, HashSet};
+
+use jvm_class_format::ClassPath;
+
+/// Top level classes of each package on the classpath.
+///
+/// Used to tell whether a `*` import would bring in a class whose simple
+/// name is already taken.
+#[derive(Debug, Clone, Default)]
+pub struct ClassIndex {
+    packages: HashMap<String, HashSet<String>>,
+}
+
+impl ClassIndex {
+    /// Adds the top level class of `class`.
+    pub fn add(&mut self, class: &ClassPath) {
+        self.packages
+            .entry(class.package_path())
+            .or_default()
+            .insert(class.name.clone());
+    }
+
+    /// Adds the class stored in archive entry `name` (e.g.
+    /// `com/example/Foo.class`); returns `false` if it's not a class.
+    pub fn add_entry(&mut self, name: &str) -> bool {
+        let Some(class) = name
+            .strip_suffix(".class")
+            // `module-info` and `package-info` aren't classes, and overlays of
+            // multi-release jars are found at their base name too
+            .filter(|it| !it.ends_with("-info") && !it.starts_with("META-INF/"))
+            .and_then(|it| ClassPath::parse(it).ok())
+        else {
+            return false;
+        };
+        self.add(&class);
+        true
+    }
+
+    /// Adds all classes of `other`.
+    pub fn extend(&mut self, other: &ClassIndex) {
+        for (package, classes) in &other.packages {
+            self.packages
+                .entry(package.clone())
+                .or_default()
+                .extend(classes.iter().cloned());
+        }
+    }
+
+    /// Returns `true` if `package` (in source form, e.g. `java.util`) has a
+    /// top level class called `name`.
+    pub fn contains(&self, package: &str, name: &str) -> bool {
+        self.packages
+            .get(package)
+            .is_some_and(|it| it.contains(name))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+}
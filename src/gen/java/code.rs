@@ -1,63 +1,817 @@
-use jvm_class_format::{attribute::CodeData, ConstantPool, Member};
+use std::io::Write;
 
 use crate::{
-    gen::{GenerateCode, GeneratorBackend},
-    ir::expression::{EmptySuperCall, Expression, InstructionComment},
+    gen::{
+        java::{
+            ast::{
+                Block, CaseLabel, Delegate, Expr, Stmt, StmtKind, SwitchCase, Type, UnaryOp,
+                PRECEDENCE_ASSIGN, PRECEDENCE_CAST, PRECEDENCE_CONDITIONAL, PRECEDENCE_POSTFIX,
+                PRECEDENCE_PRIMARY, PRECEDENCE_RELATIONAL, PRECEDENCE_UNARY,
+            },
+            lines::{line_count, LineMap, LineMapping},
+            literal::LiteralContext,
+            JavaScopeRequirements, LanguageFeature,
+        },
+        writer::BraceStyle,
+        GenerateCode, GeneratorBackend,
+    },
+    ir::value::Literal,
 };
 
 use super::JavaBackend;
 
-pub type CodeGenContext<'m, 'data> = (&'m Member, &'data CodeData);
+/// Levels of indentation of statements in a method body (class and method
+/// block).
+pub const BODY_INDENT: usize = 2;
+/// Levels of indentation of wrapped arguments and chained calls, relative to
+/// the statement.
+const CONTINUATION_INDENT: usize = 2;
 
-impl<'m, 'data> GenerateCode<Expression, CodeGenContext<'m, 'data>> for JavaBackend {
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExprContext {
+    /// Expression being written doesn't fit into the line width and should
+    /// be broken up at its outermost argument list or call chain.
+    pub wrap: bool,
+}
+
+const FLAT: ExprContext = ExprContext { wrap: false };
+const WRAP: ExprContext = ExprContext { wrap: true };
+
+/// Returns `true` for call targets that continue a call chain.
+fn is_chain_target(target: &Expr) -> bool {
+    !matches!(target, Expr::This | Expr::Super | Expr::TypeName(_))
+}
+
+impl JavaBackend {
+    /// Writes a value, wrapping it in parentheses if it binds weaker than
+    /// `min_precedence` requires.
+    fn write_operand<W: std::io::Write>(
+        &self,
+        lang: &<Self as GeneratorBackend>::LanguageContext,
+        ctx: &ExprContext,
+        value: &Expr,
+        min_precedence: u8,
+        w: &mut W,
+    ) -> Result<JavaScopeRequirements, std::io::Error> {
+        if value.precedence() < min_precedence {
+            w.write_all(b"(")?;
+            let req = self.write_value(lang, ctx, value, w)?;
+            w.write_all(b")")?;
+            Ok(req)
+        } else {
+            self.write_value(lang, ctx, value, w)
+        }
+    }
+
+    /// Writes a member qualifier followed by `.`, if there is one.
+    fn write_qualifier<W: std::io::Write>(
+        &self,
+        lang: &<Self as GeneratorBackend>::LanguageContext,
+        ctx: &ExprContext,
+        target: Option<&Expr>,
+        w: &mut W,
+    ) -> Result<JavaScopeRequirements, std::io::Error> {
+        let Some(target) = target else {
+            return Ok(Default::default());
+        };
+        let req = self.write_operand(lang, ctx, target, PRECEDENCE_PRIMARY, w)?;
+        w.write_all(b".")?;
+        Ok(req)
+    }
+
+    pub(crate) fn write_arguments<W: std::io::Write>(
+        &self,
+        lang: &<Self as GeneratorBackend>::LanguageContext,
+        ctx: &ExprContext,
+        args: &[Expr],
+        w: &mut W,
+    ) -> Result<JavaScopeRequirements, std::io::Error> {
+        let mut req = JavaScopeRequirements::default();
+        if ctx.wrap && !args.is_empty() {
+            // one argument per line, each wrapped further if it's too long
+            let levels = BODY_INDENT + CONTINUATION_INDENT;
+            w.write_all(b"(\n")?;
+            for (i, arg) in args.iter().enumerate() {
+                let (mut code, mut arg_req) = self.generate(lang, &FLAT, arg)?;
+                if !lang.style.fits(code.as_bytes(), levels) {
+                    (code, arg_req) = self.generate(lang, &WRAP, arg)?;
+                }
+                req.include(arg_req);
+                lang.style
+                    .write_indented(w, code.as_bytes(), CONTINUATION_INDENT)?;
+                if i + 1 < args.len() {
+                    w.write_all(b",\n")?;
+                }
+            }
+            w.write_all(b")")?;
+            return Ok(req);
+        }
+
+        w.write_all(b"(")?;
+        for (i, arg) in args.iter().enumerate() {
+            if i > 0 {
+                w.write_all(b", ")?;
+            }
+            req.include(self.write_value(lang, ctx, arg, w)?);
+        }
+        w.write_all(b")")?;
+        Ok(req)
+    }
+
+    /// Writes a chain of at least two method calls with each call on its own
+    /// line, e.g. `builder\n    .a()\n    .b()`.
+    ///
+    /// Returns `None` without writing anything if `value` isn't a chain.
+    fn write_call_chain<W: std::io::Write>(
+        &self,
+        lang: &<Self as GeneratorBackend>::LanguageContext,
+        value: &Expr,
+        w: &mut W,
+    ) -> Result<Option<JavaScopeRequirements>, std::io::Error> {
+        let mut links = Vec::new();
+        let mut current = value;
+        while let Expr::Call {
+            target: Some(target),
+            name,
+            args,
+        } = current
+        {
+            if !is_chain_target(target) {
+                break;
+            }
+            links.push((name, args));
+            current = target;
+        }
+        if links.len() < 2 {
+            return Ok(None);
+        }
+
+        let mut req = self.write_operand(lang, &FLAT, current, PRECEDENCE_PRIMARY, w)?;
+        for (name, args) in links.into_iter().rev() {
+            let mut call = format!(".{}", name).into_bytes();
+            let mut call_req = self.write_arguments(lang, &FLAT, args, &mut call)?;
+            if !lang.style.fits(&call, BODY_INDENT + CONTINUATION_INDENT) {
+                call = format!(".{}", name).into_bytes();
+                call_req = self.write_arguments(lang, &WRAP, args, &mut call)?;
+            }
+            req.include(call_req);
+            w.write_all(b"\n")?;
+            lang.style.write_indented(w, &call, CONTINUATION_INDENT)?;
+        }
+        Ok(Some(req))
+    }
+
+    /// Writes an `if` statement including `else` branches.
+    fn write_if<W: std::io::Write>(
+        &self,
+        lang: &<Self as GeneratorBackend>::LanguageContext,
+        ctx: &ExprContext,
+        condition: &Expr,
+        then: &Block,
+        otherwise: Option<&Stmt>,
+        w: &mut W,
+    ) -> Result<JavaScopeRequirements, std::io::Error> {
+        let mut out = Vec::with_capacity(128);
+        out.write_all(b"if (")?;
+        let mut req = self.write_value(lang, ctx, condition, &mut out)?;
+        out.write_all(b")")?;
+
+        // line mappings of a branch are shifted to where it's written
+        let write_branch = |out: &mut Vec<u8>, block: &Block| {
+            lang.style.open_block(out)?;
+            let (body, mut body_req) = self.generate(lang, &(), block)?;
+            body_req.line_map.shift(line_count(out));
+            lang.style.write_indented(out, body.as_bytes(), 1)?;
+            out.write_all(b"}")?;
+            Ok::<_, std::io::Error>(body_req)
+        };
+
+        let mut then_req = write_branch(&mut out, then)?;
+        req.line_map.0.append(&mut then_req.line_map.0);
+        req.include(then_req);
+
+        if let Some(otherwise) = otherwise {
+            match lang.style.brace_style {
+                BraceStyle::SameLine => out.write_all(b" else")?,
+                BraceStyle::NextLine => out.write_all(b"\nelse")?,
+            }
+            let mut else_req = match &otherwise.kind {
+                StmtKind::If { .. } => {
+                    out.write_all(b" ")?;
+                    let at = line_count(&out);
+                    let mut nested_req = self.write_value(lang, &FLAT, otherwise, &mut out)?;
+                    nested_req.line_map.shift(at);
+                    nested_req
+                }
+                StmtKind::Block(block) => write_branch(&mut out, block)?,
+                _ => {
+                    let block = Block {
+                        statements: vec![otherwise.clone()],
+                    };
+                    write_branch(&mut out, &block)?
+                }
+            };
+            req.line_map.0.append(&mut else_req.line_map.0);
+            req.include(else_req);
+        }
+        // nested `else if` already ends the line
+        if !out.ends_with(b"\n") {
+            out.write_all(b"\n")?;
+        }
+
+        w.write_all(&out)?;
+        Ok(req)
+    }
+
+    /// Writes a `case` label (`case 1`, `case String s`, `default`) without
+    /// the trailing `:` or `->`.
+    fn write_case_label<W: std::io::Write>(
+        &self,
+        lang: &<Self as GeneratorBackend>::LanguageContext,
+        label: &CaseLabel,
+        w: &mut W,
+    ) -> Result<JavaScopeRequirements, std::io::Error> {
+        let mut req = JavaScopeRequirements::default();
+        match label {
+            CaseLabel::Expr(expr) => {
+                if let Expr::Literal(Literal::String(_)) = expr {
+                    req.require(LanguageFeature::StringSwitch);
+                }
+                w.write_all(b"case ")?;
+                req.include(self.write_value(lang, &FLAT, expr, w)?);
+            }
+            CaseLabel::Null => {
+                req.require(LanguageFeature::SwitchPatterns);
+                w.write_all(b"case null")?;
+            }
+            CaseLabel::Pattern { ty, binding } => {
+                req.require(LanguageFeature::SwitchPatterns);
+                w.write_all(b"case ")?;
+                req.include(self.write_value(lang, &(), ty, w)?);
+                if let Some(binding) = binding {
+                    write!(w, " {}", binding)?;
+                }
+            }
+            CaseLabel::Default => w.write_all(b"default")?,
+        }
+        Ok(req)
+    }
+
+    /// Writes a `switch` statement or expression with case bodies indented
+    /// below their labels.
+    ///
+    /// Switch expressions whose cases all end with `yield` or `throw` are
+    /// written with `case ... ->` labels if the target version has them.
+    fn write_switch<W: std::io::Write>(
+        &self,
+        lang: &<Self as GeneratorBackend>::LanguageContext,
+        ctx: &ExprContext,
+        selector: &Expr,
+        cases: &[SwitchCase],
+        expression: bool,
+        w: &mut W,
+    ) -> Result<JavaScopeRequirements, std::io::Error> {
+        let mut out = Vec::with_capacity(128);
+        out.write_all(b"switch (")?;
+        let mut req = self.write_value(lang, ctx, selector, &mut out)?;
+        out.write_all(b")")?;
+        lang.style.open_block(&mut out)?;
+
+        if expression {
+            req.require(LanguageFeature::SwitchExpressions);
+        }
+        let arrows = expression
+            && lang.supports(LanguageFeature::SwitchExpressions)
+            && cases.iter().all(is_arrow_case);
+
+        for case in cases {
+            let mut labels = Vec::with_capacity(32);
+            if arrows {
+                for (i, label) in case.labels.iter().enumerate() {
+                    if i > 0 {
+                        labels.write_all(b", ")?;
+                    }
+                    let mut text = Vec::with_capacity(16);
+                    req.include(self.write_case_label(lang, label, &mut text)?);
+                    // only the first label is preceded by `case`
+                    let text = match i {
+                        0 => &text[..],
+                        _ => text.strip_prefix(b"case ").unwrap_or(&text),
+                    };
+                    labels.write_all(text)?;
+                }
+                labels.write_all(b" -> ")?;
+                lang.style.write_indented(&mut out, &labels, 1)?;
+
+                let mut body_req = match case.body.statements.as_slice() {
+                    [Stmt {
+                        origin,
+                        kind: StmtKind::Yield(value),
+                    }] => {
+                        let mut value_req = self.write_value(lang, &FLAT, value, &mut out)?;
+                        out.write_all(b";\n")?;
+                        if let Some(origin) = origin {
+                            value_req
+                                .line_map
+                                .0
+                                .push(LineMapping::new(line_count(&out), origin));
+                        }
+                        value_req
+                    }
+                    [stmt @ Stmt {
+                        kind: StmtKind::Throw(_),
+                        ..
+                    }] => {
+                        let at = line_count(&out);
+                        let mut stmt_req = self.write_value(lang, &FLAT, stmt, &mut out)?;
+                        stmt_req.line_map.shift(at);
+                        stmt_req
+                    }
+                    _ => {
+                        out.write_all(b"{\n")?;
+                        let (body, mut body_req) = self.generate(lang, &(), &case.body)?;
+                        body_req.line_map.shift(line_count(&out));
+                        lang.style.write_indented(&mut out, body.as_bytes(), 2)?;
+                        lang.style.write_indented(&mut out, b"}\n", 1)?;
+                        body_req
+                    }
+                };
+                req.line_map.0.append(&mut body_req.line_map.0);
+                req.include(body_req);
+                continue;
+            }
+
+            for label in &case.labels {
+                req.include(self.write_case_label(lang, label, &mut labels)?);
+                labels.write_all(b":\n")?;
+            }
+            lang.style.write_indented(&mut out, &labels, 1)?;
+
+            let (body, mut body_req) = self.generate(lang, &(), &case.body)?;
+            body_req.line_map.shift(line_count(&out));
+            lang.style.write_indented(&mut out, body.as_bytes(), 2)?;
+            req.line_map.0.append(&mut body_req.line_map.0);
+            req.include(body_req);
+        }
+        out.write_all(b"}")?;
+        if !expression {
+            out.write_all(b"\n")?;
+        }
+
+        w.write_all(&out)?;
+        Ok(req)
+    }
+
+    /// Writes the value of an expression statement, keeping line mappings of
+    /// statements nested in `switch` expressions.
+    fn write_statement_value<W: std::io::Write>(
+        &self,
+        lang: &<Self as GeneratorBackend>::LanguageContext,
+        ctx: &ExprContext,
+        value: &Expr,
+        req: &mut JavaScopeRequirements,
+        w: &mut W,
+    ) -> Result<(), std::io::Error> {
+        let mut value_req = self.write_value(lang, ctx, value, w)?;
+        req.line_map.0.append(&mut value_req.line_map.0);
+        req.include(value_req);
+        Ok(())
+    }
+}
+
+/// Returns `true` for cases of a `switch` expression which can be written as
+/// `case ... ->`: they can't fall through and `default` isn't combined with
+/// constants.
+fn is_arrow_case(case: &SwitchCase) -> bool {
+    let ends = matches!(
+        case.body.statements.last(),
+        Some(Stmt {
+            kind: StmtKind::Yield(_) | StmtKind::Throw(_),
+            ..
+        })
+    );
+    let labels = case.labels.len() == 1
+        || !case.labels.contains(&CaseLabel::Default)
+        || case.labels == [CaseLabel::Null, CaseLabel::Default];
+    ends && labels
+}
+
+impl GenerateCode<Expr, ExprContext> for JavaBackend {
     fn write_value<W: std::io::Write>(
         &self,
         lang: &Self::LanguageContext,
-        ctx: &CodeGenContext,
-        input: &Expression,
+        ctx: &ExprContext,
+        input: &Expr,
         w: &mut W,
     ) -> Result<Self::ScopeRequirements, std::io::Error> {
-        #[allow(unreachable_patterns)]
+        let mut req = JavaScopeRequirements::default();
+
         match input {
-            Expression::Comment(it) => self.write_value(lang, ctx, it, w),
-            Expression::Super(it) => self.write_value(lang, ctx, it, w),
-            Expression::EmptyConstructor(_) => Ok(Default::default()),
-            expr => todo!("unimplemented expression: {:?}", expr),
+            Expr::Literal(it) => {
+                req.include(self.write_value(lang, &LiteralContext::default(), it, w)?);
+            }
+            Expr::Name(name) => w.write_all(name.as_bytes())?,
+            Expr::This => w.write_all(b"this")?,
+            Expr::Super => w.write_all(b"super")?,
+            Expr::TypeName(ty) => req.include(self.write_value(lang, &(), ty, w)?),
+            Expr::Field { target, name } => {
+                req.include(self.write_qualifier(lang, ctx, target.as_deref(), w)?);
+                w.write_all(name.as_bytes())?;
+            }
+            Expr::Call { target, name, args } => {
+                if ctx.wrap {
+                    if let Some(chain_req) = self.write_call_chain(lang, input, w)? {
+                        req.include(chain_req);
+                        return Ok(req);
+                    }
+                }
+                // only the arguments of the outermost call are wrapped
+                req.include(self.write_qualifier(lang, &FLAT, target.as_deref(), w)?);
+                w.write_all(name.as_bytes())?;
+                req.include(self.write_arguments(lang, ctx, args, w)?);
+            }
+            Expr::ConstructorCall { delegate, args } => {
+                w.write_all(match delegate {
+                    Delegate::This => b"this",
+                    Delegate::Super => b"super",
+                })?;
+                req.include(self.write_arguments(lang, ctx, args, w)?);
+            }
+            Expr::New { ty, args } => {
+                w.write_all(b"new ")?;
+                req.include(self.write_value(lang, &(), ty, w)?);
+                req.include(self.write_arguments(lang, ctx, args, w)?);
+            }
+            Expr::NewArray {
+                ty,
+                length,
+                initializer,
+            } => {
+                w.write_all(b"new ")?;
+                match length {
+                    Some(length) => {
+                        // `new int[n][]` creates an array of arrays
+                        let mut element = match ty {
+                            Type::Array(it) => it.as_ref(),
+                            other => other,
+                        };
+                        let mut depth = 0;
+                        while let Type::Array(inner) = element {
+                            element = inner;
+                            depth += 1;
+                        }
+                        req.include(self.write_value(lang, &(), element, w)?);
+                        w.write_all(b"[")?;
+                        req.include(self.write_value(lang, ctx, length.as_ref(), w)?);
+                        w.write_all(b"]")?;
+                        for _ in 0..depth {
+                            w.write_all(b"[]")?;
+                        }
+                    }
+                    None => req.include(self.write_value(lang, &(), ty, w)?),
+                }
+                if let Some(values) = initializer {
+                    w.write_all(b" {")?;
+                    for (i, value) in values.iter().enumerate() {
+                        if i > 0 {
+                            w.write_all(b", ")?;
+                        }
+                        req.include(self.write_value(lang, ctx, value, w)?);
+                    }
+                    w.write_all(b"}")?;
+                }
+            }
+            Expr::Binary { op, left, right } => {
+                let precedence = op.precedence();
+                let literal_ctx = LiteralContext {
+                    hex: op.is_bitwise(),
+                };
+
+                if let Expr::Literal(it) = left.as_ref() {
+                    req.include(self.write_value(lang, &literal_ctx, it, w)?);
+                } else {
+                    req.include(self.write_operand(lang, ctx, left, precedence, w)?);
+                }
+                write!(w, " {} ", op.symbol())?;
+                if let Expr::Literal(it) = right.as_ref() {
+                    req.include(self.write_value(lang, &literal_ctx, it, w)?);
+                } else {
+                    // operators are left associative
+                    req.include(self.write_operand(lang, ctx, right, precedence + 1, w)?);
+                }
+            }
+            Expr::Unary { op, operand } => {
+                let min_precedence = match op {
+                    UnaryOp::Neg => {
+                        w.write_all(b"-")?;
+                        // avoid emitting a decrement operator
+                        PRECEDENCE_UNARY + 1
+                    }
+                    UnaryOp::Not => {
+                        w.write_all(b"!")?;
+                        PRECEDENCE_UNARY
+                    }
+                    UnaryOp::BitNot => {
+                        w.write_all(b"~")?;
+                        PRECEDENCE_UNARY
+                    }
+                    UnaryOp::PreIncrement => {
+                        w.write_all(b"++")?;
+                        PRECEDENCE_UNARY
+                    }
+                    UnaryOp::PreDecrement => {
+                        w.write_all(b"--")?;
+                        PRECEDENCE_UNARY
+                    }
+                    UnaryOp::PostIncrement | UnaryOp::PostDecrement => PRECEDENCE_POSTFIX,
+                };
+                req.include(self.write_operand(lang, ctx, operand, min_precedence, w)?);
+                match op {
+                    UnaryOp::PostIncrement => w.write_all(b"++")?,
+                    UnaryOp::PostDecrement => w.write_all(b"--")?,
+                    _ => {}
+                }
+            }
+            Expr::Cast { ty, value } => {
+                w.write_all(b"(")?;
+                req.include(self.write_value(lang, &(), ty, w)?);
+                w.write_all(b") ")?;
+                // `(Integer) -1` would be a subtraction
+                let min_precedence = match ty {
+                    Type::Primitive(_) => PRECEDENCE_CAST,
+                    _ if value.starts_with_sign() => PRECEDENCE_PRIMARY,
+                    _ => PRECEDENCE_CAST,
+                };
+                req.include(self.write_operand(lang, ctx, value, min_precedence, w)?);
+            }
+            Expr::Index { array, index } => {
+                req.include(self.write_operand(lang, ctx, array, PRECEDENCE_PRIMARY, w)?);
+                w.write_all(b"[")?;
+                req.include(self.write_value(lang, ctx, index.as_ref(), w)?);
+                w.write_all(b"]")?;
+            }
+            Expr::InstanceOf { value, ty, binding } => {
+                req.include(self.write_operand(lang, ctx, value, PRECEDENCE_RELATIONAL, w)?);
+                w.write_all(b" instanceof ")?;
+                req.include(self.write_value(lang, &(), ty, w)?);
+                if let Some(binding) = binding {
+                    req.require(LanguageFeature::InstanceofPatterns);
+                    write!(w, " {}", binding)?;
+                }
+            }
+            Expr::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                req.include(self.write_operand(
+                    lang,
+                    ctx,
+                    condition,
+                    PRECEDENCE_CONDITIONAL + 1,
+                    w,
+                )?);
+                w.write_all(b" ? ")?;
+                req.include(self.write_operand(lang, ctx, then, PRECEDENCE_CONDITIONAL + 1, w)?);
+                w.write_all(b" : ")?;
+                // conditional is right associative
+                req.include(self.write_operand(lang, ctx, otherwise, PRECEDENCE_CONDITIONAL, w)?);
+            }
+            Expr::Switch { selector, cases } => {
+                let mut switch_req = self.write_switch(lang, ctx, selector, cases, true, w)?;
+                req.line_map.0.append(&mut switch_req.line_map.0);
+                req.include(switch_req);
+            }
+            Expr::Assign { target, value } => {
+                req.include(self.write_operand(lang, &FLAT, target, PRECEDENCE_PRIMARY, w)?);
+                w.write_all(b" = ")?;
+                // assignment is right associative
+                req.include(self.write_operand(lang, ctx, value, PRECEDENCE_ASSIGN, w)?);
+            }
         }
+
+        Ok(req)
     }
 }
 
-impl<'m, 'data, B: GeneratorBackend> GenerateCode<EmptySuperCall, CodeGenContext<'m, 'data>> for B {
+impl GenerateCode<Stmt, ExprContext> for JavaBackend {
+    /// Writes a statement; line mappings are relative to its first line.
     fn write_value<W: std::io::Write>(
         &self,
-        _: &Self::LanguageContext,
-        _: &CodeGenContext<'m, 'data>,
-        _: &EmptySuperCall,
+        lang: &Self::LanguageContext,
+        ctx: &ExprContext,
+        input: &Stmt,
         w: &mut W,
     ) -> Result<Self::ScopeRequirements, std::io::Error> {
-        #[cfg(debug_assertions)]
-        w.write_all(b"super();\n")?;
-        Ok(Default::default())
+        let mut req = JavaScopeRequirements::default();
+        if let Some(origin) = &input.origin {
+            req.line_map.0.push(LineMapping::new(1, origin));
+        }
+
+        match &input.kind {
+            StmtKind::Expr(expr) => {
+                self.write_statement_value(lang, ctx, expr, &mut req, w)?;
+                w.write_all(b";\n")?;
+            }
+            StmtKind::LocalVar { ty, name, value } => {
+                req.include(self.write_value(lang, &(), ty, w)?);
+                write!(w, " {}", name)?;
+                if let Some(value) = value {
+                    w.write_all(b" = ")?;
+                    self.write_statement_value(lang, ctx, value, &mut req, w)?;
+                }
+                w.write_all(b";\n")?;
+            }
+            StmtKind::Return(value) => match value {
+                Some(value) => {
+                    w.write_all(b"return ")?;
+                    self.write_statement_value(lang, ctx, value, &mut req, w)?;
+                    w.write_all(b";\n")?;
+                }
+                None => w.write_all(b"return;\n")?,
+            },
+            StmtKind::Throw(value) => {
+                w.write_all(b"throw ")?;
+                self.write_statement_value(lang, ctx, value, &mut req, w)?;
+                w.write_all(b";\n")?;
+            }
+            StmtKind::Assert { condition, message } => {
+                req.require(LanguageFeature::Assertions);
+                w.write_all(b"assert ")?;
+                req.include(self.write_value(lang, ctx, condition, w)?);
+                if let Some(message) = message {
+                    w.write_all(b" : ")?;
+                    req.include(self.write_value(lang, ctx, message, w)?);
+                }
+                w.write_all(b";\n")?;
+            }
+            StmtKind::Yield(value) => {
+                req.require(LanguageFeature::SwitchExpressions);
+                w.write_all(b"yield ")?;
+                self.write_statement_value(lang, ctx, value, &mut req, w)?;
+                w.write_all(b";\n")?;
+            }
+            StmtKind::If {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let mut if_req =
+                    self.write_if(lang, ctx, condition, then, otherwise.as_deref(), w)?;
+                req.line_map.0.append(&mut if_req.line_map.0);
+                req.include(if_req);
+            }
+            StmtKind::Switch { selector, cases } => {
+                let mut switch_req = self.write_switch(lang, ctx, selector, cases, false, w)?;
+                req.line_map.0.append(&mut switch_req.line_map.0);
+                req.include(switch_req);
+            }
+            StmtKind::Break => w.write_all(b"break;\n")?,
+            StmtKind::Block(block) => {
+                let (body, mut body_req) = self.generate(lang, &(), block)?;
+                w.write_all(b"{\n")?;
+                lang.style.write_indented(w, body.as_bytes(), 1)?;
+                w.write_all(b"}\n")?;
+                req.line_map
+                    .include(std::mem::take(&mut body_req.line_map), 1);
+                req.include(body_req);
+            }
+            StmtKind::Comment(text) => writeln!(w, "// {}", text)?,
+        }
+
+        Ok(req)
     }
 }
 
-impl<'m, 'data, B: GeneratorBackend> GenerateCode<InstructionComment, CodeGenContext<'m, 'data>>
-    for B
-{
+impl GenerateCode<Block> for JavaBackend {
+    /// Writes statements of a block without braces or indentation; line
+    /// mappings are relative to the first statement.
     fn write_value<W: std::io::Write>(
         &self,
-        _: &Self::LanguageContext,
-        _: &CodeGenContext<'m, 'data>,
-        input: &InstructionComment,
+        lang: &Self::LanguageContext,
+        _: &(),
+        input: &Block,
         w: &mut W,
     ) -> Result<Self::ScopeRequirements, std::io::Error> {
-        w.write(b"// asm: ")?;
-        w.write(input.0.op().name().as_bytes())?;
-        for arg in input.0.args() {
-            write!(w, " 0x{:X}", *arg)?;
+        let mut req = JavaScopeRequirements::default();
+        let mut line_map = LineMap::default();
+        let mut lines = 0;
+
+        for statement in &input.statements {
+            let (mut source, mut stmt_req) = self.generate(lang, &FLAT, statement)?;
+            if !lang.style.fits(source.as_bytes(), BODY_INDENT) {
+                (source, stmt_req) = self.generate(lang, &WRAP, statement)?;
+            }
+            if source.is_empty() {
+                continue;
+            }
+            w.write_all(source.as_bytes())?;
+            line_map.include(std::mem::take(&mut stmt_req.line_map), lines);
+            req.include(stmt_req);
+            lines += line_count(source.as_bytes());
+        }
+
+        req.line_map = line_map;
+        Ok(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        gen::{java::ast::Origin, GeneratorBuilder},
+        ir::value::Literal,
+    };
+    use jvm_class_format::{ClassPath, JVMPrimitive};
+
+    fn call(name: &str) -> Stmt {
+        Stmt {
+            origin: Some(Origin {
+                method: "run()V".to_string(),
+                offset: 0,
+                original_line: None,
+            }),
+            kind: StmtKind::Expr(Expr::Call {
+                target: None,
+                name: name.to_string(),
+                args: vec![],
+            }),
         }
-        w.write(b"\n")?;
+    }
 
-        Ok(Default::default())
+    fn branch(condition: bool, then: Stmt, otherwise: Option<Stmt>) -> Stmt {
+        StmtKind::If {
+            condition: Expr::Literal(Literal::Boolean(condition)),
+            then: Block {
+                statements: vec![then],
+            },
+            otherwise: otherwise.map(Box::new),
+        }
+        .into()
+    }
+
+    #[test]
+    fn else_if_chain() {
+        let lang = GeneratorBuilder::java().build();
+        let block = Block {
+            statements: vec![branch(
+                true,
+                call("a"),
+                Some(branch(false, call("b"), Some(call("c")))),
+            )],
+        };
+
+        let (code, req) = JavaBackend.generate(&lang, &(), &block).unwrap();
+        assert_eq!(
+            code,
+            "if (true) {\n  a();\n} else if (false) {\n  b();\n} else {\n  c();\n}\n"
+        );
+        assert_eq!(
+            req.line_map
+                .0
+                .iter()
+                .map(|it| it.output_line)
+                .collect::<Vec<_>>(),
+            [2, 4, 6]
+        );
+    }
+
+    #[test]
+    fn signed_operands_of_reference_casts() {
+        let lang = GeneratorBuilder::java().build();
+        let cast = |ty: Type, value: Expr| Expr::Cast {
+            ty,
+            value: Box::new(value),
+        };
+        let integer = || Type::Class(ClassPath::java_lang_class("Integer"));
+        let cases = [
+            (
+                cast(integer(), Expr::Literal(Literal::Int(-1))),
+                "(Integer) (-1)",
+            ),
+            (
+                cast(
+                    integer(),
+                    Expr::unary(UnaryOp::Neg, Expr::Name("x".to_string())),
+                ),
+                "(Integer) (-x)",
+            ),
+            (
+                cast(
+                    Type::Primitive(JVMPrimitive::TLong),
+                    Expr::Literal(Literal::Int(-1)),
+                ),
+                "(long) -1",
+            ),
+            (
+                cast(integer(), Expr::Literal(Literal::Int(1))),
+                "(Integer) 1",
+            ),
+        ];
+        for (expr, expected) in cases {
+            let (code, _) = JavaBackend.generate(&lang, &FLAT, &expr).unwrap();
+            assert_eq!(code, expected);
+        }
     }
 }
@@ -1,54 +1,264 @@
-use jvm_class_format::{AccessFlags, Member};
+use std::collections::HashMap;
 
-use crate::gen::{
-    java::{JavaBackend, JavaScopeRequirements},
-    GenerateCode,
+use jvm_class_format::{Class, ClassPath, FieldAccessFlags};
+
+use crate::{
+    gen::{
+        java::{
+            ast::{FieldDecl, Modifier},
+            code::ExprContext,
+            lines::LineMapping,
+            write_modifiers, JavaBackend, JavaScopeRequirements,
+        },
+        GenerateCode,
+    },
+    ir::{
+        expression::{EmptyConstructor, EmptySuperCall, Expression},
+        value::{InvokeKind, StackValue},
+        Statement,
+    },
 };
 
-pub struct FieldContext;
+/// Field value assigned by `<clinit>` or all constructors, which can be
+/// declared as a field initializer instead.
+#[derive(Debug)]
+pub struct HoistedInitializer {
+    /// Index of the method the value was taken from.
+    pub method: usize,
+    /// Bytecode offset of the assignment in the method.
+    pub offset: usize,
+    pub value: StackValue,
+}
+
+/// Checks whether a value can be moved into the initializer of the field at
+/// `field_index` without changing its meaning.
+fn is_simple_initializer(class: &Class, value: &StackValue, field_index: usize) -> bool {
+    !value.any(&mut |it| match it {
+        StackValue::Local { .. } | StackValue::Uninitialized(_) => true,
+        // initializers can't reference fields declared after them
+        StackValue::GetStatic(field) | StackValue::GetField { field, .. }
+            if field.class == class.class_name =>
+        {
+            class
+                .fields
+                .iter()
+                .position(|it| it.name == field.name)
+                .is_none_or(|i| i >= field_index)
+        }
+        _ => false,
+    })
+}
+
+fn field_index(class: &Class, name: &str, is_static: bool) -> Option<usize> {
+    class.fields.iter().position(|it| {
+        it.name == name && it.field_flags().contains(FieldAccessFlags::STATIC) == is_static
+    })
+}
+
+/// Returns the class the constructor body delegates to first, if the first
+/// statement is a constructor call.
+fn constructor_call(body: &[Statement]) -> Option<&ClassPath> {
+    match &body.first()?.expression {
+        Expression::Super(EmptySuperCall(class)) => Some(class),
+        Expression::Value(StackValue::Invoke {
+            kind: InvokeKind::Special,
+            method,
+            target: Some(target),
+            ..
+        }) if method.is_constructor() && **target == StackValue::This => Some(&method.class),
+        _ => None,
+    }
+}
+
+/// Moves leading static field assignments out of `<clinit>` and field
+/// assignments shared by all constructors into field initializers.
+///
+/// Assignments are only hoisted while they follow field declaration order, so
+/// initialization order is preserved.
+pub fn hoist_initializers(
+    class: &Class,
+    bodies: &mut [Option<Vec<Statement>>],
+) -> HashMap<String, HoistedInitializer> {
+    let mut result = HashMap::new();
 
-impl FieldContext {
-    pub fn signature(access_flags: AccessFlags) -> String {
-        let mut result = String::with_capacity(64);
-        if access_flags.contains(AccessFlags::PUBLIC) {
-            result.push_str("public");
-        } else if access_flags.contains(AccessFlags::PROTECTED) {
-            result.push_str("protected");
-        } else if access_flags.contains(AccessFlags::PRIVATE) {
-            result.push_str("private");
+    let clinit = class.methods.iter().position(|it| it.name == "<clinit>");
+    if let Some((method, Some(body))) = clinit.map(|i| (i, &mut bodies[i])) {
+        let mut last = None;
+        let mut count = 0;
+        for statement in body.iter() {
+            let Expression::PutStatic(put) = &statement.expression else {
+                break;
+            };
+            if put.field.class != class.class_name {
+                break;
+            }
+            let Some(index) = field_index(class, &put.field.name, true) else {
+                break;
+            };
+            if last.is_some_and(|last| index <= last)
+                || !is_simple_initializer(class, &put.value, index)
+            {
+                break;
+            }
+            last = Some(index);
+            count += 1;
+            result.insert(
+                put.field.name.clone(),
+                HoistedInitializer {
+                    method,
+                    offset: statement.offset,
+                    value: put.value.clone(),
+                },
+            );
+        }
+        body.drain(..count);
+    }
+
+    // constructors delegating to this(...) don't run initializers
+    let constructors: Vec<usize> = class
+        .methods
+        .iter()
+        .enumerate()
+        .filter(|(_, it)| it.is_constructor())
+        .map(|(i, _)| i)
+        .filter(|i| {
+            bodies[*i]
+                .as_deref()
+                .and_then(constructor_call)
+                .is_none_or(|it| *it != class.class_name)
+        })
+        .collect();
+
+    let Some(&first) = constructors.first() else {
+        return result;
+    };
+    let all_call_super = constructors
+        .iter()
+        .all(|i| bodies[*i].as_deref().and_then(constructor_call).is_some());
+    if !all_call_super {
+        return result;
+    }
+
+    let mut last = None;
+    let mut count = 0;
+    let first_body = bodies[first].as_deref().unwrap_or_default();
+    for (position, statement) in first_body.iter().enumerate().skip(1) {
+        let Expression::PutField(put) = &statement.expression else {
+            break;
+        };
+        if *put.target != StackValue::This || put.field.class != class.class_name {
+            break;
+        }
+        let Some(index) = field_index(class, &put.field.name, false) else {
+            break;
+        };
+        if last.is_some_and(|last| index <= last)
+            || !is_simple_initializer(class, &put.value, index)
+        {
+            break;
+        }
+        let shared = constructors.iter().all(|i| {
+            matches!(
+                bodies[*i]
+                    .as_deref()
+                    .and_then(|it| it.get(position))
+                    .map(|it| &it.expression),
+                Some(Expression::PutField(other)) if other == put
+            )
+        });
+        if !shared {
+            break;
         }
+        last = Some(index);
+        count += 1;
+        result.insert(
+            put.field.name.clone(),
+            HoistedInitializer {
+                method: first,
+                offset: statement.offset,
+                value: put.value.clone(),
+            },
+        );
+    }
 
-        if access_flags.contains(AccessFlags::STATIC) {
-            if !result.is_empty() {
-                result.push(' ');
+    if count > 0 {
+        for i in constructors {
+            let Some(body) = bodies[i].as_mut() else {
+                continue;
+            };
+            body.drain(1..=count);
+            if body.len() == 1 && matches!(body[0].expression, Expression::Super(_)) {
+                body[0].expression = Expression::EmptyConstructor(EmptyConstructor);
             }
-            result.push_str("static");
         }
+    }
+
+    result
+}
+
+/// Modifiers of a field declaration.
+pub fn field_modifiers(access_flags: FieldAccessFlags, interface: bool) -> Vec<Modifier> {
+    // interface fields are implicitly public, static and final
+    let access_flags = if interface {
+        access_flags
+            - (FieldAccessFlags::PUBLIC | FieldAccessFlags::STATIC | FieldAccessFlags::FINAL)
+    } else {
+        access_flags
+    };
+
+    let mut result = Vec::with_capacity(4);
 
-        result
+    // visibility is one of following
+    if access_flags.contains(FieldAccessFlags::PUBLIC) {
+        result.push(Modifier::Public);
+    } else if access_flags.contains(FieldAccessFlags::PROTECTED) {
+        result.push(Modifier::Protected);
+    } else if access_flags.contains(FieldAccessFlags::PRIVATE) {
+        result.push(Modifier::Private);
     }
+
+    if access_flags.contains(FieldAccessFlags::STATIC) {
+        result.push(Modifier::Static);
+    }
+
+    if access_flags.contains(FieldAccessFlags::FINAL) {
+        result.push(Modifier::Final);
+    }
+
+    if access_flags.contains(FieldAccessFlags::TRANSIENT) {
+        result.push(Modifier::Transient);
+    }
+
+    if access_flags.contains(FieldAccessFlags::VOLATILE) {
+        result.push(Modifier::Volatile);
+    }
+
+    result
 }
 
-impl GenerateCode<Member, FieldContext> for JavaBackend {
+impl GenerateCode<FieldDecl> for JavaBackend {
     fn write_value<W: std::io::Write>(
         &self,
         lang: &Self::LanguageContext,
-        _c: &FieldContext,
-        field: &Member,
+        _: &(),
+        field: &FieldDecl,
         w: &mut W,
     ) -> Result<Self::ScopeRequirements, std::io::Error> {
         let mut req = JavaScopeRequirements::default();
+        if let Some(origin) = &field.origin {
+            req.line_map.0.push(LineMapping::new(1, origin));
+        }
 
-        w.write_all(FieldContext::signature(field.access_flags).as_bytes())?;
-        w.write_all(b" ")?;
-
-        let (type_name, type_req) = self.generate(lang, &(), &field.descriptor.value)?;
-        req.add_import(type_req.imports);
-        w.write_all(type_name.as_bytes())?;
+        write_modifiers(w, &field.modifiers)?;
+        req.include(self.write_value(lang, &(), &field.ty, w)?);
         w.write_all(b" ")?;
-
         w.write_all(field.name.as_bytes())?;
-        w.write(format!(";\n").as_bytes())?;
+
+        if let Some(initializer) = &field.initializer {
+            w.write_all(b" = ")?;
+            req.include(self.write_value(lang, &ExprContext::default(), initializer, w)?);
+        }
+        w.write_all(b";\n")?;
 
         Ok(req)
     }
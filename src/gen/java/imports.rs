@@ -0,0 +1,304 @@
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use jvm_class_format::ClassPath;
+
+use super::classpath::ClassIndex;
+
+/// Returns the top level class a (possibly nested) class is declared in.
+fn top_level(class: &ClassPath) -> ClassPath {
+    ClassPath {
+        package: class.package.clone(),
+        inner_classes: vec![],
+        name: class.name.clone(),
+    }
+}
+
+/// Name of a class relative to its top level class (e.g. `Map.Entry`).
+fn nested_name(class: &ClassPath) -> String {
+    let mut result = class.name.clone();
+    for inner in &class.inner_classes {
+        result.push('.');
+        result.push_str(inner);
+    }
+    result
+}
+
+/// Import group ordering; `java`, then `javax`, then everything else.
+fn import_group(class: &ClassPath) -> u8 {
+    match class.package.first().map(String::as_str) {
+        Some("java") => 0,
+        Some("javax") => 1,
+        _ => 2,
+    }
+}
+
+/// Decides which referenced classes can be used by their simple name and
+/// which imports are needed for that.
+///
+/// Classes that can't be referred to by their simple name (because it's
+/// shadowed by a nested class, or taken by another class with the same simple
+/// name) are written fully qualified instead.
+#[derive(Debug, Clone, Default)]
+pub struct ImportPlan {
+    current: ClassPath,
+    /// Member classes of the current class.
+    nested: Vec<String>,
+    /// Top level classes referred to by their simple name.
+    simple: HashSet<ClassPath>,
+    /// Import lines, grouped.
+    imports: BTreeMap<u8, BTreeSet<String>>,
+}
+
+impl ImportPlan {
+    /// Plans imports for classes `used` by the `current` class.
+    ///
+    /// Packages with at least `wildcard_threshold` imported classes are
+    /// collapsed into a single `*` import, unless `classpath` shows that the
+    /// package has a class named like another one referred to by its simple
+    /// name. Classes named like one in the current package (according to
+    /// `classpath`) are written fully qualified.
+    pub fn new<'a>(
+        current: &ClassPath,
+        nested: &[String],
+        used: impl IntoIterator<Item = &'a ClassPath>,
+        wildcard_threshold: Option<usize>,
+        classpath: &ClassIndex,
+    ) -> ImportPlan {
+        let current = top_level(current);
+
+        let mut by_name: BTreeMap<String, BTreeMap<(u8, String), ClassPath>> = BTreeMap::new();
+        for class in used {
+            let class = top_level(class);
+            // lower rank is preferred when multiple classes share a name
+            let rank = if class.package == current.package {
+                0
+            } else if class.is_in_java_lang() {
+                1
+            } else {
+                2
+            };
+            by_name
+                .entry(class.name.clone())
+                .or_default()
+                .insert((rank, class.full_path()), class);
+        }
+
+        let mut simple = HashSet::with_capacity(by_name.len() + 1);
+        simple.insert(current.clone());
+
+        let mut imported: BTreeMap<String, Vec<ClassPath>> = BTreeMap::new();
+        // packages that can't be imported with a '*' without shadowing
+        let mut conflicting = HashSet::new();
+
+        for (name, candidates) in by_name {
+            let mut candidates = candidates.into_values().peekable();
+            // a class of the current package takes precedence over `*`
+            // imports even if it's not referenced, so others named like it
+            // are qualified
+            let taken = candidates
+                .peek()
+                .is_some_and(|it| it.package != current.package)
+                && classpath.contains(&current.package_path(), &name);
+            let shadowed = name == current.name || nested.contains(&name) || taken;
+
+            let winner = if shadowed { None } else { candidates.next() };
+            if let Some(winner) = winner {
+                if winner.package != current.package && !winner.is_in_java_lang() {
+                    imported
+                        .entry(winner.package_path())
+                        .or_default()
+                        .push(winner.clone());
+                }
+                simple.insert(winner);
+            }
+
+            for loser in candidates.filter(|it| *it != current) {
+                conflicting.insert(loser.package_path());
+            }
+        }
+
+        let mut imports: BTreeMap<u8, BTreeSet<String>> = BTreeMap::new();
+        for (package, classes) in imported {
+            // classes of the current package take precedence over `*` imports
+            let ambiguous = simple.iter().any(|it| {
+                it.package != current.package
+                    && it.package_path() != package
+                    && classpath.contains(&package, &it.name)
+            });
+            let collapse = wildcard_threshold.is_some_and(|it| classes.len() >= it)
+                && !conflicting.contains(&package)
+                && !ambiguous;
+            let group = imports.entry(import_group(&classes[0])).or_default();
+            if collapse {
+                group.insert(format!("{}.*", package));
+            } else {
+                group.extend(classes.iter().map(ClassPath::full_path));
+            }
+        }
+
+        ImportPlan {
+            current,
+            nested: nested.to_vec(),
+            simple,
+            imports,
+        }
+    }
+
+    /// Returns the name `class` should be referred to by.
+    pub fn type_name(&self, class: &ClassPath) -> String {
+        let top = top_level(class);
+        if top == self.current
+            && class
+                .inner_classes
+                .first()
+                .is_some_and(|it| self.nested.contains(it))
+        {
+            return class.inner_classes.join(".");
+        }
+
+        if self.simple.contains(&top) {
+            nested_name(class)
+        } else {
+            class.full_path()
+        }
+    }
+
+    /// Writes import declarations, followed by an empty line if there are
+    /// any.
+    pub fn write_imports<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
+        for group in self.imports.values() {
+            for import in group {
+                writeln!(w, "import {};", import)?;
+            }
+            w.write_all(b"\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Name of a class without import planning.
+pub fn simple_type_name(class: &ClassPath) -> String {
+    nested_name(class)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn class(path: &str) -> ClassPath {
+        ClassPath::parse(path).unwrap()
+    }
+
+    #[test]
+    fn conflicts_fall_back_to_qualified_names() {
+        let used = [
+            class("java/util/Date"),
+            class("java/sql/Date"),
+            class("java/lang/String"),
+            class("com/example/String"),
+            class("com/example/Other"),
+            class("java/util/Map$Entry"),
+        ];
+        let plan = ImportPlan::new(
+            &class("com/example/Unit"),
+            &[],
+            &used,
+            None,
+            &ClassIndex::default(),
+        );
+
+        assert_eq!(plan.type_name(&class("java/sql/Date")), "Date");
+        assert_eq!(plan.type_name(&class("java/util/Date")), "java.util.Date");
+        assert_eq!(plan.type_name(&class("com/example/String")), "String");
+        assert_eq!(
+            plan.type_name(&class("java/lang/String")),
+            "java.lang.String"
+        );
+        assert_eq!(plan.type_name(&class("java/util/Map$Entry")), "Map.Entry");
+
+        let mut out = Vec::new();
+        plan.write_imports(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "import java.sql.Date;\nimport java.util.Map;\n\n"
+        );
+    }
+
+    #[test]
+    fn nested_classes_shadow_imports() {
+        let used = [class("java/util/List"), class("com/example/Unit$List")];
+        let plan = ImportPlan::new(
+            &class("com/example/Unit"),
+            &["List".to_string()],
+            &used,
+            None,
+            &ClassIndex::default(),
+        );
+
+        assert_eq!(plan.type_name(&class("java/util/List")), "java.util.List");
+        assert_eq!(plan.type_name(&class("com/example/Unit$List")), "List");
+    }
+
+    #[test]
+    fn wildcard_collapsing() {
+        let used = [
+            class("java/util/List"),
+            class("java/util/Map"),
+            class("java/io/File"),
+            class("org/example/Thing"),
+        ];
+        let plan = ImportPlan::new(&class("Unit"), &[], &used, Some(2), &ClassIndex::default());
+
+        let mut out = Vec::new();
+        plan.write_imports(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "import java.io.File;\nimport java.util.*;\n\nimport org.example.Thing;\n\n"
+        );
+    }
+
+    #[test]
+    fn classpath_prevents_ambiguous_wildcards() {
+        let used = [
+            class("java/lang/String"),
+            class("org/example/Thing"),
+            class("org/example/Other"),
+        ];
+        let mut classpath = ClassIndex::default();
+        classpath.add(&class("org/example/String"));
+        let plan = ImportPlan::new(&class("Unit"), &[], &used, Some(2), &classpath);
+
+        let mut out = Vec::new();
+        plan.write_imports(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "import org.example.Other;\nimport org.example.Thing;\n\n"
+        );
+    }
+
+    #[test]
+    fn same_package_classes_take_precedence() {
+        let used = [
+            class("org/example/Thing"),
+            class("org/example/Other"),
+            class("org/example/Third"),
+        ];
+        let mut classpath = ClassIndex::default();
+        classpath.add(&class("com/example/Unit"));
+        classpath.add(&class("com/example/Thing"));
+        let plan = ImportPlan::new(&class("com/example/Unit"), &[], &used, Some(2), &classpath);
+
+        assert_eq!(
+            plan.type_name(&class("org/example/Thing")),
+            "org.example.Thing"
+        );
+        assert_eq!(plan.type_name(&class("org/example/Other")), "Other");
+
+        let mut out = Vec::new();
+        plan.write_imports(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "import org.example.Other;\nimport org.example.Third;\n\n"
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use jvm_class_format::attribute::{AsData, CodeData, LineNumberTable};
+
+use super::ast::Origin;
+
+/// Associates a line of generated source with the bytecode it was generated
+/// from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineMapping {
+    /// Line in generated source, starting at 1.
+    pub output_line: usize,
+    /// Name and descriptor of the method containing the bytecode, e.g.
+    /// `run(I)V`.
+    pub method: String,
+    /// Offset of the first instruction in method bytecode.
+    pub offset: usize,
+    /// Line in the original source, if the class contains line numbers.
+    pub original_line: Option<u16>,
+    /// Lines of the declaration written right before the mapped line (e.g.
+    /// the signature of a method for its first statement), which are kept
+    /// together with it.
+    pub header_lines: usize,
+}
+
+impl LineMapping {
+    pub fn new(output_line: usize, origin: &Origin) -> Self {
+        LineMapping {
+            output_line,
+            method: origin.method.clone(),
+            offset: origin.offset,
+            original_line: origin.original_line,
+            header_lines: 0,
+        }
+    }
+}
+
+/// Output line to bytecode mappings, ordered by output line.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LineMap(pub Vec<LineMapping>);
+
+impl LineMap {
+    /// Moves all mappings `lines` lines down.
+    pub fn shift(&mut self, lines: usize) {
+        for mapping in &mut self.0 {
+            mapping.output_line += lines;
+        }
+    }
+
+    /// Appends mappings of code that was written starting at line `at`.
+    pub fn include(&mut self, mut other: LineMap, at: usize) {
+        other.shift(at);
+        self.0.append(&mut other.0);
+    }
+
+    /// Writes the map as tab separated `output line, method, offset,
+    /// original line` rows.
+    pub fn write_to<W: std::io::Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
+        for mapping in &self.0 {
+            write!(
+                w,
+                "{}\t{}\t{}\t",
+                mapping.output_line, mapping.method, mapping.offset
+            )?;
+            match mapping.original_line {
+                Some(line) => writeln!(w, "{}", line)?,
+                None => writeln!(w, "-")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the original source line of the instruction at `offset`.
+pub fn original_line(code: &CodeData, offset: usize) -> Option<u16> {
+    let lines: &LineNumberTable = code.attributes.get("LineNumberTable")?.as_data().ok()?;
+    lines
+        .table
+        .iter()
+        .filter(|it| it.start_pc as usize <= offset)
+        .max_by_key(|it| it.start_pc)
+        .map(|it| it.line_number)
+}
+
+/// Number of lines in `source` (as counted by line terminators).
+pub fn line_count(source: &[u8]) -> usize {
+    source.iter().filter(|it| **it == b'\n').count()
+}
+
+/// Inserts empty lines into `source` so that mapped statements end up on
+/// their original lines where possible, and updates the `map` accordingly.
+///
+/// Statements are never moved up, so lines that are already past their
+/// original line are left as they are. Padding for the first statement of a
+/// method is inserted before the method declaration.
+pub fn preserve_lines(source: &[u8], map: &mut LineMap) -> Vec<u8> {
+    // (line to pad before, number of lines)
+    let mut padding = Vec::new();
+    let mut shift = 0;
+    for mapping in &map.0 {
+        let Some(target) = mapping.original_line.map(|it| it as usize) else {
+            continue;
+        };
+        let current = mapping.output_line + shift;
+        if target <= current {
+            continue;
+        }
+        let before = mapping.output_line - mapping.header_lines;
+        padding.push((before, target - current));
+        shift += target - current;
+    }
+
+    let mut result = Vec::with_capacity(source.len() + shift);
+    let mut pending = padding.iter().peekable();
+    for (i, line) in source.split_inclusive(|it| *it == b'\n').enumerate() {
+        while let Some((_, count)) = pending.next_if(|(before, _)| *before == i + 1) {
+            result.extend(std::iter::repeat_n(b'\n', *count));
+        }
+        result.extend_from_slice(line);
+    }
+
+    for mapping in &mut map.0 {
+        mapping.output_line += padding
+            .iter()
+            .take_while(|(before, _)| *before <= mapping.output_line)
+            .map(|(_, count)| count)
+            .sum::<usize>();
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mapping(output_line: usize, method: &str, original_line: u16) -> LineMapping {
+        LineMapping {
+            output_line,
+            method: method.to_string(),
+            offset: 0,
+            original_line: Some(original_line),
+            header_lines: 0,
+        }
+    }
+
+    #[test]
+    fn pads_statements_to_original_lines() {
+        let source = b"class A {\n  void a() {\n    a();\n    b();\n  }\n}\n";
+        let mut map = LineMap(vec![mapping(3, "a()V", 5), mapping(4, "a()V", 7)]);
+        map.0[0].header_lines = 1;
+
+        let result = preserve_lines(source, &mut map);
+
+        assert_eq!(
+            String::from_utf8(result).unwrap(),
+            "class A {\n\n\n  void a() {\n    a();\n\n    b();\n  }\n}\n"
+        );
+        assert_eq!(
+            map.0.iter().map(|it| it.output_line).collect::<Vec<_>>(),
+            [5, 7]
+        );
+    }
+}
@@ -0,0 +1,175 @@
+use std::fmt::Write as _;
+
+use crate::{
+    gen::{
+        java::{JavaBackend, JavaScopeRequirements},
+        GenerateCode,
+    },
+    ir::value::Literal,
+};
+
+#[derive(Debug, Default)]
+pub struct LiteralContext {
+    /// Literal is an operand of a bitwise operation and reads better as hex.
+    pub hex: bool,
+}
+
+fn escape_char(c: char, quote: char, escape_unicode: bool, out: &mut String) {
+    match c {
+        '\\' => out.push_str("\\\\"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        '\u{8}' => out.push_str("\\b"),
+        '\u{c}' => out.push_str("\\f"),
+        c if c == quote => {
+            out.push('\\');
+            out.push(c);
+        }
+        c if c.is_control() || (escape_unicode && !c.is_ascii()) => {
+            let mut units = [0; 2];
+            for unit in c.encode_utf16(&mut units) {
+                let _ = write!(out, "\\u{:04X}", unit);
+            }
+        }
+        c => out.push(c),
+    }
+}
+
+pub fn string_literal(value: &str, escape_unicode: bool) -> String {
+    let mut result = String::with_capacity(value.len() + 2);
+    result.push('"');
+    for c in value.chars() {
+        escape_char(c, '"', escape_unicode, &mut result);
+    }
+    result.push('"');
+    result
+}
+
+pub fn char_literal(value: u16, escape_unicode: bool) -> String {
+    let mut result = String::with_capacity(3);
+    result.push('\'');
+    match char::from_u32(value as u32) {
+        Some(c) => escape_char(c, '\'', escape_unicode, &mut result),
+        // unpaired surrogate
+        None => {
+            let _ = write!(result, "\\u{:04X}", value);
+        }
+    }
+    result.push('\'');
+    result
+}
+
+fn float_digits(debug: String) -> String {
+    // Debug output is the shortest representation that round-trips, which
+    // is also what Java uses.
+    debug.replace('e', "E")
+}
+
+pub fn float_literal(value: f32) -> String {
+    if value.is_nan() {
+        return "Float.NaN".to_string();
+    }
+    match value {
+        f32::INFINITY => "Float.POSITIVE_INFINITY".to_string(),
+        f32::NEG_INFINITY => "Float.NEGATIVE_INFINITY".to_string(),
+        f32::MAX => "Float.MAX_VALUE".to_string(),
+        f32::MIN_POSITIVE => "Float.MIN_NORMAL".to_string(),
+        it if it.to_bits() == 1 => "Float.MIN_VALUE".to_string(),
+        it => float_digits(format!("{:?}", it)) + "F",
+    }
+}
+
+pub fn double_literal(value: f64) -> String {
+    if value.is_nan() {
+        return "Double.NaN".to_string();
+    }
+    match value {
+        f64::INFINITY => "Double.POSITIVE_INFINITY".to_string(),
+        f64::NEG_INFINITY => "Double.NEGATIVE_INFINITY".to_string(),
+        f64::MAX => "Double.MAX_VALUE".to_string(),
+        f64::MIN_POSITIVE => "Double.MIN_NORMAL".to_string(),
+        it if it.to_bits() == 1 => "Double.MIN_VALUE".to_string(),
+        it => float_digits(format!("{:?}", it)),
+    }
+}
+
+pub fn int_literal(value: i32, hex: bool) -> String {
+    match value {
+        it if hex && !(0..10).contains(&it) => format!("0x{:X}", it as u32),
+        i32::MAX => "Integer.MAX_VALUE".to_string(),
+        i32::MIN => "Integer.MIN_VALUE".to_string(),
+        it => it.to_string(),
+    }
+}
+
+pub fn long_literal(value: i64, hex: bool) -> String {
+    match value {
+        it if hex && !(0..10).contains(&it) => format!("0x{:X}L", it as u64),
+        i64::MAX => "Long.MAX_VALUE".to_string(),
+        i64::MIN => "Long.MIN_VALUE".to_string(),
+        it => format!("{}L", it),
+    }
+}
+
+impl GenerateCode<Literal, LiteralContext> for JavaBackend {
+    fn write_value<W: std::io::Write>(
+        &self,
+        lang: &Self::LanguageContext,
+        ctx: &LiteralContext,
+        input: &Literal,
+        w: &mut W,
+    ) -> Result<Self::ScopeRequirements, std::io::Error> {
+        let mut req = JavaScopeRequirements::default();
+
+        match input {
+            Literal::Null => w.write_all(b"null")?,
+            Literal::Boolean(it) => write!(w, "{}", it)?,
+            Literal::Char(it) => w.write_all(char_literal(*it, lang.escape_unicode).as_bytes())?,
+            Literal::Int(it) => w.write_all(int_literal(*it, ctx.hex).as_bytes())?,
+            Literal::Long(it) => w.write_all(long_literal(*it, ctx.hex).as_bytes())?,
+            Literal::Float(it) => w.write_all(float_literal(*it).as_bytes())?,
+            Literal::Double(it) => w.write_all(double_literal(*it).as_bytes())?,
+            Literal::String(it) => {
+                w.write_all(string_literal(it, lang.escape_unicode).as_bytes())?
+            }
+            Literal::Class(ty) => {
+                req.include(self.write_value(lang, &(), ty, w)?);
+                w.write_all(b".class")?;
+            }
+        }
+
+        Ok(req)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes() {
+        assert_eq!(string_literal("a\"b\\c\n", false), r#""a\"b\\c\n""#);
+        assert_eq!(string_literal("žaba", false), "\"žaba\"");
+        assert_eq!(string_literal("žaba", true), "\"\\u017Eaba\"");
+        assert_eq!(string_literal("😀", true), "\"\\uD83D\\uDE00\"");
+        assert_eq!(char_literal('\'' as u16, false), "'\\''");
+        assert_eq!(char_literal('"' as u16, false), "'\"'");
+        assert_eq!(char_literal(0xD800, false), "'\\uD800'");
+    }
+
+    #[test]
+    fn numbers() {
+        assert_eq!(float_literal(1.0), "1.0F");
+        assert_eq!(float_literal(1e20), "1E20F");
+        assert_eq!(double_literal(-0.5), "-0.5");
+        assert_eq!(
+            double_literal(f64::NEG_INFINITY),
+            "Double.NEGATIVE_INFINITY"
+        );
+        assert_eq!(int_literal(-256, true), "0xFFFFFF00");
+        assert_eq!(int_literal(7, true), "7");
+        assert_eq!(long_literal(255, true), "0xFFL");
+        assert_eq!(long_literal(i64::MAX, false), "Long.MAX_VALUE");
+    }
+}
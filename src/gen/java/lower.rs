@@ -0,0 +1,1451 @@
+//! Lowering of decompiled classes into the Java [`ast`](super::ast).
+
+use std::collections::HashMap;
+
+use jvm_class_format::{
+    attribute::{
+        self, AnnotationsData, AsData, AttributeValue, CodeData, ElementValue, ModuleData,
+        ModulePackage, ThrowsSignature,
+    },
+    Class, ClassAccessFlags, ClassPath, FieldAccessFlags, JVMPrimitive, JVMType, Member,
+    MethodAccessFlags, ModuleAccessFlags, RequiresFlags,
+};
+
+use crate::{
+    error::LimitExceeded,
+    gen::{
+        java::{
+            ast::{
+                Annotation, AnnotationValue, Block, CaseLabel, CompilationUnit, Delegate,
+                EnumConstant, Expr, FieldDecl, Initializer, MemberDecl, MethodDecl, Modifier,
+                ModuleDecl, ModuleDirective, Origin, Param, Stmt, StmtKind, SwitchCase, Type,
+                TypeDecl, TypeKind, UnaryOp,
+            },
+            class::{
+                class_modifiers, field_order, member_class_names, member_class_order,
+                member_classes, method_order, nested_class_flags, type_kind,
+            },
+            field::{field_modifiers, hoist_initializers},
+            lines::original_line,
+            method::{
+                argument_locals, argument_names, decompile_body, fallback_body, method_modifiers,
+                thrown_types, SkippedMethod,
+            },
+            nested::{is_inner, is_local_class, NestedClasses},
+            JavaContext, LanguageFeature,
+        },
+        GeneratorVerbosity,
+    },
+    ir::{
+        accessor::{is_accessor, Access},
+        any_value,
+        assertion::{
+            is_assertion_status, reads_assertion_status, remove_assertion_status,
+            restore_assertions,
+        },
+        class_literal::{is_class_cache, is_class_helper, uses_class_cache},
+        cleanup::remove_implicit_code,
+        expression::{
+            CaseLabel as IrCaseLabel, EmptySuperCall, Expression, ReturnStatement, SwitchStatement,
+        },
+        switch::{enum_switch, restore_string_switches, restore_type_switches},
+        value::{CompareOp, FieldRef, InvokeKind, Literal, StackValue},
+        Statement,
+    },
+};
+
+#[derive(Debug, Default)]
+pub struct ClassContext {
+    pub class_name: ClassPath,
+    /// Whether the class is an interface.
+    pub interface: bool,
+    /// Whether the class is an enum.
+    pub is_enum: bool,
+
+    pub synthetic: bool,
+    /// Class of the enclosing instance of inner classes.
+    pub outer: Option<ClassPath>,
+}
+
+/// Method code being lowered.
+pub struct BodyContext<'m> {
+    pub class: &'m ClassContext,
+    pub method: &'m Member,
+    pub code: &'m CodeData,
+    /// Names of local variable slots that are known ahead of time (arguments).
+    pub locals: HashMap<usize, String>,
+    pub lang: &'m JavaContext,
+    pub verbosity: GeneratorVerbosity,
+}
+
+impl BodyContext<'_> {
+    pub fn local_name(&self, index: usize) -> String {
+        match self.locals.get(&index) {
+            Some(it) => it.clone(),
+            None => format!("local_{}", index),
+        }
+    }
+
+    fn origin(&self, offset: usize) -> Origin {
+        Origin {
+            method: format!("{}{}", self.method.name, self.method.descriptor),
+            offset,
+            original_line: original_line(self.code, offset),
+        }
+    }
+
+    /// Qualifier of a static member, which is omitted for members of the
+    /// class being lowered.
+    fn static_qualifier(&self, class: &ClassPath) -> Option<Box<Expr>> {
+        if *class == self.class.class_name {
+            None
+        } else {
+            Some(Box::new(Expr::TypeName(Type::Class(class.clone()))))
+        }
+    }
+
+    /// Class of the enclosing instance `value` reads through `this$N` fields
+    /// of inner classes (`this.this$0.this$1`).
+    fn enclosing_instance(&self, value: &StackValue) -> Option<ClassPath> {
+        let StackValue::GetField { target, field } = value else {
+            return None;
+        };
+        if !is_outer_instance(field) {
+            return None;
+        }
+        let inner = match target.as_ref() {
+            StackValue::This if self.class.outer.is_some() => self.class.class_name.clone(),
+            StackValue::This => return None,
+            _ => self.enclosing_instance(target)?,
+        };
+        if inner != field.class {
+            return None;
+        }
+        outer_class(&inner)
+    }
+
+    /// Returns `true` for `this` and enclosing instances of inner classes.
+    fn is_own_instance(&self, value: &StackValue) -> bool {
+        *value == StackValue::This || self.enclosing_instance(value).is_some()
+    }
+
+    pub fn lower_value(&self, value: &StackValue) -> Expr {
+        let lower_all =
+            |values: &[StackValue]| values.iter().map(|it| self.lower_value(it)).collect();
+
+        match value {
+            StackValue::Literal(it) => Expr::Literal(it.clone()),
+            StackValue::This => Expr::This,
+            StackValue::Local { index, .. } => Expr::Name(self.local_name(*index)),
+            StackValue::GetStatic(field) => Expr::Field {
+                target: self.static_qualifier(&field.class),
+                name: field.name.clone(),
+            },
+            StackValue::GetField { target, field } => match self.enclosing_instance(value) {
+                // `this.this$0` is written as `Outer.this`
+                Some(outer) => Expr::Field {
+                    target: Some(Box::new(Expr::TypeName(Type::Class(outer)))),
+                    name: "this".to_string(),
+                },
+                None => Expr::Field {
+                    target: Some(Box::new(self.lower_value(target))),
+                    name: field.name.clone(),
+                },
+            },
+            StackValue::Invoke {
+                kind,
+                method,
+                target,
+                args,
+            } => {
+                // `Outer.access$000(outer)` is written as `outer.secret`
+                if *kind == InvokeKind::Static {
+                    if let Some(access) = self.lang.accessors.inline(method, args) {
+                        return self.lower_access(&access);
+                    }
+                }
+                let mut args: Vec<Expr> = lower_all(args);
+                let target = match (kind, target.as_deref()) {
+                    (InvokeKind::Static, _) => self.static_qualifier(&method.class),
+                    (InvokeKind::Special, Some(StackValue::This)) => {
+                        let own = method.class == self.class.class_name;
+                        if method.is_constructor() {
+                            // name and ordinal of enum constants are implicit
+                            if own && self.class.is_enum {
+                                args.drain(..2.min(args.len()));
+                            }
+                            return Expr::ConstructorCall {
+                                delegate: if own { Delegate::This } else { Delegate::Super },
+                                args,
+                            };
+                        }
+                        Some(Box::new(if own { Expr::This } else { Expr::Super }))
+                    }
+                    (_, Some(target)) => Some(Box::new(self.lower_value(target))),
+                    (_, None) => None,
+                };
+                Expr::Call {
+                    target,
+                    name: method.name.clone(),
+                    args,
+                }
+            }
+            StackValue::Uninitialized(class) => Expr::New {
+                ty: Type::Class(class.clone()),
+                args: vec![],
+            },
+            StackValue::New { class, args } => {
+                // the enclosing instance of `new Inner()` is implicit
+                let args = match args.split_first() {
+                    Some((first, rest))
+                        if self.lang.nested_classes.is_inner(class)
+                            && self.is_own_instance(first) =>
+                    {
+                        rest
+                    }
+                    _ => args.as_slice(),
+                };
+                Expr::New {
+                    ty: Type::Class(class.clone()),
+                    args: lower_all(args),
+                }
+            }
+            StackValue::Binary { op, left, right } => Expr::binary(
+                (*op).into(),
+                self.lower_value(left),
+                self.lower_value(right),
+            ),
+            StackValue::Negate(value) => Expr::unary(UnaryOp::Neg, self.lower_value(value)),
+            StackValue::Cast { ty, value } => Expr::Cast {
+                ty: ty.into(),
+                value: Box::new(self.lower_value(value)),
+            },
+            StackValue::ThreeWayCompare { left, right } => {
+                let class = match left.ty() {
+                    JVMType::TPrimitive(JVMPrimitive::TFloat) => "Float",
+                    JVMType::TPrimitive(JVMPrimitive::TDouble) => "Double",
+                    _ => "Long",
+                };
+                Expr::Call {
+                    target: Some(Box::new(Expr::TypeName(Type::Class(
+                        ClassPath::java_lang_class(class),
+                    )))),
+                    name: "compare".to_string(),
+                    args: vec![self.lower_value(left), self.lower_value(right)],
+                }
+            }
+            StackValue::Compare { op, left, right } => {
+                let ty = left.ty();
+                let right = match right.as_ref() {
+                    StackValue::Literal(it) => Expr::Literal(it.clone().coerce(&ty)),
+                    other => self.lower_value(other),
+                };
+                let left = self.lower_value(left);
+                // booleans are compared with 0 on the JVM
+                match (ty, op, &right) {
+                    (
+                        JVMType::TPrimitive(JVMPrimitive::TBoolean),
+                        CompareOp::Eq | CompareOp::Ne,
+                        Expr::Literal(Literal::Boolean(false)),
+                    ) => match op {
+                        CompareOp::Eq => Expr::unary(UnaryOp::Not, left),
+                        _ => left,
+                    },
+                    _ => Expr::binary((*op).into(), left, right),
+                }
+            }
+            StackValue::NewArray { ty, length, values } => {
+                let length_value = match length.as_ref() {
+                    StackValue::Literal(Literal::Int(it)) => Some(*it as usize),
+                    _ => None,
+                };
+                match (values, length_value) {
+                    (Some(values), Some(length_value)) => {
+                        // elements which weren't stored keep their default value
+                        let mut initializer: Vec<Expr> = lower_all(values);
+                        initializer.resize(length_value, Expr::Literal(Literal::default_value(ty)));
+                        Expr::NewArray {
+                            ty: (&ty.array_of()).into(),
+                            length: None,
+                            initializer: Some(initializer),
+                        }
+                    }
+                    _ => Expr::NewArray {
+                        ty: (&ty.array_of()).into(),
+                        length: Some(Box::new(self.lower_value(length))),
+                        initializer: None,
+                    },
+                }
+            }
+            StackValue::ArrayLoad { array, index } => Expr::Index {
+                array: Box::new(self.lower_value(array)),
+                index: Box::new(self.lower_value(index)),
+            },
+            StackValue::InstanceOf { value, ty } => Expr::InstanceOf {
+                value: Box::new(self.lower_value(value)),
+                ty: ty.into(),
+                binding: None,
+            },
+            StackValue::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let condition = self.lower_value(condition);
+                // `a < b` is compiled into a branch pushing 1 or 0
+                match (then.as_ref(), otherwise.as_ref()) {
+                    (
+                        StackValue::Literal(Literal::Boolean(true)),
+                        StackValue::Literal(Literal::Boolean(false)),
+                    ) => condition,
+                    (
+                        StackValue::Literal(Literal::Boolean(false)),
+                        StackValue::Literal(Literal::Boolean(true)),
+                    ) => Expr::unary(UnaryOp::Not, condition),
+                    (then, otherwise) => Expr::Conditional {
+                        condition: Box::new(condition),
+                        then: Box::new(self.lower_value(then)),
+                        otherwise: Box::new(self.lower_value(otherwise)),
+                    },
+                }
+            }
+            StackValue::Switch(switch) => {
+                let (selector, cases) = self.lower_switch(switch);
+                Expr::Switch {
+                    selector: Box::new(selector),
+                    cases,
+                }
+            }
+            // call sites of unknown bootstrap methods
+            StackValue::InvokeDynamic {
+                bootstrap,
+                name,
+                args,
+                ..
+            } => Expr::Call {
+                target: self.static_qualifier(&bootstrap.class),
+                name: name.clone(),
+                args: lower_all(args),
+            },
+        }
+    }
+
+    fn lower_access(&self, access: &Access) -> Expr {
+        match access {
+            Access::Value(value) => self.lower_value(value),
+            Access::Assign { field, value } => {
+                Expr::assign(self.lower_value(field), self.lower_value(value))
+            }
+            Access::Increment {
+                field,
+                decrement,
+                prefix,
+            } => {
+                let op = match (decrement, prefix) {
+                    (false, true) => UnaryOp::PreIncrement,
+                    (true, true) => UnaryOp::PreDecrement,
+                    (false, false) => UnaryOp::PostIncrement,
+                    (true, false) => UnaryOp::PostDecrement,
+                };
+                Expr::unary(op, self.lower_value(field))
+            }
+        }
+    }
+
+    fn lower_case_label(&self, selector: &StackValue, label: &IrCaseLabel) -> CaseLabel {
+        match label {
+            IrCaseLabel::Int(it) => {
+                CaseLabel::Expr(Expr::Literal(Literal::Int(*it).coerce(&selector.ty())))
+            }
+            IrCaseLabel::String(it) => CaseLabel::Expr(Expr::Literal(Literal::String(it.clone()))),
+            IrCaseLabel::Enum(it) => CaseLabel::Expr(Expr::Name(it.clone())),
+            IrCaseLabel::Null => CaseLabel::Null,
+            IrCaseLabel::Type { ty, binding } => CaseLabel::Pattern {
+                ty: ty.into(),
+                binding: Some(self.local_name(*binding)),
+            },
+            IrCaseLabel::Default => CaseLabel::Default,
+        }
+    }
+
+    /// Lowers the selector and cases of a `switch` statement or expression.
+    fn lower_switch(&self, switch: &SwitchStatement) -> (Expr, Vec<SwitchCase>) {
+        let lower_cases = |label: &dyn Fn(&IrCaseLabel) -> Option<CaseLabel>| {
+            switch
+                .cases
+                .iter()
+                .map(|case| {
+                    Some(SwitchCase {
+                        labels: case.labels.iter().map(label).collect::<Option<_>>()?,
+                        body: self.lower_block(&case.body),
+                    })
+                })
+                .collect::<Option<Vec<_>>>()
+        };
+
+        // switch over an enum with case values taken from its switch map
+        if let Some((value, field)) =
+            enum_switch(switch).filter(|_| self.lang.supports(LanguageFeature::Enums))
+        {
+            let constants = self.lang.switch_maps.get(field);
+            let cases = constants.and_then(|constants| {
+                lower_cases(&|label| match label {
+                    IrCaseLabel::Int(it) => {
+                        Some(CaseLabel::Expr(Expr::Name(constants.get(it)?.clone())))
+                    }
+                    IrCaseLabel::Default => Some(CaseLabel::Default),
+                    _ => None,
+                })
+            });
+            match cases {
+                Some(cases) => return (self.lower_value(value), cases),
+                None => tracing::warn!("unknown enum switch map: {}", field.name),
+            }
+        }
+
+        (
+            self.lower_value(&switch.value),
+            lower_cases(&|label| Some(self.lower_case_label(&switch.value, label)))
+                .expect("all labels are lowered"),
+        )
+    }
+
+    /// Lowers a statement; implicit statements yield `None`.
+    pub fn lower_statement(&self, statement: &Statement) -> Option<Stmt> {
+        let kind = match &statement.expression {
+            Expression::EmptyConstructor(_) => return None,
+            Expression::Super(EmptySuperCall(class)) => {
+                let delegate = if *class == self.class.class_name {
+                    Delegate::This
+                } else if self.verbosity == GeneratorVerbosity::All {
+                    Delegate::Super
+                } else {
+                    return None;
+                };
+                StmtKind::Expr(Expr::ConstructorCall {
+                    delegate,
+                    args: vec![],
+                })
+            }
+            Expression::ReturnStatement(ReturnStatement(value)) => {
+                StmtKind::Return(value.as_ref().map(|it| self.lower_value(it)))
+            }
+            Expression::Comment(it) => {
+                let mut comment = format!("asm: {}", it.0.op().name());
+                for arg in it.0.args() {
+                    comment.push_str(&format!(" 0x{:X}", *arg));
+                }
+                StmtKind::Comment(comment)
+            }
+            // enum constructors call `super(name, ordinal)` implicitly
+            Expression::Value(it) if self.class.is_enum && is_enum_super_call(it) => return None,
+            Expression::Value(it) => StmtKind::Expr(self.lower_value(it)),
+            Expression::StoreLocal(it) => {
+                let name = self.local_name(it.index);
+                let value = self.lower_value(&it.value);
+                if it.declare {
+                    StmtKind::LocalVar {
+                        ty: (&it.ty).into(),
+                        name,
+                        value: Some(value),
+                    }
+                } else {
+                    StmtKind::Expr(Expr::assign(Expr::Name(name), value))
+                }
+            }
+            Expression::PutField(it) => StmtKind::Expr(Expr::assign(
+                Expr::Field {
+                    target: Some(Box::new(self.lower_value(&it.target))),
+                    name: it.field.name.clone(),
+                },
+                self.lower_value(&it.value),
+            )),
+            Expression::PutStatic(it) => StmtKind::Expr(Expr::assign(
+                Expr::Field {
+                    target: self.static_qualifier(&it.field.class),
+                    name: it.field.name.clone(),
+                },
+                self.lower_value(&it.value),
+            )),
+            Expression::ArrayStore(it) => StmtKind::Expr(Expr::assign(
+                Expr::Index {
+                    array: Box::new(self.lower_value(&it.array)),
+                    index: Box::new(self.lower_value(&it.index)),
+                },
+                self.lower_value(&it.value),
+            )),
+            Expression::Throw(it) => StmtKind::Throw(self.lower_value(&it.0)),
+            Expression::If(it) => StmtKind::If {
+                condition: self.lower_value(&it.condition),
+                then: self.lower_block(&it.then),
+                otherwise: it
+                    .otherwise
+                    .as_ref()
+                    .map(|it| Box::new(StmtKind::Block(self.lower_block(it)).into())),
+            },
+            Expression::Switch(it) => {
+                let (selector, cases) = self.lower_switch(it);
+                StmtKind::Switch { selector, cases }
+            }
+            Expression::Break => StmtKind::Break,
+            Expression::Yield(it) => StmtKind::Yield(self.lower_value(it)),
+            Expression::Assert(it) => StmtKind::Assert {
+                condition: self.lower_value(&it.condition),
+                message: it.message.as_ref().map(|it| self.lower_value(it)),
+            },
+        };
+        Some(Stmt {
+            origin: Some(self.origin(statement.offset)),
+            kind,
+        })
+    }
+
+    pub fn lower_block(&self, statements: &[Statement]) -> Block {
+        let mut block = Block {
+            statements: statements
+                .iter()
+                .filter_map(|it| self.lower_statement(it))
+                .collect(),
+        };
+        if self.lang.supports(LanguageFeature::InstanceofPatterns) {
+            bind_instanceof_patterns(&mut block);
+        }
+        if !self.lang.supports(LanguageFeature::SwitchExpressions) {
+            unfold_switch_expressions(&mut block);
+        }
+        block
+    }
+}
+
+/// Turns `if (o instanceof T) { T t = (T) o; ... }` into
+/// `if (o instanceof T t) { ... }`.
+fn bind_instanceof_patterns(block: &mut Block) {
+    for stmt in &mut block.statements {
+        let StmtKind::If {
+            condition:
+                Expr::InstanceOf {
+                    value,
+                    ty,
+                    binding: binding @ None,
+                },
+            then,
+            ..
+        } = &mut stmt.kind
+        else {
+            continue;
+        };
+        let Some(Stmt {
+            kind:
+                StmtKind::LocalVar {
+                    ty: local_ty,
+                    name,
+                    value:
+                        Some(Expr::Cast {
+                            ty: cast_ty,
+                            value: cast_value,
+                        }),
+                },
+            ..
+        }) = then.statements.first()
+        else {
+            continue;
+        };
+        if !matches!(value.as_ref(), Expr::Name(_))
+            || local_ty != ty
+            || cast_ty != ty
+            || cast_value != value
+        {
+            continue;
+        }
+        *binding = Some(name.clone());
+        then.statements.remove(0);
+    }
+}
+
+/// Turns `return switch (...) {...};`, `T x = switch (...) {...};` and
+/// `x = switch (...) {...};` into `switch` statements whose cases return or
+/// assign the yielded values, for targets without switch expressions.
+///
+/// Switch expressions nested in other expressions are left as they are.
+fn unfold_switch_expressions(block: &mut Block) {
+    let mut i = 0;
+    while i < block.statements.len() {
+        match unfold_switch_expression(&block.statements[i]) {
+            Some(unfolded) => {
+                let count = unfolded.len();
+                block.statements.splice(i..=i, unfolded);
+                i += count;
+            }
+            None => i += 1,
+        }
+    }
+}
+
+fn unfold_switch_expression(stmt: &Stmt) -> Option<Vec<Stmt>> {
+    let (declaration, assigned, value) = match &stmt.kind {
+        StmtKind::Return(Some(value)) => (None, None, value),
+        StmtKind::LocalVar {
+            ty,
+            name,
+            value: Some(value),
+        } => (
+            Some(StmtKind::LocalVar {
+                ty: ty.clone(),
+                name: name.clone(),
+                value: None,
+            }),
+            Some(name),
+            value,
+        ),
+        StmtKind::Expr(Expr::Assign { target, value }) => match target.as_ref() {
+            Expr::Name(name) => (None, Some(name), value.as_ref()),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let Expr::Switch { selector, cases } = value else {
+        return None;
+    };
+
+    let mut cases = cases.clone();
+    for case in &mut cases {
+        if !replace_yields(&mut case.body, assigned, false) {
+            return None;
+        }
+    }
+
+    let mut result = Vec::with_capacity(2);
+    result.extend(declaration.map(|kind| Stmt {
+        origin: stmt.origin.clone(),
+        kind,
+    }));
+    result.push(Stmt {
+        origin: stmt.origin.clone(),
+        kind: StmtKind::Switch {
+            selector: selector.as_ref().clone(),
+            cases,
+        },
+    });
+    Some(result)
+}
+
+/// Replaces `yield` statements of a switch expression in `block` with a
+/// `return`, or an assignment to `assigned` followed by `break`.
+///
+/// Returns `false` if a `break` would leave a nested `switch` statement
+/// instead.
+fn replace_yields(block: &mut Block, assigned: Option<&String>, nested: bool) -> bool {
+    let mut statements = Vec::with_capacity(block.statements.len());
+    for stmt in std::mem::take(&mut block.statements) {
+        let mut stmt = match stmt {
+            Stmt {
+                origin,
+                kind: StmtKind::Yield(value),
+            } => {
+                if assigned.is_some() && nested {
+                    return false;
+                }
+                let kind = match assigned {
+                    None => StmtKind::Return(Some(value)),
+                    Some(name) => StmtKind::Expr(Expr::assign(Expr::Name(name.clone()), value)),
+                };
+                let replaced = Stmt { origin, kind };
+                // the yielded value can itself be a switch expression
+                statements.extend(unfold_switch_expression(&replaced).unwrap_or(vec![replaced]));
+                if assigned.is_some() {
+                    statements.push(StmtKind::Break.into());
+                }
+                continue;
+            }
+            other => other,
+        };
+        let replaced = match &mut stmt.kind {
+            StmtKind::If {
+                then, otherwise, ..
+            } => {
+                replace_yields(then, assigned, nested)
+                    && otherwise
+                        .as_deref_mut()
+                        .is_none_or(|it| replace_yields_in(it, assigned, nested))
+            }
+            StmtKind::Block(inner) => replace_yields(inner, assigned, nested),
+            StmtKind::Switch { cases, .. } => cases
+                .iter_mut()
+                .all(|it| replace_yields(&mut it.body, assigned, true)),
+            _ => true,
+        };
+        if !replaced {
+            return false;
+        }
+        statements.push(stmt);
+    }
+    block.statements = statements;
+    true
+}
+
+/// [`replace_yields`] for a single statement, e.g. an `else` branch.
+fn replace_yields_in(stmt: &mut Stmt, assigned: Option<&String>, nested: bool) -> bool {
+    let mut block = Block {
+        statements: vec![stmt.clone()],
+    };
+    if !replace_yields(&mut block, assigned, nested) {
+        return false;
+    }
+    *stmt = match block.statements.len() {
+        1 => block.statements.remove(0),
+        _ => StmtKind::Block(block).into(),
+    };
+    true
+}
+
+fn method_code(method: &Member) -> Option<&CodeData> {
+    method
+        .attributes
+        .get("Code")
+        .and_then(|it| it.as_data().ok())
+}
+
+/// Lowers a method; methods that are implicit in source yield `None`.
+fn lower_method(
+    lang: &JavaContext,
+    class: &Class,
+    class_ctx: &ClassContext,
+    verbosity: GeneratorVerbosity,
+    method: &Member,
+    body: Option<&[Statement]>,
+) -> Option<MemberDecl> {
+    let arg_names = argument_names(&class.constant_pool, method);
+    // enum constructors take the name and ordinal of the constant first
+    let implicit_args = if method.is_constructor() && class_ctx.is_enum {
+        2
+    } else {
+        0
+    };
+
+    // constructors of inner classes take and store the enclosing instance first
+    let outer_instance = method.is_constructor() && class_ctx.outer.is_some();
+    let without_outer: Vec<Statement>;
+    let body = match body {
+        Some(statements) if outer_instance => {
+            without_outer = statements
+                .iter()
+                .filter(|it| !stores_outer_instance(it))
+                .cloned()
+                .collect();
+            Some(without_outer.as_slice())
+        }
+        _ => body,
+    };
+
+    let body = match (method_code(method), body) {
+        (Some(code), Some(statements)) => Some(
+            BodyContext {
+                class: class_ctx,
+                method,
+                code,
+                locals: argument_locals(method, &arg_names),
+                lang,
+                verbosity,
+            }
+            .lower_block(statements),
+        ),
+        // abstract and native methods
+        _ => None,
+    };
+
+    if method.name == "<clinit>" {
+        return body.filter(|it| !it.statements.is_empty()).map(|body| {
+            MemberDecl::Initializer(Initializer {
+                is_static: true,
+                body,
+            })
+        });
+    }
+
+    if class_ctx.is_enum
+        && method.is_constructor()
+        && method.descriptor.arguments.len() == implicit_args
+        && body.as_ref().is_some_and(|it| it.statements.is_empty())
+    {
+        return None;
+    }
+
+    if verbosity == GeneratorVerbosity::All
+        && method.is_constructor()
+        && method.descriptor.arguments.len() == usize::from(outer_instance)
+        && body.as_ref().is_some_and(|it| it.statements.is_empty())
+    {
+        return None;
+    }
+
+    let (return_type, name) = if method.is_constructor() {
+        (None, class_ctx.class_name.simple_name().to_string())
+    } else {
+        (
+            Some(Type::from(&method.descriptor.value)),
+            method.name.clone(),
+        )
+    };
+
+    Some(MemberDecl::Method(MethodDecl {
+        comment: if method.is_bridge() {
+            Some("bridge method".to_string())
+        } else if class_ctx.synthetic || method.is_synthetic() {
+            Some("synthetic method".to_string())
+        } else {
+            None
+        },
+        modifiers: method_modifiers(
+            method.method_flags(),
+            class_ctx.interface,
+            class.compiler_info.major,
+        ),
+        return_type,
+        name,
+        params: method
+            .descriptor
+            .arguments
+            .iter()
+            .zip(arg_names)
+            .skip(usize::from(outer_instance) + implicit_args)
+            .map(|(ty, name)| Param {
+                ty: ty.into(),
+                name,
+            })
+            .collect(),
+        // older targets take the array itself
+        varargs: method.method_flags().contains(MethodAccessFlags::VARARGS)
+            && lang.supports(LanguageFeature::Varargs),
+        throws: thrown_types(method, lang.supports(LanguageFeature::Generics))
+            .into_iter()
+            .map(|it| match it {
+                ThrowsSignature::Class(class) => Type::Class(class),
+                ThrowsSignature::TypeVariable(name) => Type::Variable(name),
+            })
+            .collect(),
+        body,
+    }))
+}
+
+/// Class a nested class is declared in; `None` for top level classes.
+fn outer_class(class: &ClassPath) -> Option<ClassPath> {
+    let (_, outer) = class.inner_classes.split_last()?;
+    Some(ClassPath {
+        inner_classes: outer.to_vec(),
+        ..class.clone()
+    })
+}
+
+/// Returns `true` for the synthetic `this$N` field holding the enclosing
+/// instance of inner classes.
+fn is_outer_instance(field: &FieldRef) -> bool {
+    field.name.starts_with("this$")
+}
+
+/// Returns `true` for the `this.this$0 = outer;` store of inner class
+/// constructors.
+fn stores_outer_instance(statement: &Statement) -> bool {
+    matches!(
+        &statement.expression,
+        Expression::PutField(it) if *it.target == StackValue::This && is_outer_instance(&it.field)
+    )
+}
+
+/// Returns `true` for the `Enum(String, int)` constructor call.
+fn is_enum_super_call(value: &StackValue) -> bool {
+    matches!(
+        value,
+        StackValue::Invoke {
+            kind: InvokeKind::Special,
+            method,
+            target: Some(target),
+            ..
+        } if method.is_constructor()
+            && **target == StackValue::This
+            && method.class == ClassPath::java_lang_class("Enum")
+    )
+}
+
+/// Local or anonymous class instantiated in `body`; their declarations
+/// aren't generated, so the code can't refer to them. Bodies of enum
+/// constants are written with the constants.
+fn created_local_class(body: &[Statement], nested: &NestedClasses) -> Option<ClassPath> {
+    let mut found = None;
+    any_value(body, &mut |value| {
+        if let StackValue::New { class, .. } = value {
+            found = Some(class.clone()).filter(|it| is_local_class(it) && !nested.is_enum_body(it));
+        }
+        found.is_some()
+    });
+    found
+}
+
+/// Returns `true` for `values()`, `valueOf(String)` and the synthetic
+/// `$values()` helper, which are declared implicitly by enums.
+fn is_implicit_enum_method(class: &Class, method: &Member) -> bool {
+    if !method.method_flags().contains(MethodAccessFlags::STATIC) {
+        return false;
+    }
+    let returns = |ty: JVMType| method.descriptor.value == ty;
+    let own = || class.class_name.clone();
+    match method.name.as_str() {
+        "values" | "$values" => {
+            method.descriptor.arguments.is_empty()
+                && returns(JVMType::TClassArray {
+                    depth: 1,
+                    inner: own(),
+                })
+        }
+        "valueOf" => {
+            method.descriptor.arguments == [JVMType::TClass(ClassPath::java_lang_class("String"))]
+                && returns(JVMType::TClass(own()))
+        }
+        _ => false,
+    }
+}
+
+/// Returns the constant declared by an enum field initialized with
+/// `new E("NAME", ordinal, args...)`, along with the class of its body if
+/// the constant is created as an anonymous subclass `E$1`.
+fn enum_constant(
+    lang: &JavaContext,
+    class: &Class,
+    name: &str,
+    initializer: &Expr,
+) -> Option<(Vec<Expr>, Option<ClassPath>)> {
+    let Expr::New {
+        ty: Type::Class(ty),
+        args,
+    } = initializer
+    else {
+        return None;
+    };
+    let body = if *ty == class.class_name {
+        None
+    } else if lang.nested_classes.is_enum_body(ty)
+        && lang.nested_classes.get(ty)?.super_name.as_ref() == Some(&class.class_name)
+    {
+        Some(ty.clone())
+    } else {
+        return None;
+    };
+    match args.as_slice() {
+        [Expr::Literal(Literal::String(constant)), _, rest @ ..] if constant == name => {
+            Some((rest.to_vec(), body))
+        }
+        _ => None,
+    }
+}
+
+/// Members of the class body of an enum constant, without the constructor
+/// passing arguments to the enum's one.
+fn lower_enum_body(
+    lang: &JavaContext,
+    verbosity: GeneratorVerbosity,
+    name: &ClassPath,
+) -> (Vec<MemberDecl>, Vec<SkippedMethod>) {
+    let Some(class) = lang.nested_classes.get(name) else {
+        return Default::default();
+    };
+    let decl = lower_type(lang, verbosity, class, class.access_flags);
+    let members = decl
+        .members
+        .into_iter()
+        .filter(|it| !matches!(it, MemberDecl::Method(it) if it.is_constructor()))
+        .collect();
+    (members, decl.skipped_methods)
+}
+
+/// Returns `true` for compiler generated methods left out of generated code.
+///
+/// Accessors are only hidden if their calls are inlined, others stay called.
+fn is_hidden_method(lang: &JavaContext, class: &Class, method: &Member) -> bool {
+    if method.is_bridge() {
+        lang.hide_bridge_methods
+    } else {
+        lang.hide_synthetic_members
+            && is_accessor(method)
+            && lang.accessors.contains(&class.class_name, &method.name)
+    }
+}
+
+/// Lowers annotations retained in class file attributes, visible ones first.
+fn lower_annotations(class: &Class) -> Vec<Annotation> {
+    ["RuntimeVisibleAnnotations", "RuntimeInvisibleAnnotations"]
+        .into_iter()
+        .filter_map(|name| class.attributes.get(name))
+        .filter_map(|it| AsData::<AnnotationsData>::as_data(it).ok())
+        .flat_map(|it| &it.annotations)
+        .map(lower_annotation)
+        .collect()
+}
+
+fn lower_annotation(annotation: &attribute::Annotation) -> Annotation {
+    Annotation {
+        ty: annotation.ty.clone(),
+        elements: annotation
+            .elements
+            .iter()
+            .map(|(name, value)| (name.clone(), lower_element_value(value)))
+            .collect(),
+    }
+}
+
+fn lower_element_value(value: &ElementValue) -> AnnotationValue {
+    let literal = |it| AnnotationValue::Expr(Expr::Literal(it));
+    match value {
+        ElementValue::Boolean(it) => literal(Literal::Boolean(*it)),
+        ElementValue::Byte(it) => literal(Literal::Int(*it as i32)),
+        ElementValue::Char(it) => literal(Literal::Char(*it)),
+        ElementValue::Short(it) => literal(Literal::Int(*it as i32)),
+        ElementValue::Int(it) => literal(Literal::Int(*it)),
+        ElementValue::Long(it) => literal(Literal::Long(*it)),
+        ElementValue::Float(it) => literal(Literal::Float(*it)),
+        ElementValue::Double(it) => literal(Literal::Double(*it)),
+        ElementValue::String(it) => literal(Literal::String(it.clone())),
+        ElementValue::Class(it) => literal(Literal::Class(it.clone())),
+        ElementValue::Enum { ty, name } => AnnotationValue::Expr(Expr::Field {
+            target: Some(Box::new(Expr::TypeName(Type::Class(ty.clone())))),
+            name: name.clone(),
+        }),
+        ElementValue::Annotation(it) => AnnotationValue::Annotation(lower_annotation(it)),
+        ElementValue::Array(values) => {
+            AnnotationValue::Array(values.iter().map(lower_element_value).collect())
+        }
+    }
+}
+
+/// Lowers the `Module` attribute of a `module-info.class`.
+fn lower_module(class: &Class) -> Option<ModuleDecl> {
+    let Some(module) = class
+        .attributes
+        .get("Module")
+        .and_then(|it| AsData::<ModuleData>::as_data(it).ok())
+    else {
+        tracing::warn!("{} is missing the Module attribute", class.class_name);
+        return None;
+    };
+    let package = |name: &str| name.replace('/', ".");
+    // implicit directives (`requires java.base`) aren't declared in source
+    let declared = |mandated: bool, synthetic: bool| !mandated && !synthetic;
+
+    let mut directives = Vec::new();
+    for it in &module.requires {
+        if declared(
+            it.flags.contains(RequiresFlags::MANDATED),
+            it.flags.contains(RequiresFlags::SYNTHETIC),
+        ) {
+            directives.push(ModuleDirective::Requires {
+                module: it.module.clone(),
+                transitive: it.flags.contains(RequiresFlags::TRANSITIVE),
+                is_static: it.flags.contains(RequiresFlags::STATIC_PHASE),
+            });
+        }
+    }
+    let packages = |entries: &[ModulePackage]| {
+        entries
+            .iter()
+            .filter(|it| {
+                declared(
+                    it.flags.contains(ModuleAccessFlags::MANDATED),
+                    it.flags.contains(ModuleAccessFlags::SYNTHETIC),
+                )
+            })
+            .map(|it| (package(&it.package), it.to.clone()))
+            .collect::<Vec<_>>()
+    };
+    for (package, to) in packages(&module.exports) {
+        directives.push(ModuleDirective::Exports { package, to });
+    }
+    for (package, to) in packages(&module.opens) {
+        directives.push(ModuleDirective::Opens { package, to });
+    }
+    directives.extend(module.uses.iter().cloned().map(ModuleDirective::Uses));
+    directives.extend(module.provides.iter().map(|it| ModuleDirective::Provides {
+        service: it.service.clone(),
+        with: it.with.clone(),
+    }));
+
+    Some(ModuleDecl {
+        open: module.flags.contains(ModuleAccessFlags::OPEN),
+        name: module.name.clone(),
+        directives,
+    })
+}
+
+/// Lowers a class file into a compilation unit.
+pub fn lower_class(
+    lang: &JavaContext,
+    verbosity: GeneratorVerbosity,
+    class: &Class,
+) -> CompilationUnit {
+    if class.is_module_info() {
+        return CompilationUnit {
+            module: lower_module(class),
+            ..Default::default()
+        };
+    }
+    if class.is_package_info() {
+        return CompilationUnit {
+            package: class.class_name.package_path(),
+            package_annotations: lower_annotations(class),
+            ..Default::default()
+        };
+    }
+
+    let flags = if class.class_name.inner_classes.is_empty() {
+        class.access_flags
+    } else {
+        tracing::warn!("nested class {} is generated on its own", class.class_name);
+        nested_class_flags(class, &class.class_name).unwrap_or(class.access_flags)
+    };
+
+    CompilationUnit {
+        package: class.class_name.package_path(),
+        types: vec![lower_type(lang, verbosity, class, flags)],
+        ..Default::default()
+    }
+}
+
+/// Lowers a class declared with `flags`, along with its member classes.
+fn lower_type(
+    lang: &JavaContext,
+    verbosity: GeneratorVerbosity,
+    class: &Class,
+    flags: ClassAccessFlags,
+) -> TypeDecl {
+    let class_ctx = ClassContext {
+        class_name: class.class_name.clone(),
+        interface: flags.contains(ClassAccessFlags::INTERFACE),
+        is_enum: flags.contains(ClassAccessFlags::ENUM),
+        synthetic: class.is_synthetic(),
+        outer: outer_class(&class.class_name).filter(|_| is_inner(flags)),
+    };
+
+    let mut skipped_methods = Vec::new();
+    let mut bodies: Vec<_> = class
+        .methods
+        .iter()
+        .map(|method| {
+            match decompile_body(class, method, &lang.method_limits)?.and_then(|body| {
+                match created_local_class(&body, &lang.nested_classes) {
+                    Some(local) => Err(LimitExceeded::LocalClass(local)),
+                    None => Ok(body),
+                }
+            }) {
+                Ok(body) => Some(body),
+                Err(reason) => {
+                    tracing::warn!(
+                        "leaving {}.{} as assembly: {}",
+                        class.class_name,
+                        method.name,
+                        reason
+                    );
+                    let body = fallback_body(method, &reason);
+                    skipped_methods.push(SkippedMethod::new(class, method, reason));
+                    Some(body)
+                }
+            }
+        })
+        .collect();
+    // otherwise the `hashCode()` switch and `equals` chain are left as is
+    if lang.supports(LanguageFeature::StringSwitch) {
+        for body in bodies.iter_mut().flatten() {
+            restore_string_switches(body);
+        }
+    }
+    // otherwise the `typeSwitch` call site is left as is
+    if lang.supports(LanguageFeature::SwitchPatterns) {
+        for body in bodies.iter_mut().flatten() {
+            restore_type_switches(body);
+        }
+    }
+    // boxing and variable arity calls are written out for older targets
+    if lang.supports(LanguageFeature::Autoboxing) {
+        for body in bodies.iter_mut().flatten() {
+            remove_implicit_code(class, &lang.method_index, body);
+        }
+    }
+    let decompile_assertions =
+        lang.decompile_assertions && lang.supports(LanguageFeature::Assertions);
+    if decompile_assertions {
+        for body in bodies.iter_mut().flatten() {
+            restore_assertions(body);
+        }
+    }
+    // the field is kept for checks that weren't restored
+    let hide_assertion_status =
+        decompile_assertions && !bodies.iter().flatten().any(|it| reads_assertion_status(it));
+    if hide_assertion_status {
+        for body in bodies.iter_mut().flatten() {
+            remove_assertion_status(body);
+        }
+    }
+    // cached class lookups of Java 1.4 were restored as class literals
+    let hide_class_cache = !class
+        .methods
+        .iter()
+        .zip(&bodies)
+        .filter(|(method, _)| !is_class_helper(method))
+        .any(|(_, body)| body.as_deref().is_some_and(uses_class_cache));
+    let initializers = hoist_initializers(class, &mut bodies);
+
+    let mut members = Vec::with_capacity(class.fields.len() + class.methods.len());
+    let mut enum_constants = Vec::new();
+
+    tracing::debug!("- Generating fields for {}", class.class_name);
+    for field in field_order(&class.fields, lang.member_order)
+        .into_iter()
+        .map(|i| &class.fields[i])
+    {
+        if hide_assertion_status && is_assertion_status(field)
+            || hide_class_cache && is_class_cache(field)
+            || lang.hide_synthetic_members && field.is_synthetic()
+        {
+            continue;
+        }
+        let (initializer, origin) = match initializers.get(&field.name) {
+            Some(hoisted) => {
+                let method = &class.methods[hoisted.method];
+                let body_ctx = BodyContext {
+                    class: &class_ctx,
+                    method,
+                    code: method_code(method).expect("initializer taken from method without code"),
+                    locals: Default::default(),
+                    lang,
+                    verbosity,
+                };
+                (
+                    Some(body_ctx.lower_value(&hoisted.value)),
+                    Some(body_ctx.origin(hoisted.offset)),
+                )
+            }
+            None => match field.attributes.get("ConstantValue") {
+                Some(AttributeValue::ConstantValue(index)) => {
+                    match Literal::from_constant(&class.constant_pool, *index as usize) {
+                        Ok(value) => (
+                            Some(Expr::Literal(value.coerce(&field.descriptor.value))),
+                            None,
+                        ),
+                        Err(err) => {
+                            tracing::warn!("invalid constant value of '{}': {}", field.name, err);
+                            (None, None)
+                        }
+                    }
+                }
+                _ => (None, None),
+            },
+        };
+
+        if field.field_flags().contains(FieldAccessFlags::ENUM) {
+            let constant = initializer
+                .as_ref()
+                .and_then(|it| enum_constant(lang, class, &field.name, it));
+            if let Some((args, body)) = constant {
+                let body = body.map(|name| {
+                    let (members, skipped) = lower_enum_body(lang, verbosity, &name);
+                    skipped_methods.extend(skipped);
+                    members
+                });
+                enum_constants.push(EnumConstant {
+                    name: field.name.clone(),
+                    args,
+                    body,
+                    origin,
+                });
+                continue;
+            }
+        }
+
+        members.push(MemberDecl::Field(FieldDecl {
+            modifiers: field_modifiers(field.field_flags(), class_ctx.interface),
+            ty: Type::from(&field.descriptor.value),
+            name: field.name.clone(),
+            initializer,
+            origin,
+        }));
+    }
+
+    tracing::debug!("- Generating methods for {}", class.class_name);
+    for i in method_order(&class.methods, lang.member_order) {
+        if hide_class_cache && is_class_helper(&class.methods[i])
+            || class_ctx.is_enum && is_implicit_enum_method(class, &class.methods[i])
+            || is_hidden_method(lang, class, &class.methods[i])
+        {
+            continue;
+        }
+        members.extend(lower_method(
+            lang,
+            class,
+            &class_ctx,
+            verbosity,
+            &class.methods[i],
+            bodies[i].as_deref(),
+        ));
+    }
+
+    let nested = member_classes(class);
+    let names: Vec<_> = nested.iter().map(|(name, _)| name.clone()).collect();
+    for i in member_class_order(&names, &lang.nested_classes, lang.member_order) {
+        let (name, flags) = &nested[i];
+        match lang.nested_classes.get(name) {
+            Some(member) => members.push(MemberDecl::Type(lower_type(
+                lang, verbosity, member, *flags,
+            ))),
+            None => tracing::warn!("member class {} wasn't found", name),
+        }
+    }
+
+    let kind = type_kind(flags);
+    let mut modifiers = class_modifiers(flags);
+    // member interfaces, enums and annotations are implicitly static
+    if kind != TypeKind::Class {
+        modifiers.retain(|it| *it != Modifier::Static);
+    }
+    // enums are implicitly final, or abstract if constants have bodies
+    if kind == TypeKind::Enum {
+        modifiers.retain(|it| !matches!(it, Modifier::Final | Modifier::Abstract));
+    }
+
+    TypeDecl {
+        modifiers,
+        kind,
+        name: class.class_name.clone(),
+        extends: class
+            .super_name
+            .as_ref()
+            .filter(|it| !it.is_object() && kind != TypeKind::Enum)
+            .map(|it| Type::Class(it.clone())),
+        implements: class
+            .interfaces
+            .iter()
+            .map(|it| Type::Class(it.clone()))
+            .collect(),
+        member_classes: member_class_names(class),
+        enum_constants,
+        members,
+        skipped_methods,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gen::{
+        java::{JavaBackend, JavaVersion},
+        GenerateCode, GeneratorBuilder,
+    };
+
+    fn int(value: i32) -> Expr {
+        Expr::Literal(Literal::Int(value))
+    }
+
+    fn case(label: CaseLabel, statements: Vec<Stmt>) -> SwitchCase {
+        SwitchCase {
+            labels: vec![label],
+            body: Block { statements },
+        }
+    }
+
+    fn switch_expression(cases: Vec<SwitchCase>) -> Expr {
+        Expr::Switch {
+            selector: Box::new(Expr::Name("a".to_string())),
+            cases,
+        }
+    }
+
+    fn generate(statements: Vec<Stmt>) -> String {
+        let lang = GeneratorBuilder::java().version(JavaVersion::Java8).build();
+        let mut block = Block { statements };
+        unfold_switch_expressions(&mut block);
+        JavaBackend.generate(&lang, &(), &block).unwrap().0
+    }
+
+    #[test]
+    fn switch_expressions_for_older_targets() {
+        let declared = StmtKind::LocalVar {
+            ty: Type::Primitive(JVMPrimitive::TInt),
+            name: "x".to_string(),
+            value: Some(switch_expression(vec![
+                case(
+                    CaseLabel::Expr(int(1)),
+                    vec![StmtKind::Yield(int(10)).into()],
+                ),
+                case(
+                    CaseLabel::Default,
+                    vec![
+                        StmtKind::Expr(Expr::Call {
+                            target: None,
+                            name: "run".to_string(),
+                            args: vec![],
+                        })
+                        .into(),
+                        StmtKind::Yield(int(20)).into(),
+                    ],
+                ),
+            ])),
+        };
+        assert_eq!(
+            generate(vec![declared.into()]),
+            "int x;\nswitch (a) {\n  case 1:\n    x = 10;\n    break;\n  default:\n    run();\n    x = 20;\n    break;\n}\n"
+        );
+
+        // yielded switch expressions are unfolded as well
+        let returned = StmtKind::Return(Some(switch_expression(vec![case(
+            CaseLabel::Default,
+            vec![StmtKind::Yield(switch_expression(vec![case(
+                CaseLabel::Default,
+                vec![StmtKind::Yield(int(1)).into()],
+            )]))
+            .into()],
+        )])));
+        assert_eq!(
+            generate(vec![returned.into()]),
+            "switch (a) {\n  default:\n    switch (a) {\n      default:\n        return 1;\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn switch_expression_yielding_from_nested_switch() {
+        // `break` after the assignment would leave the inner switch
+        let assigned = StmtKind::Expr(Expr::assign(
+            Expr::Name("x".to_string()),
+            switch_expression(vec![case(
+                CaseLabel::Default,
+                vec![StmtKind::Switch {
+                    selector: Expr::Name("b".to_string()),
+                    cases: vec![case(
+                        CaseLabel::Default,
+                        vec![StmtKind::Yield(int(1)).into()],
+                    )],
+                }
+                .into()],
+            )]),
+        ));
+        let mut block = Block {
+            statements: vec![assigned.clone().into()],
+        };
+        unfold_switch_expressions(&mut block);
+        assert_eq!(block.statements, [Stmt::from(assigned)]);
+    }
+
+    #[test]
+    fn local_classes_are_found() {
+        let created = |name: &str| {
+            created_local_class(
+                &[Statement::new(
+                    0,
+                    Expression::ReturnStatement(ReturnStatement(Some(StackValue::New {
+                        class: ClassPath::parse(name).unwrap(),
+                        args: vec![],
+                    }))),
+                )],
+                &NestedClasses::default(),
+            )
+        };
+        assert_eq!(created("A$1"), Some(ClassPath::parse("A$1").unwrap()));
+        assert!(created("A$1Local").is_some());
+        assert!(created("A$1$Member").is_some());
+        assert_eq!(created("A$Member"), None);
+    }
+}
@@ -1,140 +1,444 @@
-use std::io::Cursor;
+use std::collections::HashMap;
+use std::io::Write;
 
 use jvm_class_format::{
-    attribute::{AsData, CodeData, MethodParameterData},
-    AccessFlags, ClassPath, Constant, Member,
+    attribute::{
+        AsData, CodeData, ExceptionData, MethodParameterData, SignatureData, ThrowsSignature,
+    },
+    Class, ClassPath, Constant, ConstantPool, Instruction, JVMPrimitive, JVMType, Member,
+    MethodAccessFlags,
 };
 
 use crate::{
+    error::LimitExceeded,
     gen::{
-        java::{JavaBackend, JavaScopeRequirements},
-        GenerateCode, GeneratorBackend, GeneratorVerbosity,
+        java::{
+            ast::{Block, Initializer, MethodDecl, Modifier, Type},
+            lines::line_count,
+            write_modifiers, JavaBackend, JavaContext, JavaScopeRequirements, LanguageFeature,
+        },
+        GenerateCode,
+    },
+    ir::{
+        decompile,
+        expression::{
+            Expression, IfStatement, InstructionComment, ReturnStatement, ThrowStatement,
+        },
+        value::{Literal, StackValue},
+        MethodLimits, Statement,
     },
-    ir::decompile,
 };
 
-#[derive(Debug, Default)]
-pub struct ClassContext {
-    pub class_name: ClassPath,
+/// Method whose body was left as assembly because it exceeded
+/// [`MethodLimits`] or couldn't be decompiled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SkippedMethod {
+    pub class: ClassPath,
+    pub name: String,
+    pub descriptor: String,
+    pub reason: LimitExceeded,
+}
+
+impl SkippedMethod {
+    pub fn new(class: &Class, method: &Member, reason: LimitExceeded) -> Self {
+        SkippedMethod {
+            class: class.class_name.clone(),
+            name: method.name.clone(),
+            descriptor: method.descriptor.to_string(),
+            reason,
+        }
+    }
+}
+
+/// Decompiles method code, if the method has any.
+pub fn decompile_body(
+    class: &Class,
+    method: &Member,
+    limits: &MethodLimits,
+) -> Option<Result<Vec<Statement>, LimitExceeded>> {
+    let code: &CodeData = method.attributes.get("Code")?.as_data().ok()?;
+
+    let mut statements = match decompile(class, method, code, limits) {
+        Ok(it) => it,
+        Err(err) => return Some(Err(err)),
+    };
+    // implicit at the end of void methods
+    if let Some(Expression::ReturnStatement(ReturnStatement(None))) =
+        statements.last().map(|it| &it.expression)
+    {
+        statements.pop();
+    }
+    Some(Ok(statements))
+}
+
+/// Body of a method that wasn't decompiled: its disassembly in comments,
+/// followed by a `throw` so the generated code still compiles.
+pub fn fallback_body(method: &Member, reason: &LimitExceeded) -> Vec<Statement> {
+    let Some(code) = method
+        .attributes
+        .get("Code")
+        .and_then(|it| AsData::<CodeData>::as_data(it).ok())
+    else {
+        return vec![];
+    };
 
-    pub synthetic: bool,
+    let mut result: Vec<_> = Instruction::collect_with_offsets(&code.code)
+        .into_iter()
+        .map(|(offset, it)| Statement::new(offset, Expression::Comment(InstructionComment(*it))))
+        .collect();
+    let last = result.last().map(|it| it.offset).unwrap_or_default();
+    let throw = Statement::new(
+        last,
+        Expression::Throw(ThrowStatement(StackValue::New {
+            class: ClassPath::java_lang_class("UnsupportedOperationException"),
+            args: vec![StackValue::Literal(Literal::String(format!(
+                "method wasn't decompiled: {}",
+                reason
+            )))],
+        })),
+    );
+    // static initializers must be able to complete normally
+    if method.name == "<clinit>" {
+        result.push(Statement::new(
+            last,
+            Expression::If(IfStatement {
+                condition: StackValue::Literal(Literal::Boolean(true)),
+                then: vec![throw],
+                otherwise: None,
+            }),
+        ));
+    } else {
+        result.push(throw);
+    }
+    result
 }
 
-pub fn method_signature(access_flags: AccessFlags) -> String {
-    let mut result = String::with_capacity(64);
-    if access_flags.contains(AccessFlags::PUBLIC) {
-        result.push_str("public");
-    } else if access_flags.contains(AccessFlags::PROTECTED) {
-        result.push_str("protected");
-    } else if access_flags.contains(AccessFlags::PRIVATE) {
-        result.push_str("private");
+/// Modifiers of a method declaration in a class file with `major` version.
+pub fn method_modifiers(
+    access_flags: MethodAccessFlags,
+    interface: bool,
+    major: u16,
+) -> Vec<Modifier> {
+    let mut result = Vec::with_capacity(4);
+
+    // visibility is one of following, interface methods are implicitly public
+    if access_flags.contains(MethodAccessFlags::PUBLIC) {
+        if !interface {
+            result.push(Modifier::Public);
+        }
+    } else if access_flags.contains(MethodAccessFlags::PROTECTED) {
+        result.push(Modifier::Protected);
+    } else if access_flags.contains(MethodAccessFlags::PRIVATE) {
+        result.push(Modifier::Private);
     }
 
-    if access_flags.contains(AccessFlags::STATIC) {
-        if !result.is_empty() {
-            result.push(' ');
+    if access_flags.contains(MethodAccessFlags::ABSTRACT) {
+        // interface methods without a body are implicitly abstract
+        if !interface {
+            result.push(Modifier::Abstract);
         }
-        result.push_str("static");
+    } else if interface
+        && !access_flags.intersects(MethodAccessFlags::STATIC | MethodAccessFlags::PRIVATE)
+    {
+        // interface instance methods with a body
+        result.push(Modifier::Default);
+    }
+
+    if access_flags.contains(MethodAccessFlags::STATIC) {
+        result.push(Modifier::Static);
+    }
+
+    if access_flags.contains(MethodAccessFlags::FINAL) {
+        result.push(Modifier::Final);
     }
 
+    if access_flags.contains(MethodAccessFlags::SYNCHRONIZED) {
+        result.push(Modifier::Synchronized);
+    }
+
+    if access_flags.contains(MethodAccessFlags::NATIVE) {
+        result.push(Modifier::Native);
+    }
+
+    // the flag only means strictfp for majors 46 through 60; all floating
+    // point is strict since Java 17
+    if access_flags.contains(MethodAccessFlags::STRICT) && (46..=60).contains(&major) {
+        result.push(Modifier::Strictfp);
+    }
+
+    result
+}
+
+pub fn argument_names(constant_pool: &ConstantPool, method: &Member) -> Vec<String> {
+    let params = method
+        .attributes
+        .get("MethodParameters")
+        .and_then(|attr| AsData::<MethodParameterData>::as_data(attr).ok())
+        .map(|it| &it.parameters);
+
+    (0..method.descriptor.arguments.len())
+        .map(|i| {
+            // let flags = param.access_flags; // TODO: Check spec
+            match params
+                .and_then(|it| it.get(i))
+                .and_then(|param| constant_pool.try_get(param.name_index as usize).ok())
+            {
+                Some(Constant::Utf8 { value }) => value.to_string(),
+                _ => format!("arg_{}", i),
+            }
+        })
+        .collect()
+}
+
+/// Types thrown by the method, preferring generic ones from its signature
+/// if `generics` can be used.
+pub fn thrown_types(method: &Member, generics: bool) -> Vec<ThrowsSignature> {
+    let generic = method
+        .attributes
+        .get("Signature")
+        .filter(|_| generics)
+        .and_then(|attr| AsData::<SignatureData>::as_data(attr).ok())
+        .map(|it| it.method_throws());
+    match generic {
+        Some(Ok(it)) if !it.is_empty() => return it,
+        Some(Err(err)) => tracing::warn!("invalid signature of '{}': {}", method.name, err),
+        _ => {}
+    }
+
+    method
+        .attributes
+        .get("Exceptions")
+        .and_then(|attr| AsData::<ExceptionData>::as_data(attr).ok())
+        .map(|it| {
+            it.exceptions
+                .iter()
+                .cloned()
+                .map(ThrowsSignature::Class)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Maps local variable slots of method arguments to their names.
+pub fn argument_locals(method: &Member, names: &[String]) -> HashMap<usize, String> {
+    let mut slot = if method.method_flags().contains(MethodAccessFlags::STATIC) {
+        0
+    } else {
+        1
+    };
+
+    let mut result = HashMap::with_capacity(names.len());
+    for (arg, name) in method.descriptor.arguments.iter().zip(names) {
+        result.insert(slot, name.clone());
+        slot += match arg {
+            JVMType::TPrimitive(JVMPrimitive::TLong | JVMPrimitive::TDouble) => 2,
+            _ => 1,
+        };
+    }
     result
 }
 
-impl GenerateCode<Member, ClassContext> for JavaBackend {
+impl JavaBackend {
+    /// Writes a block after its `header`; line mappings are relative to the
+    /// first header line.
+    fn write_body<W: std::io::Write>(
+        &self,
+        lang: &JavaContext,
+        header: &[u8],
+        body: &Block,
+        w: &mut W,
+    ) -> Result<JavaScopeRequirements, std::io::Error> {
+        let (generated, mut req) = self.generate(lang, &(), body)?;
+        req.line_map
+            .shift(line_count(header) + 1 + lang.style.brace_lines());
+        // padding for original lines goes before the whole header
+        if let Some(first) = req.line_map.0.first_mut() {
+            first.header_lines = first.output_line - 1;
+        }
+        w.write_all(header)?;
+        lang.style.write_block(w, generated.as_bytes(), false)?;
+        Ok(req)
+    }
+}
+
+impl GenerateCode<Initializer> for JavaBackend {
     fn write_value<W: std::io::Write>(
         &self,
         lang: &Self::LanguageContext,
-        ctx: &ClassContext,
-        method: &Member,
+        _: &(),
+        input: &Initializer,
         w: &mut W,
     ) -> Result<Self::ScopeRequirements, std::io::Error> {
-        let mut req = JavaScopeRequirements::default();
+        let header: &[u8] = if input.is_static { b"static" } else { b"" };
+        self.write_body(lang, header, &input.body, w)
+    }
+}
 
-        let code: &CodeData = method
-            .attributes
-            .get("Code")
-            .expect("expected a code attribute")
-            .as_data()
-            .unwrap();
+impl GenerateCode<MethodDecl> for JavaBackend {
+    fn write_value<W: std::io::Write>(
+        &self,
+        lang: &Self::LanguageContext,
+        _: &(),
+        method: &MethodDecl,
+        w: &mut W,
+    ) -> Result<Self::ScopeRequirements, std::io::Error> {
+        let mut req = JavaScopeRequirements::default();
 
-        let constant_pool = lang.constant_pool.as_ref().expect("no contant pool");
+        let mut header = Vec::new();
+        if let Some(comment) = &method.comment {
+            write!(header, "// {}\n\n", comment)?;
+        }
 
-        let expressions = decompile(constant_pool, method, code);
+        write_modifiers(&mut header, &method.modifiers)?;
+        if let Some(return_type) = &method.return_type {
+            req.include(self.write_value(lang, &(), return_type, &mut header)?);
+            header.write_all(b" ")?;
+        }
+        write!(header, "{}(", method.name)?;
 
-        let mut generated = Vec::new();
-        {
-            let mut gen_w = Cursor::new(&mut generated);
-            for expression in expressions {
-                let e_req = self.write_value(lang, &(method, code), &expression, &mut gen_w)?;
-                req.include(e_req);
+        for (i, param) in method.params.iter().enumerate() {
+            if i > 0 {
+                header.write_all(b", ")?;
+            }
+            match &param.ty {
+                Type::Array(element) if method.varargs && i + 1 == method.params.len() => {
+                    req.require(LanguageFeature::Varargs);
+                    req.include(self.write_value(lang, &(), element.as_ref(), &mut header)?);
+                    header.write_all(b"...")?;
+                }
+                ty => req.include(self.write_value(lang, &(), ty, &mut header)?),
             }
+            write!(header, " {}", param.name)?;
         }
+        header.write_all(b")")?;
 
-        if self.verbosity() == GeneratorVerbosity::All {
-            if method.is_constructor()
-                && method.descriptor.arguments.len() == 0
-                && generated.len() == 0
-            {
-                return Ok(req);
-            }
+        for (i, thrown) in method.throws.iter().enumerate() {
+            header.write_all(if i == 0 { b" throws " } else { b", " })?;
+            req.include(self.write_value(lang, &(), thrown, &mut header)?);
         }
 
-        if ctx.synthetic {
-            w.write_all(b"// synthetic method\n\n")?;
+        match &method.body {
+            Some(body) => {
+                let mut body_req = self.write_body(lang, &header, body, w)?;
+                req.line_map = std::mem::take(&mut body_req.line_map);
+                req.include(body_req);
+            }
+            None => {
+                w.write_all(&header)?;
+                w.write_all(b";\n")?;
+            }
         }
 
-        w.write_all(method_signature(method.access_flags).as_bytes())?;
+        Ok(req)
+    }
+}
 
-        if !method.is_constructor() {
-            let (tn, method_req) = self.generate(lang, &(), &method.descriptor.value)?;
-            req.add_import(method_req.imports);
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
 
-            write!(w, " {} {}(", tn, method.name)?;
-        } else {
-            write!(w, " {}(", ctx.class_name.name)?;
-        }
+    use jvm_class_format::{
+        attribute::AttributeValue, constant::ConstantPool, AccessFlags, ClassAccessFlags,
+        CompilerInfo, Op, SourceLanguage,
+    };
 
-        if !method.descriptor.arguments.is_empty() {
-            let params = if let Some(MethodParameterData { parameters }) = method
-                .attributes
-                .get("MethodParameters")
-                .and_then(|attr| attr.as_data().ok())
-            {
-                Some(parameters)
-            } else {
-                None
-            };
-
-            for (i, arg) in method.descriptor.arguments.iter().enumerate() {
-                let (arg_type, tr) = self.generate(lang, &(), arg)?;
-                req.add_import(tr.imports);
-
-                let arg_name: String = if let Some(param) = params.and_then(|it| it.get(i)) {
-                    // let flags = param.access_flags; // TODO: Check spec
-
-                    if let Some(Constant::Utf8 { value }) = lang
-                        .constant_pool
-                        .as_ref()
-                        .and_then(|it| it.try_get(param.name_index as usize).ok())
-                    {
-                        value.to_string()
-                    } else {
-                        format!("arg_{}", i)
-                    }
-                } else {
-                    format!("arg_{}", i)
-                };
-
-                write!(w, "{} {}", arg_type, arg_name)?;
-                if i < method.descriptor.arguments.len() - 1 {
-                    write!(w, ", ")?;
-                }
-            }
-        }
-        w.write_all(b") {\n")?;
-        w.write_all(&generated)?;
-        w.write_all(b"}\n")?;
+    use super::*;
 
-        Ok(req)
+    #[test]
+    fn fallback_body_keeps_disassembly_and_throws() {
+        let code = CodeData {
+            max_stack: 1,
+            max_locals: 1,
+            // iconst_1; ireturn
+            code: vec![0x04, 0xAC],
+            exception_table: vec![],
+            attributes: HashMap::new(),
+        };
+        let method = Member {
+            access_flags: AccessFlags::empty(),
+            name: "one".to_string(),
+            descriptor: "()I".parse().unwrap(),
+            attributes: HashMap::from([("Code".to_string(), AttributeValue::Code(code))]),
+        };
+
+        let body = fallback_body(&method, &LimitExceeded::Instructions { count: 2, limit: 1 });
+        assert_eq!(body.len(), 3);
+        assert!(body[..2]
+            .iter()
+            .all(|it| matches!(it.expression, Expression::Comment(_))));
+        assert_eq!(body[2].offset, 1);
+        assert!(matches!(
+            &body[2].expression,
+            Expression::Throw(ThrowStatement(StackValue::New { class, .. }))
+                if class.name == "UnsupportedOperationException"
+        ));
+
+        let initializer = Member {
+            name: "<clinit>".to_string(),
+            descriptor: "()V".parse().unwrap(),
+            ..method
+        };
+        let body = fallback_body(
+            &initializer,
+            &LimitExceeded::Instructions { count: 2, limit: 1 },
+        );
+        assert!(matches!(
+            &body[2].expression,
+            Expression::If(IfStatement { then, .. })
+                if matches!(then[0].expression, Expression::Throw(_))
+        ));
+    }
+
+    #[test]
+    fn partially_decompiled_methods_are_skipped() {
+        let code = CodeData {
+            max_stack: 2,
+            max_locals: 2,
+            // return a > 0 && b > 0;
+            code: vec![
+                0x1A, 0x9E, 0x00, 0x0B, 0x1B, 0x9E, 0x00, 0x07, 0x04, 0xA7, 0x00, 0x04, 0x03, 0xAC,
+            ],
+            exception_table: vec![],
+            attributes: HashMap::new(),
+        };
+        let method = Member {
+            access_flags: AccessFlags::STATIC,
+            name: "both".to_string(),
+            descriptor: "(II)Z".parse().unwrap(),
+            attributes: HashMap::from([("Code".to_string(), AttributeValue::Code(code))]),
+        };
+        let class = Class {
+            compiler_info: CompilerInfo {
+                major: 52,
+                minor: 0,
+                language: SourceLanguage::Java,
+            },
+            access_flags: ClassAccessFlags::SUPER,
+            constant_pool: ConstantPool::new(),
+            this_class: 0,
+            class_name: ClassPath::parse("Foo").unwrap(),
+            super_name: None,
+            interfaces: vec![],
+            fields: vec![],
+            methods: vec![],
+            attributes: HashMap::new(),
+        };
+
+        let result = decompile_body(&class, &method, &MethodLimits::default());
+        assert_eq!(
+            result,
+            Some(Err(LimitExceeded::Unsupported {
+                offset: 1,
+                op: Op::Ifle
+            }))
+        );
+    }
+
+    #[test]
+    fn strictfp_only_within_its_versions() {
+        let flags = MethodAccessFlags::STATIC | MethodAccessFlags::STRICT;
+        assert!(!method_modifiers(flags, false, 45).contains(&Modifier::Strictfp));
+        assert!(method_modifiers(flags, false, 52).contains(&Modifier::Strictfp));
+        assert_eq!(method_modifiers(flags, false, 61), vec![Modifier::Static]);
     }
 }
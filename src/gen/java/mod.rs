@@ -1,11 +1,35 @@
-use super::{indent::IndentKind, GenerateCode, GeneratorBackend, GeneratorVerbosity};
-use jvm_class_format::{ClassPath, ConstantPool, JVMPrimitive, JVMType};
-use std::collections::HashSet;
+use super::{writer::CodeStyle, GenerateCode, GeneratorBackend, GeneratorVerbosity, MemberOrder};
+use crate::{
+    ir::{accessor::Accessors, cleanup::MethodIndex, switch::SwitchMaps, MethodLimits},
+    settings::Settings,
+};
+use ast::{Modifier, Type};
+use classpath::ClassIndex;
+use imports::{simple_type_name, ImportPlan};
+use jvm_class_format::{ClassPath, JVMPrimitive, JVMType};
+use lines::LineMap;
+use method::SkippedMethod;
+use nested::NestedClasses;
+use rewrite::{default_passes, RewritePass};
+use std::{collections::HashSet, sync::Arc};
 
+pub mod annotation;
+pub mod ast;
 pub mod class;
+pub mod classpath;
 pub mod code;
 pub mod field;
+pub mod imports;
+pub mod lines;
+pub mod literal;
+pub mod lower;
 pub mod method;
+pub mod module;
+pub mod nested;
+pub mod rewrite;
+mod version;
+
+pub use version::{JavaVersion, LanguageFeature};
 
 pub fn primitive_name(primitive: JVMPrimitive) -> &'static str {
     match primitive {
@@ -21,37 +45,42 @@ pub fn primitive_name(primitive: JVMPrimitive) -> &'static str {
     }
 }
 
-pub struct Type;
+/// Writes modifier keywords, each followed by a space.
+pub fn write_modifiers<W: std::io::Write>(
+    w: &mut W,
+    modifiers: &[Modifier],
+) -> Result<(), std::io::Error> {
+    for modifier in modifiers {
+        w.write_all(modifier.keyword().as_bytes())?;
+        w.write_all(b" ")?;
+    }
+    Ok(())
+}
 
-impl GenerateCode<JVMType> for JavaBackend {
+impl GenerateCode<Type> for JavaBackend {
     fn write_value<W: std::io::Write>(
         &self,
-        _lang: &Self::LanguageContext,
+        lang: &Self::LanguageContext,
         _: &(),
-        input: &JVMType,
+        input: &Type,
         w: &mut W,
     ) -> Result<Self::ScopeRequirements, std::io::Error> {
         let mut req = JavaScopeRequirements::default();
         match input {
-            JVMType::TPrimitive(primitive) => {
+            Type::Primitive(primitive) => {
                 w.write_all(primitive_name(*primitive).as_bytes())?;
             }
-            JVMType::TClass(class) => {
-                if !class.is_in_java_lang() {
-                    req.imports.insert(class.clone());
-                }
-                w.write_all(class.name.as_bytes())?;
+            Type::Class(class) => {
+                req.imports.insert(class.clone());
+                w.write_all(lang.type_name(class).as_bytes())?;
             }
-            JVMType::TPrimitiveArray { depth, inner } => {
-                w.write_all(primitive_name(*inner).as_bytes())?;
-                w.write_all("[]".repeat(*depth).as_bytes())?;
+            Type::Variable(name) => {
+                req.require(LanguageFeature::Generics);
+                w.write_all(name.as_bytes())?;
             }
-            JVMType::TClassArray { depth, inner } => {
-                if !inner.is_in_java_lang() {
-                    req.add_import([inner.clone()]);
-                }
-                w.write_all(inner.name.as_bytes())?;
-                w.write_all("[]".repeat(*depth).as_bytes())?;
+            Type::Array(inner) => {
+                req.include(self.write_value(lang, &(), inner.as_ref(), w)?);
+                w.write_all(b"[]")?;
             }
         };
 
@@ -59,33 +88,15 @@ impl GenerateCode<JVMType> for JavaBackend {
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq)]
-#[non_exhaustive]
-#[repr(u32)]
-pub enum JavaVersion {
-    Unsupported = 0,
-    Java1,
-    Java5,
-    Java6,
-    Java7,
-    Java8,
-    Java9,
-    Java10,
-    Java11,
-    Java12,
-    Java13,
-    Java14,
-    Java15,
-    Java16,
-    Java17,
-    Java18,
-    Java19,
-    Java20,
-}
-
-impl Default for JavaVersion {
-    fn default() -> Self {
-        Self::Java20
+impl GenerateCode<JVMType> for JavaBackend {
+    fn write_value<W: std::io::Write>(
+        &self,
+        lang: &Self::LanguageContext,
+        _: &(),
+        input: &JVMType,
+        w: &mut W,
+    ) -> Result<Self::ScopeRequirements, std::io::Error> {
+        self.write_value(lang, &(), &Type::from(input), w)
     }
 }
 
@@ -99,8 +110,10 @@ impl JavaGeneratorBuilder {
         Self::default()
     }
 
+    /// Sets the Java version generated code must compile with; by default
+    /// it's the version each class was compiled from.
     pub fn version(mut self, version: JavaVersion) -> Self {
-        self.result.target_version = version;
+        self.result.target_version = Some(version);
         self
     }
 
@@ -114,6 +127,122 @@ impl JavaGeneratorBuilder {
         self
     }
 
+    pub fn escape_unicode(mut self, escape: bool) -> Self {
+        self.result.escape_unicode = escape;
+        self
+    }
+
+    /// Collapses imports from a package into a `*` import when at least
+    /// `threshold` of its classes are imported.
+    pub fn import_wildcard_threshold(mut self, threshold: Option<usize>) -> Self {
+        self.result.import_wildcard_threshold = threshold;
+        self
+    }
+
+    pub fn member_order(mut self, order: MemberOrder) -> Self {
+        self.result.member_order = order;
+        self
+    }
+
+    pub fn style(mut self, style: CodeStyle) -> Self {
+        self.result.style = style;
+        self
+    }
+
+    /// Adds a pass that rewrites the syntax tree before it's printed; passes
+    /// run in the order they were added, after the default ones.
+    pub fn rewrite_pass(mut self, pass: impl RewritePass + 'static) -> Self {
+        self.result.rewrite_passes.push(Arc::new(pass));
+        self
+    }
+
+    /// Removes all rewrite passes, including the default ones.
+    pub fn clear_rewrite_passes(mut self) -> Self {
+        self.result.rewrite_passes.clear();
+        self
+    }
+
+    /// Places statements on their original lines where possible.
+    pub fn preserve_line_numbers(mut self, preserve: bool) -> Self {
+        self.result.preserve_line_numbers = preserve;
+        self
+    }
+
+    /// Writes `assert` statements in place of `$assertionsDisabled` checks.
+    pub fn decompile_assertions(mut self, decompile: bool) -> Self {
+        self.result.decompile_assertions = decompile;
+        self
+    }
+
+    /// Hides bridge methods.
+    pub fn hide_bridge_methods(mut self, hide: bool) -> Self {
+        self.result.hide_bridge_methods = hide;
+        self
+    }
+
+    /// Hides synthetic fields and `access$NNN` accessor methods.
+    pub fn hide_synthetic_members(mut self, hide: bool) -> Self {
+        self.result.hide_synthetic_members = hide;
+        self
+    }
+
+    /// Sets bounds of work spent on a single method; methods exceeding them
+    /// are written as assembly with a body that throws.
+    pub fn method_limits(mut self, limits: MethodLimits) -> Self {
+        self.result.method_limits = limits;
+        self
+    }
+
+    /// Sets accessors whose calls are written as the access they perform.
+    pub fn accessors(mut self, accessors: Accessors) -> Self {
+        self.result.accessors = Arc::new(accessors);
+        self
+    }
+
+    /// Sets classes available next to the generated ones (e.g. libraries).
+    pub fn class_index(mut self, index: ClassIndex) -> Self {
+        self.result.class_index = Arc::new(index);
+        self
+    }
+
+    /// Sets methods of classes next to the generated ones, whose calls can
+    /// then be written without implicit boxing and varargs arrays.
+    pub fn method_index(mut self, index: MethodIndex) -> Self {
+        self.result.method_index = Arc::new(index);
+        self
+    }
+
+    /// Sets nested classes written as members of their outer class.
+    pub fn nested_classes(mut self, classes: NestedClasses) -> Self {
+        self.result.nested_classes = Arc::new(classes);
+        self
+    }
+
+    /// Sets switch maps used to restore `case` labels of switches over enums.
+    pub fn switch_maps(mut self, maps: SwitchMaps) -> Self {
+        self.result.switch_maps = Arc::new(maps);
+        self
+    }
+
+    /// Applies user provided [`Settings`].
+    pub fn settings(mut self, settings: &Settings) -> Self {
+        if let Some(release) = settings.target_version {
+            match JavaVersion::from_release(release) {
+                Some(version) => self = self.version(version),
+                None => tracing::warn!("unsupported target Java release: {}", release),
+            }
+        }
+        self.escape_unicode(settings.escape_unicode)
+            .import_wildcard_threshold(settings.import_wildcard_threshold)
+            .member_order(settings.member_order)
+            .preserve_line_numbers(settings.preserve_line_numbers)
+            .decompile_assertions(settings.decompile_assertions.unwrap_or(true))
+            .hide_bridge_methods(settings.hide_bridge_methods.unwrap_or(true))
+            .hide_synthetic_members(settings.hide_synthetic_members.unwrap_or(true))
+            .method_limits(settings.method_limits())
+            .style(settings.code_style())
+    }
+
     pub fn build(self) -> JavaContext {
         self.result
     }
@@ -121,18 +250,84 @@ impl JavaGeneratorBuilder {
 
 #[derive(Debug, Clone)]
 pub struct JavaContext {
-    pub target_version: JavaVersion,
+    /// Java version generated code must compile with; `None` uses the version
+    /// of the class file.
+    ///
+    /// Constructs the target doesn't have are written as older compilers
+    /// desugar them: string, enum and pattern switches as switches over `int`
+    /// values, switch expressions as switch statements, and boxing, variable
+    /// arity calls and `assert`s as the code they're compiled into. `var`,
+    /// lambdas, diamonds, text blocks and records are never generated.
+    pub target_version: Option<JavaVersion>,
 
     pub header_message: Option<String>,
-    pub indentation: IndentKind,
+    pub style: CodeStyle,
+    /// Encode non-ASCII characters in string and character literals as
+    /// Unicode escapes.
+    pub escape_unicode: bool,
+    /// Number of imported classes from a single package after which they're
+    /// replaced by a `*` import.
+    pub import_wildcard_threshold: Option<usize>,
+    pub member_order: MemberOrder,
+    /// Pad generated code so statements end up on their original lines.
+    pub preserve_line_numbers: bool,
+    /// Write `assert` statements instead of checks of the synthetic
+    /// `$assertionsDisabled` field.
+    pub decompile_assertions: bool,
+    /// Leave out bridge methods.
+    pub hide_bridge_methods: bool,
+    /// Leave out synthetic fields and `access$NNN` accessors.
+    pub hide_synthetic_members: bool,
+    /// Bounds of work spent on a single method.
+    pub method_limits: MethodLimits,
 
-    pub constant_pool: Option<ConstantPool>,
+    /// Passes applied to the syntax tree of each class before it's printed.
+    pub rewrite_passes: Vec<Arc<dyn RewritePass>>,
+    /// Enum switch maps of synthetic classes next to the generated ones.
+    pub switch_maps: Arc<SwitchMaps>,
+    /// Accessors of classes nested with the generated ones.
+    pub accessors: Arc<Accessors>,
+    /// Nested classes of the generated ones.
+    pub nested_classes: Arc<NestedClasses>,
+    /// Classes available next to the generated ones.
+    pub class_index: Arc<ClassIndex>,
+    /// Methods of classes next to the generated ones.
+    pub method_index: Arc<MethodIndex>,
+    /// Imports planned for the class being generated.
+    pub imports: Option<ImportPlan>,
+}
+
+impl JavaContext {
+    /// Version generated code targets.
+    pub fn target(&self) -> JavaVersion {
+        self.target_version.unwrap_or(JavaVersion::LATEST)
+    }
+
+    /// Whether `feature` can be used in generated code.
+    pub fn supports(&self, feature: LanguageFeature) -> bool {
+        self.target().supports(feature)
+    }
+
+    /// Returns the name `class` should be referred to by in generated code.
+    pub fn type_name(&self, class: &ClassPath) -> String {
+        match &self.imports {
+            Some(plan) => plan.type_name(class),
+            None => simple_type_name(class),
+        }
+    }
 }
 
 #[derive(Debug, Default)]
 pub struct JavaScopeRequirements {
+    /// Classes referenced by generated code.
     pub imports: HashSet<ClassPath>,
+    /// Lowest Java version generated code compiles with.
     pub language_level: JavaVersion,
+    /// Bytecode locations of generated lines, relative to the start of
+    /// generated code.
+    pub line_map: LineMap,
+    /// Methods left as assembly because they exceeded limits.
+    pub skipped_methods: Vec<SkippedMethod>,
 }
 
 impl JavaScopeRequirements {
@@ -143,8 +338,15 @@ impl JavaScopeRequirements {
         }
     }
 
+    /// Raises the language level so that `feature` is available.
+    pub fn require(&mut self, feature: LanguageFeature) {
+        self.language_level = self.language_level.max(feature.since());
+    }
+
     pub fn include(&mut self, other: Self) {
         self.add_import(other.imports);
+        self.language_level = self.language_level.max(other.language_level);
+        self.skipped_methods.extend(other.skipped_methods);
     }
 }
 
@@ -168,12 +370,26 @@ impl GeneratorBackend for JavaBackend {
 impl Default for JavaContext {
     fn default() -> JavaContext {
         JavaContext {
-            target_version: JavaVersion::default(),
+            target_version: None,
             header_message: Some(
                 "Generated file - do not edit, your changes will be lost.".to_string(),
             ),
-            indentation: IndentKind::Space(2),
-            constant_pool: None,
+            style: CodeStyle::default(),
+            escape_unicode: false,
+            import_wildcard_threshold: None,
+            member_order: MemberOrder::default(),
+            preserve_line_numbers: false,
+            decompile_assertions: true,
+            hide_bridge_methods: true,
+            hide_synthetic_members: true,
+            method_limits: MethodLimits::default(),
+            rewrite_passes: default_passes(),
+            switch_maps: Default::default(),
+            accessors: Default::default(),
+            nested_classes: Default::default(),
+            class_index: Default::default(),
+            method_index: Default::default(),
+            imports: None,
         }
     }
 }
@@ -0,0 +1,74 @@
+//! Generation of `module-info.java` declarations.
+
+use std::io::Write;
+
+use crate::gen::{
+    java::{
+        ast::{ModuleDecl, ModuleDirective},
+        JavaBackend, JavaContext, JavaScopeRequirements, LanguageFeature,
+    },
+    GenerateCode,
+};
+
+impl GenerateCode<ModuleDecl> for JavaBackend {
+    fn write_value<W: Write>(
+        &self,
+        lang: &JavaContext,
+        _: &(),
+        decl: &ModuleDecl,
+        w: &mut W,
+    ) -> Result<Self::ScopeRequirements, std::io::Error> {
+        let mut req = JavaScopeRequirements::default();
+        req.require(LanguageFeature::Modules);
+
+        if decl.open {
+            w.write_all(b"open ")?;
+        }
+        write!(w, "module {}", decl.name)?;
+
+        let mut contents = Vec::with_capacity(256);
+        let mut previous: Option<&ModuleDirective> = None;
+        for directive in &decl.directives {
+            // directives of the same kind are grouped
+            if previous.is_some_and(|it| it.keyword() != directive.keyword()) {
+                contents.write_all(b"\n")?;
+            }
+            write_directive(directive, &mut contents)?;
+            previous = Some(directive);
+        }
+
+        lang.style.write_block(w, &contents, true)?;
+        Ok(req)
+    }
+}
+
+fn write_directive<W: Write>(directive: &ModuleDirective, w: &mut W) -> std::io::Result<()> {
+    write!(w, "{}", directive.keyword())?;
+    match directive {
+        ModuleDirective::Requires {
+            module,
+            transitive,
+            is_static,
+        } => {
+            if *transitive {
+                w.write_all(b" transitive")?;
+            }
+            if *is_static {
+                w.write_all(b" static")?;
+            }
+            write!(w, " {}", module)?;
+        }
+        ModuleDirective::Exports { package, to } | ModuleDirective::Opens { package, to } => {
+            write!(w, " {}", package)?;
+            if !to.is_empty() {
+                write!(w, " to {}", to.join(", "))?;
+            }
+        }
+        ModuleDirective::Uses(service) => write!(w, " {}", service.full_path())?,
+        ModuleDirective::Provides { service, with } => {
+            let with: Vec<_> = with.iter().map(|it| it.full_path()).collect();
+            write!(w, " {} with {}", service.full_path(), with.join(", "))?;
+        }
+    }
+    w.write_all(b";\n")
+}
@@ -0,0 +1,82 @@
+//! Nested classes written as members of their outer class.
+
+use std::collections::HashMap;
+
+use jvm_class_format::{Class, ClassAccessFlags, ClassPath};
+
+use super::class::{is_member_class, nested_class_flags};
+
+/// Nested classes compiled into separate class files next to the generated
+/// ones.
+#[derive(Debug, Clone, Default)]
+pub struct NestedClasses {
+    classes: HashMap<ClassPath, Class>,
+}
+
+impl NestedClasses {
+    /// Adds `class` if it's nested in another class; returns `false`
+    /// otherwise.
+    pub fn add_class(&mut self, class: Class) -> bool {
+        if class.class_name.inner_classes.is_empty() {
+            return false;
+        }
+        self.classes.insert(class.class_name.clone(), class);
+        true
+    }
+
+    pub fn get(&self, name: &ClassPath) -> Option<&Class> {
+        self.classes.get(name)
+    }
+
+    /// Returns `true` if `name` is written within its top-level class: it's a
+    /// member of its outer class, which is in turn top-level or such a member.
+    ///
+    /// Local and anonymous classes (and classes nested in them) aren't.
+    pub fn is_member(&self, name: &ClassPath) -> bool {
+        let mut outer = name.clone();
+        if outer.inner_classes.pop().is_none() {
+            return true;
+        }
+        self.get(name).is_some_and(|it| is_member_class(it, name)) && self.is_member(&outer)
+    }
+
+    /// Returns `true` if `name` is the body of an enum constant: an
+    /// anonymous subclass of the enum it's nested in.
+    pub fn is_enum_body(&self, name: &ClassPath) -> bool {
+        let mut outer = name.clone();
+        if outer.inner_classes.pop().is_none() {
+            return false;
+        }
+        self.get(name).is_some_and(|it| {
+            it.access_flags.contains(ClassAccessFlags::ENUM)
+                && it.super_name.as_ref() == Some(&outer)
+        })
+    }
+
+    /// Returns `true` if `name` is an inner class, whose instances have an
+    /// enclosing instance of the outer class.
+    pub fn is_inner(&self, name: &ClassPath) -> bool {
+        self.get(name)
+            .and_then(|it| nested_class_flags(it, name))
+            .is_some_and(is_inner)
+    }
+}
+
+/// Returns `true` if `name` is named like a local or anonymous class (or a
+/// class nested in one); javac numbers them, as in `Outer$1` or
+/// `Outer$1Local`.
+pub fn is_local_class(name: &ClassPath) -> bool {
+    name.inner_classes
+        .iter()
+        .any(|it| it.starts_with(|c: char| c.is_ascii_digit()))
+}
+
+/// Returns `true` for flags of nested classes that aren't static.
+pub fn is_inner(flags: ClassAccessFlags) -> bool {
+    !flags.intersects(
+        ClassAccessFlags::STATIC
+            | ClassAccessFlags::INTERFACE
+            | ClassAccessFlags::ENUM
+            | ClassAccessFlags::ANNOTATION,
+    )
+}
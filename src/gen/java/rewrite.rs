@@ -0,0 +1,431 @@
+//! Passes that rewrite the [`ast`](super::ast) between lowering and
+//! printing.
+//!
+//! Library users can add their own passes with
+//! [`JavaGeneratorBuilder::rewrite_pass`](super::JavaGeneratorBuilder::rewrite_pass).
+//! Most passes are easiest to write as a [`VisitMut`] which transforms nodes
+//! after their children were visited.
+
+use std::sync::Arc;
+
+use super::ast::{
+    BinaryOp, Block, CaseLabel, CompilationUnit, Expr, MemberDecl, Stmt, StmtKind, SwitchCase,
+    Type, TypeDecl, UnaryOp,
+};
+
+/// Transformation of a whole compilation unit.
+pub trait RewritePass: Send + Sync {
+    /// Name used in diagnostics.
+    fn name(&self) -> &'static str;
+
+    fn rewrite(&self, unit: &mut CompilationUnit);
+}
+
+impl std::fmt::Debug for dyn RewritePass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Passes applied when none are configured explicitly.
+pub fn default_passes() -> Vec<Arc<dyn RewritePass>> {
+    vec![
+        Arc::new(RemoveRedundantCasts),
+        Arc::new(SimplifyNegation),
+        Arc::new(CollapseElseIf),
+    ]
+}
+
+/// Mutable traversal of the syntax tree.
+///
+/// Default implementations visit all children through the `walk_*`
+/// functions; overriding implementations call them to keep descending.
+pub trait VisitMut {
+    fn visit_compilation_unit(&mut self, unit: &mut CompilationUnit) {
+        walk_compilation_unit(self, unit)
+    }
+
+    fn visit_type_decl(&mut self, decl: &mut TypeDecl) {
+        walk_type_decl(self, decl)
+    }
+
+    fn visit_member(&mut self, member: &mut MemberDecl) {
+        walk_member(self, member)
+    }
+
+    fn visit_block(&mut self, block: &mut Block) {
+        walk_block(self, block)
+    }
+
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt(self, stmt)
+    }
+
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr)
+    }
+}
+
+pub fn walk_compilation_unit<V: VisitMut + ?Sized>(v: &mut V, unit: &mut CompilationUnit) {
+    for decl in &mut unit.types {
+        v.visit_type_decl(decl);
+    }
+}
+
+pub fn walk_type_decl<V: VisitMut + ?Sized>(v: &mut V, decl: &mut TypeDecl) {
+    for member in &mut decl.members {
+        v.visit_member(member);
+    }
+}
+
+pub fn walk_member<V: VisitMut + ?Sized>(v: &mut V, member: &mut MemberDecl) {
+    match member {
+        MemberDecl::Field(field) => {
+            if let Some(initializer) = &mut field.initializer {
+                v.visit_expr(initializer);
+            }
+        }
+        MemberDecl::Method(method) => {
+            if let Some(body) = &mut method.body {
+                v.visit_block(body);
+            }
+        }
+        MemberDecl::Initializer(initializer) => v.visit_block(&mut initializer.body),
+        MemberDecl::Type(decl) => v.visit_type_decl(decl),
+    }
+}
+
+pub fn walk_block<V: VisitMut + ?Sized>(v: &mut V, block: &mut Block) {
+    for stmt in &mut block.statements {
+        v.visit_stmt(stmt);
+    }
+}
+
+pub fn walk_stmt<V: VisitMut + ?Sized>(v: &mut V, stmt: &mut Stmt) {
+    match &mut stmt.kind {
+        StmtKind::Expr(expr) | StmtKind::Throw(expr) | StmtKind::Yield(expr) => v.visit_expr(expr),
+        StmtKind::LocalVar { value, .. } | StmtKind::Return(value) => {
+            if let Some(value) = value {
+                v.visit_expr(value);
+            }
+        }
+        StmtKind::If {
+            condition,
+            then,
+            otherwise,
+        } => {
+            v.visit_expr(condition);
+            v.visit_block(then);
+            if let Some(otherwise) = otherwise {
+                v.visit_stmt(otherwise);
+            }
+        }
+        StmtKind::Switch { selector, cases } => walk_switch(v, selector, cases),
+        StmtKind::Assert { condition, message } => {
+            v.visit_expr(condition);
+            if let Some(message) = message {
+                v.visit_expr(message);
+            }
+        }
+        StmtKind::Block(block) => v.visit_block(block),
+        StmtKind::Break | StmtKind::Comment(_) => {}
+    }
+}
+
+fn walk_switch<V: VisitMut + ?Sized>(v: &mut V, selector: &mut Expr, cases: &mut [SwitchCase]) {
+    v.visit_expr(selector);
+    for case in cases {
+        for label in &mut case.labels {
+            if let CaseLabel::Expr(expr) = label {
+                v.visit_expr(expr);
+            }
+        }
+        v.visit_block(&mut case.body);
+    }
+}
+
+pub fn walk_expr<V: VisitMut + ?Sized>(v: &mut V, expr: &mut Expr) {
+    match expr {
+        Expr::Literal(_) | Expr::Name(_) | Expr::This | Expr::Super | Expr::TypeName(_) => {}
+        Expr::Field { target, .. } => {
+            if let Some(target) = target {
+                v.visit_expr(target);
+            }
+        }
+        Expr::Call { target, args, .. } => {
+            if let Some(target) = target {
+                v.visit_expr(target);
+            }
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        Expr::ConstructorCall { args, .. } | Expr::New { args, .. } => {
+            for arg in args {
+                v.visit_expr(arg);
+            }
+        }
+        Expr::NewArray {
+            length,
+            initializer,
+            ..
+        } => {
+            if let Some(length) = length {
+                v.visit_expr(length);
+            }
+            for value in initializer.iter_mut().flatten() {
+                v.visit_expr(value);
+            }
+        }
+        Expr::Unary { operand, .. } => v.visit_expr(operand),
+        Expr::Binary { left, right, .. } => {
+            v.visit_expr(left);
+            v.visit_expr(right);
+        }
+        Expr::Cast { value, .. } | Expr::InstanceOf { value, .. } => v.visit_expr(value),
+        Expr::Conditional {
+            condition,
+            then,
+            otherwise,
+        } => {
+            v.visit_expr(condition);
+            v.visit_expr(then);
+            v.visit_expr(otherwise);
+        }
+        Expr::Switch { selector, cases } => walk_switch(v, selector, cases),
+        Expr::Index { array, index } => {
+            v.visit_expr(array);
+            v.visit_expr(index);
+        }
+        Expr::Assign { target, value } => {
+            v.visit_expr(target);
+            v.visit_expr(value);
+        }
+    }
+}
+
+/// Replaces `expr` with the result of `f` applied to it.
+fn replace_with(expr: &mut Expr, f: impl FnOnce(Expr) -> Expr) {
+    let value = std::mem::replace(expr, Expr::This);
+    *expr = f(value);
+}
+
+/// Removes casts of values that already have the target type, e.g. repeated
+/// casts or casts of literals.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RemoveRedundantCasts;
+
+impl RemoveRedundantCasts {
+    /// Type of `expr` if it can be determined without context.
+    fn known_type(expr: &Expr) -> Option<Type> {
+        match expr {
+            Expr::Literal(it) if !matches!(it, crate::ir::value::Literal::Null) => {
+                Some(Type::from(&it.ty()))
+            }
+            Expr::New { ty, .. } | Expr::NewArray { ty, .. } | Expr::Cast { ty, .. } => {
+                Some(ty.clone())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl VisitMut for RemoveRedundantCasts {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+        let Expr::Cast { ty, value } = expr else {
+            return;
+        };
+        if Self::known_type(value).as_ref() == Some(ty) {
+            replace_with(expr, |it| match it {
+                Expr::Cast { value, .. } => *value,
+                other => other,
+            });
+        }
+    }
+}
+
+impl RewritePass for RemoveRedundantCasts {
+    fn name(&self) -> &'static str {
+        "remove-redundant-casts"
+    }
+
+    fn rewrite(&self, unit: &mut CompilationUnit) {
+        Self.visit_compilation_unit(unit)
+    }
+}
+
+/// Simplifies negated equality checks (`!(a == b)` to `a != b`) and double
+/// negation.
+///
+/// Relational operators are left alone as `!(a < b)` and `a >= b` differ for
+/// `NaN` operands.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SimplifyNegation;
+
+impl VisitMut for SimplifyNegation {
+    fn visit_expr(&mut self, expr: &mut Expr) {
+        walk_expr(self, expr);
+        let Expr::Unary {
+            op: UnaryOp::Not,
+            operand,
+        } = expr
+        else {
+            return;
+        };
+        match operand.as_mut() {
+            Expr::Binary { op, .. } if matches!(op, BinaryOp::Eq | BinaryOp::Ne) => {
+                *op = if *op == BinaryOp::Eq {
+                    BinaryOp::Ne
+                } else {
+                    BinaryOp::Eq
+                };
+            }
+            Expr::Unary {
+                op: UnaryOp::Not, ..
+            } => {}
+            _ => return,
+        }
+        replace_with(expr, |it| match it {
+            Expr::Unary { operand, .. } => match *operand {
+                Expr::Unary {
+                    op: UnaryOp::Not,
+                    operand,
+                } => *operand,
+                other => other,
+            },
+            other => other,
+        });
+    }
+}
+
+impl RewritePass for SimplifyNegation {
+    fn name(&self) -> &'static str {
+        "simplify-negation"
+    }
+
+    fn rewrite(&self, unit: &mut CompilationUnit) {
+        Self.visit_compilation_unit(unit)
+    }
+}
+
+/// Turns `else { if (...) {...} }` into `else if (...) {...}`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CollapseElseIf;
+
+impl VisitMut for CollapseElseIf {
+    fn visit_stmt(&mut self, stmt: &mut Stmt) {
+        walk_stmt(self, stmt);
+        let StmtKind::If {
+            otherwise: Some(otherwise),
+            ..
+        } = &mut stmt.kind
+        else {
+            return;
+        };
+        if let StmtKind::Block(block) = &mut otherwise.kind {
+            if let [Stmt {
+                kind: StmtKind::If { .. },
+                ..
+            }] = block.statements.as_slice()
+            {
+                let nested = block.statements.pop().expect("single statement");
+                **otherwise = nested;
+            }
+        }
+    }
+}
+
+impl RewritePass for CollapseElseIf {
+    fn name(&self) -> &'static str {
+        "collapse-else-if"
+    }
+
+    fn rewrite(&self, unit: &mut CompilationUnit) {
+        Self.visit_compilation_unit(unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jvm_class_format::JVMPrimitive;
+
+    use super::*;
+    use crate::ir::value::Literal;
+
+    fn name(it: &str) -> Expr {
+        Expr::Name(it.to_string())
+    }
+
+    #[test]
+    fn negation() {
+        let mut expr = Expr::unary(
+            UnaryOp::Not,
+            Expr::binary(BinaryOp::Eq, name("a"), name("b")),
+        );
+        SimplifyNegation.visit_expr(&mut expr);
+        assert_eq!(expr, Expr::binary(BinaryOp::Ne, name("a"), name("b")));
+
+        let mut expr = Expr::unary(UnaryOp::Not, Expr::unary(UnaryOp::Not, name("a")));
+        SimplifyNegation.visit_expr(&mut expr);
+        assert_eq!(expr, name("a"));
+
+        let lt = Expr::unary(
+            UnaryOp::Not,
+            Expr::binary(BinaryOp::Lt, name("a"), name("b")),
+        );
+        let mut expr = lt.clone();
+        SimplifyNegation.visit_expr(&mut expr);
+        assert_eq!(expr, lt);
+    }
+
+    #[test]
+    fn redundant_casts() {
+        let int = Type::Primitive(JVMPrimitive::TInt);
+        let mut expr = Expr::Cast {
+            ty: int.clone(),
+            value: Box::new(Expr::Cast {
+                ty: int.clone(),
+                value: Box::new(name("a")),
+            }),
+        };
+        RemoveRedundantCasts.visit_expr(&mut expr);
+        assert_eq!(
+            expr,
+            Expr::Cast {
+                ty: int.clone(),
+                value: Box::new(name("a")),
+            }
+        );
+
+        let mut expr = Expr::Cast {
+            ty: int,
+            value: Box::new(Expr::Literal(Literal::Int(1))),
+        };
+        RemoveRedundantCasts.visit_expr(&mut expr);
+        assert_eq!(expr, Expr::Literal(Literal::Int(1)));
+    }
+
+    #[test]
+    fn else_if() {
+        let branch = |condition: &str, otherwise: Option<Stmt>| -> Stmt {
+            StmtKind::If {
+                condition: name(condition),
+                then: Block::default(),
+                otherwise: otherwise.map(Box::new),
+            }
+            .into()
+        };
+        let mut stmt = branch(
+            "a",
+            Some(
+                StmtKind::Block(Block {
+                    statements: vec![branch("b", None)],
+                })
+                .into(),
+            ),
+        );
+        CollapseElseIf.visit_stmt(&mut stmt);
+        assert_eq!(stmt, branch("a", Some(branch("b", None))));
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt::Display;
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, PartialOrd, Ord, Hash)]
+#[non_exhaustive]
+#[repr(u32)]
+pub enum JavaVersion {
+    Unsupported = 0,
+    /// Java 1.0 up to 1.3
+    #[default]
+    Java1,
+    /// Java 1.4
+    Java4,
+    Java5,
+    Java6,
+    Java7,
+    Java8,
+    Java9,
+    Java10,
+    Java11,
+    Java12,
+    Java13,
+    Java14,
+    Java15,
+    Java16,
+    Java17,
+    Java18,
+    Java19,
+    Java20,
+    Java21,
+}
+
+impl JavaVersion {
+    /// Newest supported version.
+    pub const LATEST: JavaVersion = JavaVersion::Java21;
+
+    const RELEASES: [(u16, JavaVersion); 19] = [
+        (1, JavaVersion::Java1),
+        (4, JavaVersion::Java4),
+        (5, JavaVersion::Java5),
+        (6, JavaVersion::Java6),
+        (7, JavaVersion::Java7),
+        (8, JavaVersion::Java8),
+        (9, JavaVersion::Java9),
+        (10, JavaVersion::Java10),
+        (11, JavaVersion::Java11),
+        (12, JavaVersion::Java12),
+        (13, JavaVersion::Java13),
+        (14, JavaVersion::Java14),
+        (15, JavaVersion::Java15),
+        (16, JavaVersion::Java16),
+        (17, JavaVersion::Java17),
+        (18, JavaVersion::Java18),
+        (19, JavaVersion::Java19),
+        (20, JavaVersion::Java20),
+        (21, JavaVersion::Java21),
+    ];
+
+    /// Version from a release number as used by `javac --release` (e.g. `8`
+    /// or `17`); `1.x` releases can be given by their minor number.
+    pub fn from_release(release: u16) -> Option<JavaVersion> {
+        let release = match release {
+            2 | 3 => 1,
+            other => other,
+        };
+        Self::RELEASES
+            .iter()
+            .find(|(it, _)| *it == release)
+            .map(|(_, version)| *version)
+    }
+
+    /// Release number of the version, e.g. `8`.
+    pub fn release(self) -> u16 {
+        Self::RELEASES
+            .iter()
+            .find(|(_, it)| *it == self)
+            .map(|(release, _)| *release)
+            .unwrap_or_default()
+    }
+
+    /// Version of the source language a class file with `major` version
+    /// was compiled from.
+    ///
+    /// Class files newer than the latest supported version are treated as
+    /// the latest version.
+    pub fn from_class_major(major: u16) -> JavaVersion {
+        match major {
+            0..=44 => JavaVersion::Unsupported,
+            45..=47 => JavaVersion::Java1,
+            48 => JavaVersion::Java4,
+            major => Self::from_release(major - 44).unwrap_or(Self::LATEST),
+        }
+    }
+
+    pub fn supports(self, feature: LanguageFeature) -> bool {
+        self >= feature.since()
+    }
+}
+
+impl Display for JavaVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JavaVersion::Unsupported => f.write_str("unsupported Java version"),
+            JavaVersion::Java1 => f.write_str("Java 1"),
+            JavaVersion::Java4 => f.write_str("Java 1.4"),
+            other => write!(f, "Java {}", other.release()),
+        }
+    }
+}
+
+/// Source language constructs which aren't available in all Java versions.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum LanguageFeature {
+    Assertions,
+    Generics,
+    Annotations,
+    Enums,
+    /// Implicit boxing and unboxing of primitives
+    Autoboxing,
+    Varargs,
+    EnhancedFor,
+    StringSwitch,
+    Diamond,
+    TryWithResources,
+    MultiCatch,
+    Lambdas,
+    MethodReferences,
+    DefaultMethods,
+    StaticInterfaceMethods,
+    PrivateInterfaceMethods,
+    Modules,
+    /// Local variable type inference (`var`)
+    Var,
+    SwitchExpressions,
+    TextBlocks,
+    Records,
+    InstanceofPatterns,
+    SealedClasses,
+    SwitchPatterns,
+    RecordPatterns,
+}
+
+impl LanguageFeature {
+    /// First version which supports the feature without preview flags.
+    pub fn since(self) -> JavaVersion {
+        match self {
+            LanguageFeature::Assertions => JavaVersion::Java4,
+            LanguageFeature::Generics
+            | LanguageFeature::Annotations
+            | LanguageFeature::Enums
+            | LanguageFeature::Autoboxing
+            | LanguageFeature::Varargs
+            | LanguageFeature::EnhancedFor => JavaVersion::Java5,
+            LanguageFeature::StringSwitch
+            | LanguageFeature::Diamond
+            | LanguageFeature::TryWithResources
+            | LanguageFeature::MultiCatch => JavaVersion::Java7,
+            LanguageFeature::Lambdas
+            | LanguageFeature::MethodReferences
+            | LanguageFeature::DefaultMethods
+            | LanguageFeature::StaticInterfaceMethods => JavaVersion::Java8,
+            LanguageFeature::PrivateInterfaceMethods | LanguageFeature::Modules => {
+                JavaVersion::Java9
+            }
+            LanguageFeature::Var => JavaVersion::Java10,
+            LanguageFeature::SwitchExpressions => JavaVersion::Java14,
+            LanguageFeature::TextBlocks => JavaVersion::Java15,
+            LanguageFeature::Records | LanguageFeature::InstanceofPatterns => JavaVersion::Java16,
+            LanguageFeature::SealedClasses => JavaVersion::Java17,
+            LanguageFeature::SwitchPatterns | LanguageFeature::RecordPatterns => {
+                JavaVersion::Java21
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn class_file_versions() {
+        assert_eq!(JavaVersion::from_class_major(45), JavaVersion::Java1);
+        assert_eq!(JavaVersion::from_class_major(48), JavaVersion::Java4);
+        assert_eq!(JavaVersion::from_class_major(52), JavaVersion::Java8);
+        assert_eq!(JavaVersion::from_class_major(61), JavaVersion::Java17);
+        assert_eq!(JavaVersion::from_class_major(99), JavaVersion::LATEST);
+        assert_eq!(JavaVersion::from_release(3), Some(JavaVersion::Java1));
+        assert_eq!(JavaVersion::Java8.to_string(), "Java 8");
+
+        assert!(JavaVersion::Java8.supports(LanguageFeature::Lambdas));
+        assert!(!JavaVersion::Java7.supports(LanguageFeature::Lambdas));
+        assert!(!JavaVersion::Java15.supports(LanguageFeature::Records));
+    }
+}
@@ -1,18 +1,31 @@
 use java::JavaGeneratorBuilder;
 use std::{io::Cursor, ops::Deref};
 
-use self::indent::IndentKind;
-
 pub mod java;
-pub mod indent;
+pub mod writer;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize)]
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, serde::Serialize, serde::Deserialize,
+)]
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 pub enum GeneratorVerbosity {
     /// Generate all expressions, including synthetic and implicit ones (e.g. `super()`)
     All,
     /// Generate clean, minimal sources that produce the same output classes
-    Clean
+    Clean,
+}
+
+/// Order in which class members are written.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum MemberOrder {
+    /// Same order as in the class file
+    #[default]
+    ClassFile,
+    /// Approximate source order using line numbers of method bodies
+    LineNumber,
+    /// Alphabetical, grouped by member kind
+    Alphabetical,
 }
 
 pub trait GeneratorBackend: Sized {
@@ -0,0 +1,164 @@
+use std::io::Write;
+
+/// Line terminator used in generated code.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum LineEnding {
+    /// Unix style line feed (`\n`)
+    #[default]
+    Lf,
+    /// Windows style carriage return and line feed (`\r\n`)
+    CrLf,
+}
+
+/// Placement of opening braces of blocks.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+pub enum BraceStyle {
+    /// Opening brace at the end of the line that starts the block
+    #[default]
+    SameLine,
+    /// Opening brace on its own line
+    NextLine,
+}
+
+/// Formatting of generated code.
+///
+/// Generators write code with `\n` line endings and without indentation;
+/// nested code is indented line by line when it's embedded into its parent
+/// block, and line endings are converted once the output is complete.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeStyle {
+    /// String used for a single level of indentation.
+    pub indent: String,
+    pub line_ending: LineEnding,
+    pub brace_style: BraceStyle,
+    /// Preferred maximum line length, long argument lists and call chains
+    /// are wrapped to fit it.
+    pub line_width: usize,
+}
+
+impl Default for CodeStyle {
+    fn default() -> Self {
+        CodeStyle {
+            indent: "  ".to_string(),
+            line_ending: LineEnding::default(),
+            brace_style: BraceStyle::default(),
+            line_width: 100,
+        }
+    }
+}
+
+impl CodeStyle {
+    /// Width of `levels` levels of indentation, counting tabs as 4 columns.
+    pub fn indent_width(&self, levels: usize) -> usize {
+        let width: usize = self
+            .indent
+            .chars()
+            .map(|it| if it == '\t' { 4 } else { 1 })
+            .sum();
+        width * levels
+    }
+
+    /// Returns whether all lines of `code` fit into the line width when
+    /// indented by `levels` levels.
+    pub fn fits(&self, code: &[u8], levels: usize) -> bool {
+        let available = self.line_width.saturating_sub(self.indent_width(levels));
+        code.split(|it| *it == b'\n')
+            .all(|line| String::from_utf8_lossy(line).chars().count() <= available)
+    }
+
+    /// Number of lines the opening brace of a block adds after the header.
+    pub fn brace_lines(&self) -> usize {
+        match self.brace_style {
+            BraceStyle::SameLine => 0,
+            BraceStyle::NextLine => 1,
+        }
+    }
+
+    /// Writes `code` with each non-empty line indented by `levels` levels.
+    pub fn write_indented<W: Write>(
+        &self,
+        w: &mut W,
+        code: &[u8],
+        levels: usize,
+    ) -> Result<(), std::io::Error> {
+        let indent = self.indent.repeat(levels);
+        for line in code.split_inclusive(|it| *it == b'\n') {
+            if line != b"\n" {
+                w.write_all(indent.as_bytes())?;
+            }
+            w.write_all(line)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the opening brace of a block after its header, followed by a
+    /// line ending.
+    pub fn open_block<W: Write>(&self, w: &mut W) -> Result<(), std::io::Error> {
+        match self.brace_style {
+            BraceStyle::SameLine => w.write_all(b" {\n"),
+            BraceStyle::NextLine => w.write_all(b"\n{\n"),
+        }
+    }
+
+    /// Writes a block with indented `body` after a block header (e.g. method
+    /// signature), including the trailing line ending.
+    ///
+    /// Empty blocks are written as `{}` when `compact_empty` is set.
+    pub fn write_block<W: Write>(
+        &self,
+        w: &mut W,
+        body: &[u8],
+        compact_empty: bool,
+    ) -> Result<(), std::io::Error> {
+        if body.is_empty() && compact_empty {
+            return match self.brace_style {
+                BraceStyle::SameLine => w.write_all(b" {}\n"),
+                BraceStyle::NextLine => w.write_all(b"\n{}\n"),
+            };
+        }
+        self.open_block(w)?;
+        self.write_indented(w, body, 1)?;
+        w.write_all(b"}\n")
+    }
+
+    /// Converts `\n` line endings of complete output to the configured ones.
+    pub fn convert_line_endings(&self, code: Vec<u8>) -> Vec<u8> {
+        match self.line_ending {
+            LineEnding::Lf => code,
+            LineEnding::CrLf => {
+                let mut result = Vec::with_capacity(code.len() + code.len() / 16);
+                for byte in code {
+                    if byte == b'\n' {
+                        result.push(b'\r');
+                    }
+                    result.push(byte);
+                }
+                result
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks() {
+        let mut style = CodeStyle::default();
+        let mut out = Vec::new();
+        style
+            .write_block(&mut out, b"a('{');\n\nb(\"}\");\n", false)
+            .unwrap();
+        assert_eq!(out, b" {\n  a('{');\n\n  b(\"}\");\n}\n");
+
+        style.brace_style = BraceStyle::NextLine;
+        style.indent = "\t".to_string();
+        style.line_ending = LineEnding::CrLf;
+        let mut out = Vec::new();
+        style.write_block(&mut out, b"a();\n", false).unwrap();
+        assert_eq!(style.convert_line_endings(out), b"\r\n{\r\n\ta();\r\n}\r\n");
+    }
+}
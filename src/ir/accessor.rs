@@ -0,0 +1,280 @@
+//! Inlining of synthetic `access$NNN` accessors.
+//!
+//! Before nest-based access control (Java 11), javac let nested classes use
+//! private members of each other through static accessors generated in the
+//! class declaring the member:
+//!
+//! ```java
+//! static int access$000(Outer x) {
+//!   return x.secret;
+//! }
+//! ```
+//!
+//! Calls of accessors are written as the access they perform
+//! (`outer.secret`, `outer.secret = value` or `outer.secret++`).
+
+use std::collections::HashMap;
+
+use jvm_class_format::attribute::{AsData, CodeData};
+use jvm_class_format::{Class, ClassPath, JVMPrimitive, JVMType, Member, MethodAccessFlags};
+
+use super::expression::{Expression, PutField, PutStatic, ReturnStatement};
+use super::value::{BinaryOp, Literal, MethodRef, StackValue};
+use super::{decompile, MethodLimits, Statement};
+
+/// Prefix of synthetic accessor method names.
+pub const ACCESSOR_PREFIX: &str = "access$";
+
+/// Returns `true` for synthetic `access$NNN` accessor methods.
+pub fn is_accessor(method: &Member) -> bool {
+    method.name.starts_with(ACCESSOR_PREFIX)
+        && method.is_synthetic()
+        && method.method_flags().contains(MethodAccessFlags::STATIC)
+}
+
+/// What a call of an accessor does.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Access {
+    /// Reads a field or calls a method.
+    Value(StackValue),
+    /// Assigns `value` to `field` (a field read), evaluating to `value`.
+    Assign {
+        field: StackValue,
+        value: Box<StackValue>,
+    },
+    /// Adds one to or subtracts one from `field` (a field read).
+    Increment {
+        field: StackValue,
+        decrement: bool,
+        /// Whether the access evaluates to the new value (`++x`) rather than
+        /// the previous one (`x++`).
+        prefix: bool,
+    },
+}
+
+impl Access {
+    fn operands(&self) -> Vec<&StackValue> {
+        match self {
+            Access::Value(value) | Access::Increment { field: value, .. } => vec![value],
+            Access::Assign { field, value } => vec![field, value.as_ref()],
+        }
+    }
+
+    fn operands_mut(&mut self) -> Vec<&mut StackValue> {
+        match self {
+            Access::Value(value) | Access::Increment { field: value, .. } => vec![value],
+            Access::Assign { field, value } => vec![field, value.as_mut()],
+        }
+    }
+}
+
+/// Accesses performed by accessors of the classes of a nest.
+#[derive(Debug, Clone, Default)]
+pub struct Accessors {
+    /// Access with arguments as locals, keyed by the class declaring the
+    /// accessor and its name.
+    accessors: HashMap<(ClassPath, String), Access>,
+}
+
+impl Accessors {
+    /// Reads accessors declared by `class`; returns `false` if it doesn't
+    /// declare any that can be inlined.
+    pub fn add_class(&mut self, class: &Class) -> bool {
+        let mut found = false;
+        for method in class.methods.iter().filter(|it| is_accessor(it)) {
+            let Some(code) = method
+                .attributes
+                .get("Code")
+                .and_then(|it| AsData::<CodeData>::as_data(it).ok())
+            else {
+                continue;
+            };
+            let Ok(statements) = decompile(class, method, code, &MethodLimits::default()) else {
+                continue;
+            };
+            let Some(access) = access(&statements) else {
+                continue;
+            };
+            if !uses_arguments_once(&access, &method.descriptor.arguments) {
+                continue;
+            }
+            self.accessors
+                .insert((class.class_name.clone(), method.name.clone()), access);
+            found = true;
+        }
+        found
+    }
+
+    /// Returns `true` if accessor `name` of `class` is inlined into its calls.
+    pub fn contains(&self, class: &ClassPath, name: &str) -> bool {
+        self.accessors
+            .contains_key(&(class.clone(), name.to_string()))
+    }
+
+    /// Access performed by a call of `method` with `args`, if it's a known
+    /// accessor.
+    pub fn inline(&self, method: &MethodRef, args: &[StackValue]) -> Option<Access> {
+        let access = self
+            .accessors
+            .get(&(method.class.clone(), method.name.clone()))?;
+        let slots = argument_slots(&method.descriptor.arguments);
+        if slots.len() != args.len() {
+            return None;
+        }
+        let mut result = access.clone();
+        for operand in result.operands_mut() {
+            substitute(operand, &slots, args);
+        }
+        Some(result)
+    }
+}
+
+/// Field read and stored value of a field assignment.
+fn field_store(expression: &Expression) -> Option<(StackValue, &StackValue)> {
+    match expression {
+        Expression::PutField(PutField {
+            target,
+            field,
+            value,
+        }) => Some((
+            StackValue::GetField {
+                target: target.clone(),
+                field: field.clone(),
+            },
+            value,
+        )),
+        Expression::PutStatic(PutStatic { field, value }) => {
+            Some((StackValue::GetStatic(field.clone()), value))
+        }
+        _ => None,
+    }
+}
+
+/// Returns whether `value` is a read `is_read` accepts plus one (`false`)
+/// or minus one (`true`).
+fn step(value: &StackValue, is_read: impl Fn(&StackValue) -> bool) -> Option<bool> {
+    let StackValue::Binary { op, left, right } = value else {
+        return None;
+    };
+    let one = matches!(
+        right.as_ref(),
+        StackValue::Literal(
+            Literal::Int(1) | Literal::Long(1) | Literal::Float(1.0) | Literal::Double(1.0)
+        )
+    );
+    if !one || !is_read(left) {
+        return None;
+    }
+    match op {
+        BinaryOp::Add => Some(false),
+        BinaryOp::Sub => Some(true),
+        _ => None,
+    }
+}
+
+/// Increment of a field kept in local `index` and returned from it:
+/// `int t = x.f; x.f = t + 1; return t;` or
+/// `int t = x.f + 1; x.f = t; return t;`.
+fn increment(index: usize, local_value: &StackValue, store: &Expression) -> Option<Access> {
+    let (field, stored) = field_store(store)?;
+    let is_local =
+        |it: &StackValue| matches!(it, StackValue::Local { index: it, .. } if *it == index);
+    let (decrement, prefix) = if *local_value == field {
+        (step(stored, is_local)?, false)
+    } else if is_local(stored) {
+        (step(local_value, |it| *it == field)?, true)
+    } else {
+        return None;
+    };
+    Some(Access::Increment {
+        field,
+        decrement,
+        prefix,
+    })
+}
+
+/// Access performed by an accessor body.
+///
+/// Values of stores and increments are kept in a local by the decompiler, as
+/// the bytecode duplicates them on the stack.
+fn access(statements: &[Statement]) -> Option<Access> {
+    let expressions: Vec<_> = statements.iter().map(|it| &it.expression).collect();
+    let returned = |it: &Expression| match it {
+        Expression::ReturnStatement(ReturnStatement(value)) => Some(value.clone()),
+        _ => None,
+    };
+    match expressions.as_slice() {
+        [result] => Some(Access::Value(returned(result)??)),
+        [Expression::Value(value @ StackValue::Invoke { .. }), result]
+            if returned(result)?.is_none() =>
+        {
+            Some(Access::Value(value.clone()))
+        }
+        // `return x.f = value;`
+        [store, result] => {
+            let (field, value) = field_store(store)?;
+            (Some(value) == returned(result)?.as_ref()).then(|| Access::Assign {
+                field,
+                value: Box::new(value.clone()),
+            })
+        }
+        [Expression::StoreLocal(local), store, result] => match returned(result)? {
+            Some(StackValue::Local { index, .. }) if index == local.index => {
+                increment(local.index, &local.value, store)
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Local variable slots of static method arguments.
+fn argument_slots(arguments: &[JVMType]) -> Vec<usize> {
+    let mut slot = 0;
+    arguments
+        .iter()
+        .map(|it| {
+            let current = slot;
+            slot += match it {
+                JVMType::TPrimitive(JVMPrimitive::TLong | JVMPrimitive::TDouble) => 2,
+                _ => 1,
+            };
+            current
+        })
+        .collect()
+}
+
+/// Returns `true` if `access` reads each argument exactly once, so inlining
+/// it neither repeats nor drops evaluation of call arguments.
+fn uses_arguments_once(access: &Access, arguments: &[JVMType]) -> bool {
+    let slots = argument_slots(arguments);
+    let mut uses = vec![0; slots.len()];
+    let mut unknown = false;
+    for operand in access.operands() {
+        operand.any(&mut |it| {
+            match it {
+                StackValue::Local { index, .. } => match slots.iter().position(|it| it == index) {
+                    Some(i) => uses[i] += 1,
+                    None => unknown = true,
+                },
+                // switch cases aren't substituted
+                StackValue::Switch(_) | StackValue::This => unknown = true,
+                _ => {}
+            }
+            false
+        });
+    }
+    !unknown && uses.iter().all(|it| *it == 1)
+}
+
+fn substitute(value: &mut StackValue, slots: &[usize], args: &[StackValue]) {
+    if let StackValue::Local { index, .. } = value {
+        if let Some(i) = slots.iter().position(|it| it == index) {
+            *value = args[i].clone();
+        }
+        return;
+    }
+    for operand in value.operands_mut() {
+        substitute(operand, slots, args);
+    }
+}
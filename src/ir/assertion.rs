@@ -0,0 +1,136 @@
+//! Recognition of `assert` statements.
+//!
+//! javac stores whether assertions are disabled for a class in a synthetic
+//! `$assertionsDisabled` field initialized in `<clinit>` and compiles
+//! `assert cond : msg;` into:
+//!
+//! ```java
+//! if (!$assertionsDisabled && !cond) {
+//!   throw new AssertionError(msg);
+//! }
+//! ```
+
+use jvm_class_format::{FieldAccessFlags, Member};
+
+use super::expression::{AssertStatement, Expression, IfStatement, PutStatic, ThrowStatement};
+use super::switch::restore_nested;
+use super::value::{CompareOp, Literal, StackValue};
+use super::{any_value, Statement};
+
+/// Name of the synthetic field holding the assertion status of a class.
+pub const ASSERTIONS_DISABLED: &str = "$assertionsDisabled";
+
+/// Returns `true` for the synthetic `$assertionsDisabled` field.
+pub fn is_assertion_status(field: &Member) -> bool {
+    field.name == ASSERTIONS_DISABLED
+        && field
+            .field_flags()
+            .contains(FieldAccessFlags::STATIC | FieldAccessFlags::SYNTHETIC)
+}
+
+/// Restores `assert` statements in `statements` and nested blocks.
+pub fn restore_assertions(statements: &mut [Statement]) {
+    restore_nested(statements, |it| restore_assertions(it));
+
+    for statement in statements.iter_mut() {
+        if let Expression::If(it) = &statement.expression {
+            if let Some(assert) = assertion(it) {
+                statement.expression = Expression::Assert(assert);
+            }
+        }
+    }
+}
+
+/// Removes initialization of `$assertionsDisabled` from `statements`.
+pub fn remove_assertion_status(statements: &mut Vec<Statement>) {
+    statements.retain(|it| !is_status_initializer(&it.expression));
+}
+
+fn is_status_initializer(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::PutStatic(PutStatic { field, .. }) if field.name == ASSERTIONS_DISABLED
+    )
+}
+
+fn is_status(value: &StackValue) -> bool {
+    matches!(value, StackValue::GetStatic(field) if field.name == ASSERTIONS_DISABLED)
+}
+
+/// Returns `true` if `statements` read `$assertionsDisabled` outside of
+/// restored `assert` statements.
+pub fn reads_assertion_status(statements: &[Statement]) -> bool {
+    any_value(statements, &mut is_status)
+}
+
+/// Matches `if (!$assertionsDisabled) { if (!cond) throw new AssertionError(msg); }`.
+fn assertion(statement: &IfStatement) -> Option<AssertStatement> {
+    if statement.otherwise.is_some() || !checks_enabled(&statement.condition) {
+        return None;
+    }
+    match statement.then.as_slice() {
+        // `assert false`
+        [Statement {
+            expression: Expression::Throw(ThrowStatement(error)),
+            ..
+        }] => Some(AssertStatement {
+            condition: StackValue::Literal(Literal::Boolean(false)),
+            message: assertion_message(error)?,
+        }),
+        [Statement {
+            expression:
+                Expression::If(IfStatement {
+                    condition,
+                    then,
+                    otherwise: None,
+                }),
+            ..
+        }] => {
+            let [Statement {
+                expression: Expression::Throw(ThrowStatement(error)),
+                ..
+            }] = then.as_slice()
+            else {
+                return None;
+            };
+            Some(AssertStatement {
+                condition: condition.clone().negate_condition(),
+                message: assertion_message(error)?,
+            })
+        }
+        _ => None,
+    }
+}
+
+/// `$assertionsDisabled == false`
+fn checks_enabled(condition: &StackValue) -> bool {
+    let StackValue::Compare {
+        op: CompareOp::Eq,
+        left,
+        right,
+    } = condition
+    else {
+        return false;
+    };
+    is_status(left)
+        && matches!(
+            right.as_ref(),
+            StackValue::Literal(Literal::Int(0) | Literal::Boolean(false))
+        )
+}
+
+/// Message of a `new AssertionError(...)`; `None` if `error` is something
+/// else.
+fn assertion_message(error: &StackValue) -> Option<Option<StackValue>> {
+    let StackValue::New { class, args } = error else {
+        return None;
+    };
+    if !class.is_in_java_lang() || class.name != "AssertionError" {
+        return None;
+    }
+    match args.as_slice() {
+        [] => Some(None),
+        [message] => Some(Some(message.clone())),
+        _ => None,
+    }
+}
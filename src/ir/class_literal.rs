@@ -0,0 +1,144 @@
+//! Recognition of class literals compiled for Java 1.4 and older.
+//!
+//! Before `ldc` could load classes, javac compiled `Foo.class` into a lookup
+//! cached in a synthetic static field, using a synthetic `class$` helper which
+//! calls `Class.forName`:
+//!
+//! ```java
+//! (class$Foo == null ? (class$Foo = class$("Foo")) : class$Foo)
+//! ```
+//!
+//! Eclipse emits the same lookup with a `dup` of the field value instead of a
+//! second read.
+//!
+//! Lookups are always collapsed; unlike fernflower's `dc4` there's no option
+//! to keep them, as the assignment within the lookup can't be written
+//! otherwise.
+
+use jvm_class_format::{FieldAccessFlags, JVMType, Member, MethodAccessFlags, Op};
+
+use super::flow::MethodCode;
+use super::frame::RuntimeFrame;
+use super::value::{class_constant_type, FieldRef, Literal, StackValue};
+use super::{any_value, Statement};
+
+/// Name of the synthetic method which loads classes by name.
+pub const CLASS_HELPER: &str = "class$";
+
+/// javac: `getstatic; ifnonnull A; ldc; invokestatic; dup; putstatic; goto B;
+/// A: getstatic; B:`
+const JAVAC_PATTERN: [Op; 8] = [
+    Op::Getstatic,
+    Op::Ifnonnull,
+    Op::Ldc,
+    Op::Invokestatic,
+    Op::Dup,
+    Op::Putstatic,
+    Op::Goto,
+    Op::Getstatic,
+];
+
+/// Eclipse: `getstatic; dup; ifnonnull A; pop; ldc; invokestatic; dup;
+/// putstatic; A:`
+const ECJ_PATTERN: [Op; 8] = [
+    Op::Getstatic,
+    Op::Dup,
+    Op::Ifnonnull,
+    Op::Pop,
+    Op::Ldc,
+    Op::Invokestatic,
+    Op::Dup,
+    Op::Putstatic,
+];
+
+/// Returns `true` for the synthetic `class$` helper method.
+pub fn is_class_helper(method: &Member) -> bool {
+    method.name == CLASS_HELPER
+        && method.method_flags().contains(MethodAccessFlags::STATIC)
+        && method.descriptor.to_string() == "(Ljava/lang/String;)Ljava/lang/Class;"
+}
+
+/// Returns `true` for synthetic static fields caching class literals.
+pub fn is_class_cache(field: &Member) -> bool {
+    field.name.starts_with(CLASS_HELPER)
+        && field.field_flags().contains(FieldAccessFlags::STATIC)
+        && is_class_type(&field.descriptor.value)
+}
+
+fn is_class_type(ty: &JVMType) -> bool {
+    matches!(ty, JVMType::TClass(it) if it.is_in_java_lang() && it.name == "Class")
+}
+
+/// Returns `true` if `statements` still use class literal caches or the
+/// `class$` helper, e.g. because a lookup wasn't recognized.
+pub fn uses_class_cache(statements: &[Statement]) -> bool {
+    any_value(statements, &mut |it| match it {
+        StackValue::GetStatic(field) => field.name.starts_with(CLASS_HELPER),
+        StackValue::Invoke { method, .. } => method.name == CLASS_HELPER,
+        _ => false,
+    })
+}
+
+impl MethodCode<'_> {
+    /// Matches a cached class lookup starting at `index`, returning the
+    /// number of its instructions and the class literal.
+    pub(super) fn class_literal(
+        &self,
+        frame: &RuntimeFrame,
+        index: usize,
+    ) -> Option<(usize, StackValue)> {
+        let ops = |pattern: &[Op]| {
+            self.instructions
+                .get(index..index + pattern.len())
+                .is_some_and(|it| {
+                    it.iter()
+                        .zip(pattern)
+                        .all(|(it, op)| it.op() == *op || (*op == Op::Ldc && it.op() == Op::LdcW))
+                })
+        };
+        // indices of the field reads and writes, class name and helper call
+        let (fields, name, helper) = if ops(&JAVAC_PATTERN)
+            && self.jump_target(index + 1) == Some(index + 7)
+            && self.jump_target(index + 6) == Some(index + 8)
+        {
+            (vec![index, index + 5, index + 7], index + 2, index + 3)
+        } else if ops(&ECJ_PATTERN) && self.jump_target(index + 2) == Some(index + 8) {
+            (vec![index, index + 7], index + 4, index + 5)
+        } else {
+            return None;
+        };
+
+        let field = self.field_at(frame, fields[0])?;
+        if !field.name.starts_with(CLASS_HELPER)
+            || !is_class_type(&field.ty)
+            || fields[1..]
+                .iter()
+                .any(|it| self.field_at(frame, *it).as_ref() != Some(&field))
+        {
+            return None;
+        }
+        let method = frame.method_ref(self.u16_arg(helper)).ok()?;
+        if method.name != CLASS_HELPER {
+            return None;
+        }
+
+        let name = match self.instructions[name].op() {
+            Op::Ldc => self.instructions[name].args()[0] as usize,
+            _ => self.u16_arg(name),
+        };
+        let Ok(Literal::String(name)) = Literal::from_constant(frame.constant_pool, name) else {
+            return None;
+        };
+        let ty = class_constant_type(&name.replace('.', "/")).ok()?;
+        Some((8, StackValue::Literal(Literal::Class(ty))))
+    }
+
+    fn field_at(&self, frame: &RuntimeFrame, index: usize) -> Option<FieldRef> {
+        frame.field_ref(self.u16_arg(index)).ok()
+    }
+
+    fn u16_arg(&self, index: usize) -> usize {
+        let args = self.instructions[index].args();
+        ((args[0] as usize) << 8) | args[1] as usize
+    }
+}
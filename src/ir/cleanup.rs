@@ -0,0 +1,405 @@
+//! Removal of code javac generates implicitly.
+//!
+//! Since Java 5, javac boxes and unboxes primitives with calls to the wrapper
+//! classes (`Integer.valueOf(x)`, `x.intValue()`) and passes variable arity
+//! arguments in a new array (`f(new Object[] {a, b})`). Both are written out
+//! in the decompiled source only where leaving them out would change its
+//! meaning.
+//!
+//! Whether a call would pick a different overload without them can only be
+//! told for methods of classes in the [`MethodIndex`]; calls to other classes
+//! (e.g. of the JDK) keep their boxing and variable arity arrays.
+
+use std::collections::{HashMap, HashSet};
+
+use jvm_class_format::{Class, ClassPath, Descriptor, JVMPrimitive, JVMType, MethodAccessFlags};
+
+use super::expression::{Expression, ReturnStatement, ThrowStatement};
+use super::value::{CompareOp, InvokeKind, Literal, MethodRef, StackValue};
+use super::Statement;
+
+/// First class file version which can contain implicit boxing and varargs
+/// calls (Java 5).
+const JAVA_5_MAJOR: u16 = 49;
+
+/// Methods declared by classes next to the generated ones, used to tell
+/// whether a call picks the same overload without implicit code.
+#[derive(Debug, Clone, Default)]
+pub struct MethodIndex {
+    classes: HashMap<ClassPath, IndexedClass>,
+}
+
+#[derive(Debug, Clone)]
+struct IndexedClass {
+    supertypes: Vec<ClassPath>,
+    /// Methods that can be called from source code.
+    methods: Vec<IndexedMethod>,
+}
+
+#[derive(Debug, Clone)]
+struct IndexedMethod {
+    name: String,
+    descriptor: Descriptor,
+    varargs: bool,
+}
+
+impl From<&Class> for IndexedClass {
+    fn from(class: &Class) -> Self {
+        IndexedClass {
+            supertypes: class
+                .super_name
+                .iter()
+                .chain(&class.interfaces)
+                .cloned()
+                .collect(),
+            methods: class
+                .methods
+                .iter()
+                .filter(|it| !it.is_bridge() && !it.is_synthetic())
+                .map(|it| IndexedMethod {
+                    name: it.name.clone(),
+                    descriptor: it.descriptor.clone(),
+                    varargs: it.method_flags().contains(MethodAccessFlags::VARARGS),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl MethodIndex {
+    /// Adds methods declared by `class`.
+    pub fn add_class(&mut self, class: &Class) {
+        self.classes
+            .insert(class.class_name.clone(), IndexedClass::from(class));
+    }
+}
+
+/// Removes implicit boxing and unboxing calls and collapses arrays passed to
+/// variable arity methods in `statements` and nested blocks.
+///
+/// Methods of `class` are known even if it's not in `index`.
+pub fn remove_implicit_code(class: &Class, index: &MethodIndex, statements: &mut [Statement]) {
+    if class.compiler_info.major < JAVA_5_MAJOR {
+        return;
+    }
+    Cleanup {
+        own: (class.class_name.clone(), IndexedClass::from(class)),
+        index,
+    }
+    .statements(statements);
+}
+
+struct Cleanup<'i> {
+    own: (ClassPath, IndexedClass),
+    index: &'i MethodIndex,
+}
+
+impl Cleanup<'_> {
+    fn statements(&self, statements: &mut [Statement]) {
+        for statement in statements {
+            match &mut statement.expression {
+                Expression::ReturnStatement(ReturnStatement(Some(value)))
+                | Expression::Value(value)
+                | Expression::Throw(ThrowStatement(value))
+                | Expression::Yield(value) => self.value(value, true),
+                Expression::StoreLocal(it) => self.value(&mut it.value, true),
+                Expression::PutField(it) => {
+                    self.value(&mut it.target, false);
+                    self.value(&mut it.value, true);
+                }
+                Expression::PutStatic(it) => self.value(&mut it.value, true),
+                Expression::ArrayStore(it) => {
+                    self.value(&mut it.array, false);
+                    self.value(&mut it.index, true);
+                    self.value(&mut it.value, true);
+                }
+                Expression::If(it) => {
+                    self.value(&mut it.condition, true);
+                    self.statements(&mut it.then);
+                    if let Some(otherwise) = &mut it.otherwise {
+                        self.statements(otherwise);
+                    }
+                }
+                Expression::Switch(it) => {
+                    self.value(&mut it.value, true);
+                    for case in &mut it.cases {
+                        self.statements(&mut case.body);
+                    }
+                }
+                Expression::Assert(it) => {
+                    self.value(&mut it.condition, true);
+                    if let Some(message) = &mut it.message {
+                        self.value(message, true);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    /// Cleans up `value` and its operands; boxing of `value` itself is only
+    /// removed if `unbox` is set.
+    fn value(&self, value: &mut StackValue, unbox: bool) {
+        match value {
+            // `Integer.valueOf(x).hashCode()` can't be written as `x.hashCode()`
+            StackValue::Invoke {
+                method,
+                target,
+                args,
+                ..
+            } => {
+                if let Some(target) = target {
+                    self.value(target, false);
+                }
+                // `list.remove(Integer.valueOf(i))` would call `remove(int)`
+                let unbox_args = self.has_single_overload(method);
+                for arg in args.iter_mut() {
+                    self.value(arg, unbox_args);
+                }
+                if self.is_varargs(method) {
+                    collapse_varargs(args);
+                }
+            }
+            StackValue::New { class, args } => {
+                let unbox_args = self.has_single_constructor(class, args.len());
+                for arg in args {
+                    self.value(arg, unbox_args);
+                }
+            }
+            StackValue::GetField { target, .. } => self.value(target, false),
+            // `a.intValue() == b.intValue()` would compare references
+            StackValue::Compare {
+                op: CompareOp::Eq | CompareOp::Ne,
+                left,
+                right,
+            } if unboxed(left).is_some() && unboxed(right).is_some() => {
+                self.value(left, false);
+                self.value(right, false);
+            }
+            // `c ? Integer.valueOf(1) : i` would unbox `i`
+            StackValue::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => {
+                let unbox_operands = boxed(then).is_some()
+                    && boxed(otherwise).is_some()
+                    && then.ty() == otherwise.ty();
+                self.value(condition, true);
+                self.value(then, unbox_operands);
+                self.value(otherwise, unbox_operands);
+                if let Some(ty) = conditional_cast(then, otherwise) {
+                    let value = std::mem::replace(then.as_mut(), StackValue::This);
+                    **then = StackValue::Cast {
+                        ty,
+                        value: Box::new(value),
+                    };
+                }
+            }
+            StackValue::Switch(switch) => {
+                self.value(&mut switch.value, true);
+                for case in &mut switch.cases {
+                    self.statements(&mut case.body);
+                }
+            }
+            _ => {
+                for operand in value.operands_mut() {
+                    self.value(operand, true);
+                }
+            }
+        }
+
+        if unbox {
+            if let Some(inner) = boxed(value).or_else(|| unboxed(value)).cloned() {
+                *value = inner;
+            }
+        }
+    }
+
+    fn class(&self, class: &ClassPath) -> Option<&IndexedClass> {
+        if *class == self.own.0 {
+            return Some(&self.own.1);
+        }
+        self.index.classes.get(class)
+    }
+
+    /// Methods called `name` that `class` declares or inherits; `None` if
+    /// some of its supertypes aren't known.
+    fn methods(&self, class: &ClassPath, name: &str) -> Option<Vec<&IndexedMethod>> {
+        let mut result: Vec<&IndexedMethod> = Vec::new();
+        let mut visited = HashSet::new();
+        let mut remaining = vec![class];
+        while let Some(class) = remaining.pop() {
+            // `Object` methods aren't overloaded by arguments that are boxed
+            if class.is_object() || !visited.insert(class) {
+                continue;
+            }
+            let indexed = self.class(class)?;
+            for method in indexed.methods.iter().filter(|it| it.name == name) {
+                // overridden methods are the same overload
+                if result.iter().all(|it| it.descriptor != method.descriptor) {
+                    result.push(method);
+                }
+            }
+            remaining.extend(&indexed.supertypes);
+        }
+        Some(result)
+    }
+
+    /// Returns `true` if no other method of the same name and arity could
+    /// be called in place of `method`.
+    fn has_single_overload(&self, method: &MethodRef) -> bool {
+        if method.is_constructor() {
+            return self.has_single_constructor(&method.class, method.descriptor.arguments.len());
+        }
+        self.methods(&method.class, &method.name)
+            .is_some_and(|methods| {
+                !methods.iter().any(|it| {
+                    it.descriptor != method.descriptor
+                        && it.descriptor.arguments.len() == method.descriptor.arguments.len()
+                })
+            })
+    }
+
+    /// Returns `true` if `class` declares a single constructor taking `arity`
+    /// arguments.
+    fn has_single_constructor(&self, class: &ClassPath, arity: usize) -> bool {
+        self.class(class).is_some_and(|class| {
+            class
+                .methods
+                .iter()
+                .filter(|it| it.name == "<init>" && it.descriptor.arguments.len() == arity)
+                .count()
+                == 1
+        })
+    }
+
+    /// Returns `true` if `method` is a variable arity method that isn't
+    /// overloaded, so its arguments can't be mistaken for another overload.
+    fn is_varargs(&self, method: &MethodRef) -> bool {
+        if method.is_constructor() {
+            return false;
+        }
+        match self.methods(&method.class, &method.name).as_deref() {
+            Some([it]) => it.varargs && it.descriptor == method.descriptor,
+            _ => false,
+        }
+    }
+}
+
+/// Primitive wrapped by `ty`, if it's a wrapper class.
+fn wrapped_primitive(ty: &JVMType) -> Option<JVMPrimitive> {
+    let JVMType::TClass(class) = ty else {
+        return None;
+    };
+    [
+        JVMPrimitive::TBoolean,
+        JVMPrimitive::TByte,
+        JVMPrimitive::TChar,
+        JVMPrimitive::TShort,
+        JVMPrimitive::TInt,
+        JVMPrimitive::TLong,
+        JVMPrimitive::TFloat,
+        JVMPrimitive::TDouble,
+    ]
+    .into_iter()
+    .find(|it| it.class_path() == *class)
+}
+
+/// Type the first operand of a conditional expression with operands of
+/// different wrapper types is cast to.
+///
+/// Without it, `c ? Integer.valueOf(1) : Double.valueOf(2.0)` would be
+/// unboxed and promoted to `double`, as both operands are convertible to
+/// numeric types.
+fn conditional_cast(then: &StackValue, otherwise: &StackValue) -> Option<JVMType> {
+    let numeric = |value: &StackValue| {
+        wrapped_primitive(&value.ty()).filter(|it| *it != JVMPrimitive::TBoolean)
+    };
+    let (then, otherwise) = (numeric(then)?, numeric(otherwise)?);
+    if then == otherwise {
+        return None;
+    }
+    let common = if then == JVMPrimitive::TChar || otherwise == JVMPrimitive::TChar {
+        "Object"
+    } else {
+        "Number"
+    };
+    Some(JVMType::TClass(ClassPath::java_lang_class(common)))
+}
+
+/// Boxed primitive of `Integer.valueOf(x)` and other wrappers.
+fn boxed(value: &StackValue) -> Option<&StackValue> {
+    let StackValue::Invoke {
+        kind: InvokeKind::Static,
+        method,
+        args,
+        ..
+    } = value
+    else {
+        return None;
+    };
+    let primitive = wrapped_primitive(&method.descriptor.value)?;
+    match args.as_slice() {
+        [arg]
+            if method.name == "valueOf"
+                && method.class == primitive.class_path()
+                && method.descriptor.arguments == [JVMType::TPrimitive(primitive)] =>
+        {
+            Some(arg)
+        }
+        _ => None,
+    }
+}
+
+/// Unboxed wrapper of `x.intValue()` and other wrappers.
+fn unboxed(value: &StackValue) -> Option<&StackValue> {
+    let StackValue::Invoke {
+        kind: InvokeKind::Virtual,
+        method,
+        target: Some(target),
+        args,
+    } = value
+    else {
+        return None;
+    };
+    let JVMType::TPrimitive(primitive) = method.descriptor.value else {
+        return None;
+    };
+    let name = match primitive {
+        JVMPrimitive::TBoolean => "booleanValue",
+        JVMPrimitive::TByte => "byteValue",
+        JVMPrimitive::TChar => "charValue",
+        JVMPrimitive::TShort => "shortValue",
+        JVMPrimitive::TInt => "intValue",
+        JVMPrimitive::TLong => "longValue",
+        JVMPrimitive::TFloat => "floatValue",
+        JVMPrimitive::TDouble => "doubleValue",
+        JVMPrimitive::TVoid => return None,
+    };
+    (args.is_empty() && method.name == name && method.class == primitive.class_path())
+        .then_some(target.as_ref())
+}
+
+/// Replaces the array passed as the last argument of a variable arity method
+/// with its elements.
+fn collapse_varargs(args: &mut Vec<StackValue>) {
+    let Some(StackValue::NewArray { length, values, .. }) = args.last() else {
+        return;
+    };
+    let StackValue::Literal(Literal::Int(length)) = length.as_ref() else {
+        return;
+    };
+    let values = values.clone().unwrap_or_default();
+    if values.len() != *length as usize {
+        return;
+    }
+    // a single `null` or array would be passed as the array itself
+    if let [value] = values.as_slice() {
+        if matches!(value, StackValue::Literal(Literal::Null)) || value.ty().array_depth() > 0 {
+            return;
+        }
+    }
+    args.pop();
+    args.extend(values);
+}
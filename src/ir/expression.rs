@@ -1,21 +1,23 @@
-use jvm_class_format::{attribute::CodeData, Instruction, Op};
+use jvm_class_format::{ClassPath, Instruction, JVMType, Op};
 
 use super::frame::RuntimeFrame;
+use super::value::{FieldRef, StackValue};
+use super::Statement;
 
 pub struct OpSeq<const LENGTH: usize>(pub [Op; LENGTH]);
 
 impl<const L: usize> OpSeq<L> {
-    pub fn test<'code>(&self, buffer: &[&'code Instruction], offset: usize) -> bool {
-        if L > buffer.as_ref()[offset..].len() {
+    pub fn test(&self, buffer: &[&Instruction], offset: usize) -> bool {
+        if L > buffer[offset..].len() {
             return false;
         }
         for (i, op) in self.0.iter().enumerate() {
-            if buffer.as_ref()[offset + i].op() != *op {
+            if buffer[offset + i].op() != *op {
                 return false;
             }
         }
 
-        return true;
+        true
     }
 }
 
@@ -37,15 +39,29 @@ pub trait CheckExpression {
     ) -> Option<(usize, Expression)>;
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Expression {
     EmptyConstructor(EmptyConstructor),
     ReturnStatement(ReturnStatement),
     Super(EmptySuperCall),
     Comment(InstructionComment),
+    /// Value evaluated only for its side effects (e.g. a method call).
+    Value(StackValue),
+    StoreLocal(StoreLocal),
+    PutField(PutField),
+    PutStatic(PutStatic),
+    ArrayStore(Box<ArrayStore>),
+    Throw(ThrowStatement),
+    If(IfStatement),
+    Switch(SwitchStatement),
+    /// Exit from the enclosing `switch`.
+    Break,
+    /// Result of the enclosing `switch` expression.
+    Yield(StackValue),
+    Assert(AssertStatement),
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct InstructionComment(pub Instruction);
 
 impl CheckExpression for InstructionComment {
@@ -57,13 +73,13 @@ impl CheckExpression for InstructionComment {
         unsafe {
             Some((
                 1,
-                Expression::Comment(InstructionComment(**instr.as_ref().get_unchecked(offset))),
+                Expression::Comment(InstructionComment(**instr.get_unchecked(offset))),
             ))
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct EmptyConstructor;
 
 impl CheckExpression for EmptyConstructor {
@@ -72,7 +88,7 @@ impl CheckExpression for EmptyConstructor {
         offset: usize,
         _: &RuntimeFrame<'cp, 'code>,
     ) -> Option<(usize, Expression)> {
-        if buffer.as_ref().len() != 3 {
+        if buffer.len() != 3 {
             return None;
         }
 
@@ -91,8 +107,8 @@ impl CheckExpression for EmptyConstructor {
     }
 }
 
-#[derive(Debug)]
-pub struct ReturnStatement;
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReturnStatement(pub Option<StackValue>);
 
 impl CheckExpression for ReturnStatement {
     fn test<'cp, 'code>(
@@ -109,18 +125,19 @@ impl CheckExpression for ReturnStatement {
             return None;
         }
 
-        Some((1, Expression::ReturnStatement(Self)))
+        Some((1, Expression::ReturnStatement(Self(None))))
     }
 }
 
-#[derive(Debug)]
-pub struct EmptySuperCall;
+/// Argumentless `super()` (or `this()`) constructor call of the given class.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmptySuperCall(pub ClassPath);
 
 impl CheckExpression for EmptySuperCall {
     fn test<'cp, 'code>(
         buffer: &[&'code Instruction],
         offset: usize,
-        _: &RuntimeFrame<'cp, 'code>,
+        ctx: &RuntimeFrame<'cp, 'code>,
     ) -> Option<(usize, Expression)> {
         let result = OpSeq([
             Op::Aload0,        // push this to stack
@@ -132,9 +149,87 @@ impl CheckExpression for EmptySuperCall {
             return None;
         }
 
-        Some((2, Expression::Super(Self)))
+        let args = buffer[offset + 1].args();
+        let method = ctx
+            .method_ref(((args[0] as usize) << 8) | args[1] as usize)
+            .ok()?;
+        if !method.is_constructor() || !method.descriptor.arguments.is_empty() {
+            return None;
+        }
+
+        Some((2, Expression::Super(Self(method.class))))
     }
 }
 
-#[derive(Debug)]
-pub struct LocalDeclaration {}
+#[derive(Debug, Clone, PartialEq)]
+pub struct StoreLocal {
+    pub index: usize,
+    pub ty: JVMType,
+    pub value: StackValue,
+    /// Whether this is the first assignment to the local variable slot.
+    pub declare: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PutField {
+    pub target: Box<StackValue>,
+    pub field: FieldRef,
+    pub value: StackValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PutStatic {
+    pub field: FieldRef,
+    pub value: StackValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArrayStore {
+    pub array: StackValue,
+    pub index: StackValue,
+    pub value: StackValue,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThrowStatement(pub StackValue);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IfStatement {
+    pub condition: StackValue,
+    pub then: Vec<Statement>,
+    pub otherwise: Option<Vec<Statement>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CaseLabel {
+    Int(i32),
+    String(String),
+    /// Enum constant name.
+    Enum(String),
+    Null,
+    /// Type pattern, binding the matched value to a local variable slot.
+    Type {
+        ty: JVMType,
+        binding: usize,
+    },
+    Default,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssertStatement {
+    pub condition: StackValue,
+    pub message: Option<StackValue>,
+}
+
+/// Statements following one or more `case` labels, up to the next label.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchCase {
+    pub labels: Vec<CaseLabel>,
+    pub body: Vec<Statement>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchStatement {
+    pub value: StackValue,
+    pub cases: Vec<SwitchCase>,
+}
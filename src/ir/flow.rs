@@ -0,0 +1,498 @@
+//! Reconstruction of structured statements (`if`, `switch`) from branch
+//! instructions.
+//!
+//! Method code is decompiled as nested ranges of instructions: a forward
+//! conditional branch encloses the skipped instructions in an `if` and a
+//! switch splits the code up to its end into cases. Branches and switches
+//! that leave a value on the operand stack are conditional and `switch`
+//! expressions. Branches that don't fit this shape (e.g. loops) are left as
+//! assembly comments.
+
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::time::Instant;
+
+use jvm_class_format::{op::SwitchTable, Instruction, Op};
+
+use crate::test_many_expr;
+
+use super::expression::*;
+use super::frame::RuntimeFrame;
+use super::value::StackValue;
+use super::Statement;
+
+/// Relative jump offset of a branch instruction.
+fn jump_offset(instruction: &Instruction) -> Option<i64> {
+    let args = instruction.args();
+    match instruction.op() {
+        Op::GotoW => Some(i32::from_be_bytes([args[0], args[1], args[2], args[3]]) as i64),
+        op if op == Op::Goto || is_conditional(op) => {
+            Some(i16::from_be_bytes([args[0], args[1]]) as i64)
+        }
+        _ => None,
+    }
+}
+
+fn is_conditional(op: Op) -> bool {
+    matches!(
+        op,
+        Op::Ifeq
+            | Op::Ifne
+            | Op::Iflt
+            | Op::Ifge
+            | Op::Ifgt
+            | Op::Ifle
+            | Op::Ifnull
+            | Op::Ifnonnull
+            | Op::IfIcmpeq
+            | Op::IfIcmpne
+            | Op::IfIcmplt
+            | Op::IfIcmpge
+            | Op::IfIcmpgt
+            | Op::IfIcmple
+            | Op::IfAcmpeq
+            | Op::IfAcmpne
+    )
+}
+
+fn is_goto(op: Op) -> bool {
+    matches!(op, Op::Goto | Op::GotoW)
+}
+
+/// Decoded method code.
+pub struct MethodCode<'code> {
+    code: &'code [u8],
+    pub instructions: Vec<&'code Instruction>,
+    offsets: Vec<usize>,
+    /// Time after which decompilation is abandoned.
+    deadline: Option<Instant>,
+    timed_out: Cell<bool>,
+    /// Offsets of instructions that couldn't be evaluated, including in
+    /// blocks that were tried and dropped.
+    failures: RefCell<Vec<usize>>,
+}
+
+/// Statements of a range of instructions.
+pub struct Block {
+    pub statements: Vec<Statement>,
+    /// Single value left on the operand stack at the end of the range (e.g.
+    /// by a branch of a conditional expression) and the offset of its first
+    /// instruction.
+    pub value: Option<(usize, StackValue)>,
+    /// Values were left on the operand stack that aren't part of a
+    /// structured expression.
+    pub dangling: bool,
+}
+
+impl Block {
+    /// Returns `true` if the range only computes a value.
+    fn is_value(&self) -> bool {
+        self.value.is_some() && self.statements.is_empty() && !self.dangling
+    }
+
+    /// Returns `true` if the range only contains statements.
+    fn is_statement(&self) -> bool {
+        self.value.is_none() && !self.dangling
+    }
+}
+
+/// Returns `true` if `statements` yield a `switch` expression value.
+fn yields(statements: &[Statement]) -> bool {
+    statements.iter().any(|it| match &it.expression {
+        Expression::Yield(_) => true,
+        Expression::If(it) => yields(&it.then) || it.otherwise.as_deref().is_some_and(yields),
+        _ => false,
+    })
+}
+
+impl<'code> MethodCode<'code> {
+    pub fn new(code: &'code [u8]) -> Self {
+        let (offsets, instructions) = Instruction::collect_with_offsets(code).into_iter().unzip();
+        MethodCode {
+            code,
+            instructions,
+            offsets,
+            deadline: None,
+            timed_out: Cell::new(false),
+            failures: RefCell::default(),
+        }
+    }
+
+    /// Stops decompiling blocks once `deadline` passes.
+    pub fn with_deadline(mut self, deadline: Option<Instant>) -> Self {
+        self.deadline = deadline;
+        self
+    }
+
+    /// Returns `true` if the deadline passed before all blocks were
+    /// decompiled; statements are incomplete then.
+    pub fn timed_out(&self) -> bool {
+        self.timed_out.get()
+    }
+
+    fn past_deadline(&self) -> bool {
+        if !self.timed_out.get() && self.deadline.is_some_and(|it| Instant::now() >= it) {
+            self.timed_out.set(true);
+        }
+        self.timed_out.get()
+    }
+
+    /// First instruction at or after `offset` that couldn't be evaluated.
+    pub fn failure_from(&self, offset: usize) -> Option<(usize, Op)> {
+        let failure = self
+            .failures
+            .borrow()
+            .iter()
+            .copied()
+            .filter(|it| *it >= offset)
+            .min()?;
+        let index = self.offsets.binary_search(&failure).ok()?;
+        Some((failure, self.instructions[index].op()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.instructions.is_empty()
+    }
+
+    /// Instruction index at bytecode `offset`; end of code maps to the
+    /// instruction count.
+    fn index_of(&self, offset: i64) -> Option<usize> {
+        if offset == self.code.len() as i64 {
+            return Some(self.instructions.len());
+        }
+        let offset = usize::try_from(offset).ok()?;
+        self.offsets.binary_search(&offset).ok()
+    }
+
+    fn offset_of(&self, index: usize) -> usize {
+        self.offsets.get(index).copied().unwrap_or(self.code.len())
+    }
+
+    /// Index of the instruction the branch at `index` jumps to.
+    pub(super) fn jump_target(&self, index: usize) -> Option<usize> {
+        let offset = jump_offset(self.instructions[index])?;
+        self.index_of(self.offsets[index] as i64 + offset)
+    }
+
+    fn is_goto_at(&self, index: usize) -> bool {
+        self.instructions
+            .get(index)
+            .is_some_and(|it| is_goto(it.op()))
+    }
+
+    /// Decompiles instructions in `start..end`.
+    ///
+    /// `exit` is the index jumps to which leave the innermost `switch`.
+    /// Values on the operand stack at `start` are left untouched.
+    pub fn block(
+        &self,
+        frame: &mut RuntimeFrame,
+        start: usize,
+        end: usize,
+        exit: Option<usize>,
+    ) -> Block {
+        let base = frame.stack.len();
+        let mut result = Vec::new();
+        // instructions that contributed to values still on the stack
+        let mut pending: Vec<(usize, &Instruction)> = Vec::new();
+
+        let mut index = start;
+        while index < end {
+            if self.past_deadline() {
+                break;
+            }
+            if frame.stack.len() == base {
+                #[rustfmt::skip]
+                let matched = test_many_expr!(&[
+                    EmptySuperCall
+                ], self.instructions.as_slice(), index, &*frame);
+
+                if let Some((instruction_count, expr)) = matched {
+                    result.push(Statement::new(self.offsets[index], expr));
+                    index += instruction_count;
+                    continue;
+                }
+            }
+
+            if let Some((count, value)) = self.class_literal(frame, index) {
+                pending.extend(
+                    (index..index + count).map(|it| (self.offsets[it], self.instructions[it])),
+                );
+                frame.push_to_stack(value);
+                index += count;
+                continue;
+            }
+
+            let instruction = self.instructions[index];
+            let op = instruction.op();
+            let start_offset = pending
+                .first()
+                .map(|it| it.0)
+                .unwrap_or(self.offsets[index]);
+
+            if is_conditional(op) || is_goto(op) || matches!(op, Op::Tableswitch | Op::Lookupswitch)
+            {
+                let structured = match op {
+                    Op::Tableswitch | Op::Lookupswitch => self.switch(frame, index, end, base),
+                    _ if is_goto(op) => self.jump(frame, index, exit, base),
+                    _ => self.branch(frame, index, end, exit, base),
+                };
+                match structured {
+                    Some((next, Some(expression))) => {
+                        pending.clear();
+                        result.push(Statement::new(start_offset, expression));
+                        index = next;
+                        continue;
+                    }
+                    // conditional or `switch` expression pushed onto the stack
+                    Some((next, None)) => {
+                        pending.extend(
+                            (index..next).map(|it| (self.offsets[it], self.instructions[it])),
+                        );
+                        index = next;
+                        continue;
+                    }
+                    None => {}
+                }
+            }
+
+            pending.push((self.offsets[index], instruction));
+            index += 1;
+
+            match frame.execute(instruction) {
+                Ok(expr) => {
+                    let start = pending[0].0;
+                    result.extend(expr.map(|it| Statement::new(start, it)));
+                    if frame.stack.len() == base {
+                        pending.clear();
+                    }
+                }
+                Err(err) => {
+                    tracing::debug!("unable to evaluate '{}': {}", instruction.op(), err);
+                    self.failures.borrow_mut().push(self.offsets[index - 1]);
+                    // fall back to assembly for the whole unfinished expression
+                    frame.stack.truncate(base);
+                    result.extend(pending.drain(..).map(|(offset, it)| {
+                        Statement::new(offset, Expression::Comment(InstructionComment(*it)))
+                    }));
+                }
+            }
+        }
+
+        let value = match frame.stack.len() == base + 1 {
+            true => frame.stack.pop().map(|it| {
+                let offset = pending
+                    .first()
+                    .map(|it| it.0)
+                    .unwrap_or(self.offset_of(end));
+                (offset, it)
+            }),
+            false => None,
+        };
+        let dangling = frame.stack.len() != base;
+        frame.stack.truncate(base);
+        if value.is_none() {
+            result.extend(pending.drain(..).map(|(offset, it)| {
+                Statement::new(offset, Expression::Comment(InstructionComment(*it)))
+            }));
+        }
+
+        Block {
+            statements: result,
+            value,
+            dangling,
+        }
+    }
+
+    /// Unconditional jump out of the enclosing `switch`, which yields its
+    /// value if one is left above `base` of the stack.
+    fn jump(
+        &self,
+        frame: &mut RuntimeFrame,
+        index: usize,
+        exit: Option<usize>,
+        base: usize,
+    ) -> Option<(usize, Option<Expression>)> {
+        if exit.is_none() || self.jump_target(index) != exit {
+            return None;
+        }
+        if frame.stack.len() == base {
+            Some((index + 1, Some(Expression::Break)))
+        } else if frame.stack.len() == base + 1 {
+            let value = frame.pop().ok()?;
+            Some((index + 1, Some(Expression::Yield(value))))
+        } else {
+            None
+        }
+    }
+
+    /// Forward conditional branch enclosing an `if` statement, or a
+    /// conditional expression if both branches only push a value.
+    ///
+    /// Statements require the stack to be at `base` of the enclosing block.
+    fn branch(
+        &self,
+        frame: &mut RuntimeFrame,
+        index: usize,
+        end: usize,
+        exit: Option<usize>,
+        base: usize,
+    ) -> Option<(usize, Option<Expression>)> {
+        let target = self.jump_target(index)?;
+        let mut inner = frame.clone();
+        let taken = inner.branch_condition(self.instructions[index]).ok()?;
+
+        if exit == Some(target) {
+            if inner.stack.len() != base {
+                return None;
+            }
+            *frame = inner;
+            return Some((
+                index + 1,
+                Some(Expression::If(IfStatement {
+                    condition: taken,
+                    then: vec![Statement::new(self.offsets[index], Expression::Break)],
+                    otherwise: None,
+                })),
+            ));
+        }
+        if target <= index || target > end {
+            return None;
+        }
+
+        // `then` branch ends with a jump over the `else` branch
+        let else_end = Some(target - 1)
+            .filter(|it| *it > index && self.is_goto_at(*it))
+            .and_then(|it| self.jump_target(it))
+            .filter(|after| Some(*after) != exit && *after > target && *after <= end);
+        let then_end = if else_end.is_some() {
+            target - 1
+        } else {
+            target
+        };
+
+        let then = self.block(&mut inner.clone(), index + 1, then_end, exit);
+        let otherwise = else_end.map(|after| self.block(&mut inner.clone(), target, after, exit));
+
+        if let (Some(after), Some(otherwise)) = (else_end, &otherwise) {
+            if then.is_value() && otherwise.is_value() {
+                let (_, then) = then.value.expect("value branch");
+                let (_, otherwise) = otherwise.value.clone().expect("value branch");
+                inner.push_to_stack(StackValue::Conditional {
+                    condition: Box::new(taken.negate_condition()),
+                    then: Box::new(then),
+                    otherwise: Box::new(otherwise),
+                });
+                *frame = inner;
+                return Some((after, None));
+            }
+        }
+
+        if inner.stack.len() != base
+            || !then.is_statement()
+            || otherwise.as_ref().is_some_and(|it| !it.is_statement())
+        {
+            return None;
+        }
+
+        *frame = inner;
+        Some((
+            else_end.unwrap_or(target),
+            Some(Expression::If(IfStatement {
+                condition: taken.negate_condition(),
+                then: then.statements,
+                otherwise: otherwise.map(|it| it.statements),
+            })),
+        ))
+    }
+
+    /// `tableswitch` or `lookupswitch` along with its cases; a `switch`
+    /// expression if cases yield a value.
+    fn switch(
+        &self,
+        frame: &mut RuntimeFrame,
+        index: usize,
+        end: usize,
+        base: usize,
+    ) -> Option<(usize, Option<Expression>)> {
+        let mut inner = frame.clone();
+        let value = inner.pop().ok()?;
+
+        let table = SwitchTable::read(self.code, self.offsets[index])?;
+        let offset = self.offsets[index] as i64;
+        let target = |relative: i32| {
+            self.index_of(offset + relative as i64)
+                .filter(|it| *it > index && *it <= end)
+        };
+
+        let default = target(table.default)?;
+        let mut labels: BTreeMap<usize, Vec<CaseLabel>> = BTreeMap::new();
+        for (value, relative) in table.cases {
+            labels
+                .entry(target(relative)?)
+                .or_default()
+                .push(CaseLabel::Int(value));
+        }
+        let first = *labels.keys().next().unwrap_or(&default);
+        let last = labels
+            .keys()
+            .last()
+            .copied()
+            .unwrap_or(default)
+            .max(default);
+
+        // `break` jumps past the last case
+        let switch_end = (first..last)
+            .filter(|it| self.is_goto_at(*it))
+            .filter_map(|it| self.jump_target(it))
+            .filter(|it| *it > last && *it <= end)
+            .fold(last, usize::max);
+        if default != switch_end {
+            labels.entry(default).or_default().push(CaseLabel::Default);
+        }
+
+        let starts: Vec<usize> = labels.keys().copied().collect();
+        let mut cases = Vec::with_capacity(starts.len());
+        let mut expression = false;
+        // cases share a scope
+        let mut case_frame = inner.clone();
+        for (i, (start, labels)) in labels.into_iter().enumerate() {
+            let case_end = starts.get(i + 1).copied().unwrap_or(switch_end);
+            let mut body = self.block(&mut case_frame, start, case_end, Some(switch_end));
+            if body.dangling {
+                return None;
+            }
+            // value of the last case is yielded by falling through to the end
+            if let Some((offset, value)) = body.value.take() {
+                body.statements
+                    .push(Statement::new(offset, Expression::Yield(value)));
+            }
+            expression |= yields(&body.statements);
+            cases.push(SwitchCase {
+                labels,
+                body: body.statements,
+            });
+        }
+
+        tracing::trace!(
+            "switch at {} ends at {}",
+            self.offsets[index],
+            self.offset_of(switch_end)
+        );
+        let switch = SwitchStatement { value, cases };
+        if expression {
+            inner.push_to_stack(StackValue::Switch(Box::new(switch)));
+            *frame = inner;
+            return Some((switch_end, None));
+        }
+        if inner.stack.len() != base {
+            return None;
+        }
+        *frame = inner;
+        Some((switch_end, Some(Expression::Switch(switch))))
+    }
+}
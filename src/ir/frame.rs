@@ -1,20 +1,27 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use jvm_class_format::{
-    attribute::{AttributeValue, ExceptionTableEntry, CodeData},
-    ConstantPool,
+    attribute::{AttributeValue, BootstrapMethod, CodeData, ExceptionTableEntry},
+    ClassPath, Constant, ConstantPool, Descriptor, Instruction, JVMPrimitive, JVMType, Member,
+    MethodAccessFlags, Op,
 };
 
+use crate::error::InstructionError;
+
+use super::expression::{
+    ArrayStore, Expression, PutField, PutStatic, ReturnStatement, StoreLocal, ThrowStatement,
+};
+use super::value::*;
+
 pub struct RuntimeBase {
     pub constant_pool: ConstantPool,
 }
 
-#[derive(Debug, Clone, Copy)]
-pub enum StackValue {}
-
 #[derive(Debug, Clone)]
 pub struct RuntimeFrame<'cp, 'code> {
     pub constant_pool: &'cp ConstantPool,
+    /// Bootstrap methods of `invokedynamic` call sites declared by the class.
+    pub bootstrap_methods: &'cp [BootstrapMethod],
 
     pub exception_table: &'code [ExceptionTableEntry],
     pub attributes: &'code HashMap<String, AttributeValue>,
@@ -23,12 +30,63 @@ pub struct RuntimeFrame<'cp, 'code> {
     pub stack: Vec<StackValue>,
 
     pub max_locals: usize,
+    /// Local variable slots that were assigned a value, including arguments.
+    pub assigned: HashSet<usize>,
+    /// Types of values last stored into local variable slots, which are more
+    /// precise than types implied by load instructions.
+    pub local_types: HashMap<usize, JVMType>,
+
+    pub is_static: bool,
+    pub return_type: JVMType,
+}
+
+fn u16_arg(instruction: &Instruction) -> usize {
+    let args = instruction.args();
+    ((args[0] as usize) << 8) | args[1] as usize
+}
+
+/// Bootstrap methods whose call sites are reconstructed as source constructs.
+const SUPPORTED_BOOTSTRAPS: &[(&str, &str)] = &[
+    ("java/lang/runtime/SwitchBootstraps", "typeSwitch"),
+    ("java/lang/runtime/SwitchBootstraps", "enumSwitch"),
+];
+
+/// Returns `true` for values that can be evaluated again without side
+/// effects or a different result.
+fn is_duplicable(value: &StackValue) -> bool {
+    matches!(
+        value,
+        StackValue::Literal(_) | StackValue::This | StackValue::Local { .. }
+    )
+}
+
+fn object_type() -> JVMType {
+    JVMType::TClass(ClassPath::default())
 }
 
 impl<'cp, 'code> RuntimeFrame<'cp, 'code> {
-    pub fn new(base: &'cp ConstantPool, code: &'code CodeData) -> Self {
+    pub fn new(base: &'cp ConstantPool, method: &Member, code: &'code CodeData) -> Self {
+        let is_static = method.method_flags().contains(MethodAccessFlags::STATIC);
+
+        let mut assigned = HashSet::with_capacity(code.max_locals);
+        let mut local_types = HashMap::with_capacity(code.max_locals);
+        let mut slot = 0;
+        if !is_static {
+            assigned.insert(slot);
+            slot += 1;
+        }
+        for arg in &method.descriptor.arguments {
+            assigned.insert(slot);
+            local_types.insert(slot, arg.clone());
+            slot += match arg {
+                JVMType::TPrimitive(JVMPrimitive::TLong | JVMPrimitive::TDouble) => 2,
+                _ => 1,
+            };
+        }
+
         RuntimeFrame {
             constant_pool: base,
+            bootstrap_methods: &[],
 
             exception_table: &code.exception_table,
             attributes: &code.attributes,
@@ -37,12 +95,18 @@ impl<'cp, 'code> RuntimeFrame<'cp, 'code> {
             stack: Vec::with_capacity(code.max_stack),
 
             max_locals: code.max_locals,
+            assigned,
+            local_types,
+
+            is_static,
+            return_type: method.descriptor.value.clone(),
         }
     }
 
     pub fn new_inner(&self) -> Self {
         RuntimeFrame {
             constant_pool: self.constant_pool,
+            bootstrap_methods: self.bootstrap_methods,
 
             exception_table: self.exception_table,
             attributes: self.attributes,
@@ -51,9 +115,19 @@ impl<'cp, 'code> RuntimeFrame<'cp, 'code> {
             stack: self.stack.clone(),
 
             max_locals: self.max_locals,
+            assigned: self.assigned.clone(),
+            local_types: self.local_types.clone(),
+
+            is_static: self.is_static,
+            return_type: self.return_type.clone(),
         }
     }
 
+    pub fn with_bootstrap_methods(mut self, bootstrap_methods: &'cp [BootstrapMethod]) -> Self {
+        self.bootstrap_methods = bootstrap_methods;
+        self
+    }
+
     pub fn push_to_stack(&mut self, value: StackValue) {
         if self.stack_size < self.stack.len() {
             tracing::warn!("exceeded stack limit!");
@@ -61,4 +135,626 @@ impl<'cp, 'code> RuntimeFrame<'cp, 'code> {
 
         self.stack.push(value);
     }
+
+    pub fn pop(&mut self) -> Result<StackValue, InstructionError> {
+        self.stack.pop().ok_or(InstructionError::StackUnderflow)
+    }
+
+    fn pop_many(&mut self, count: usize) -> Result<Vec<StackValue>, InstructionError> {
+        if self.stack.len() < count {
+            return Err(InstructionError::StackUnderflow);
+        }
+        Ok(self.stack.split_off(self.stack.len() - count))
+    }
+
+    /// Type named by a class constant, which is an array type for names like
+    /// `[Ljava/lang/String;`.
+    fn class_ref(&self, index: usize) -> Result<JVMType, InstructionError> {
+        match self.constant_pool.try_get(index)? {
+            Constant::Class { name_index } => {
+                class_constant_type(self.constant_pool.get_utf8(*name_index as usize)?)
+            }
+            other => Err(InstructionError::UnsupportedConstant(other.tag())),
+        }
+    }
+
+    fn member_ref(&self, index: usize) -> Result<(ClassPath, &str, &str), InstructionError> {
+        let (class_index, nat_index) = match self.constant_pool.try_get(index)? {
+            Constant::Fieldref {
+                class_index,
+                name_and_type_info,
+            }
+            | Constant::Methodref {
+                class_index,
+                name_and_type_info,
+            }
+            | Constant::InterfaceMethodref {
+                class_index,
+                name_and_type_info,
+            } => (*class_index as usize, *name_and_type_info as usize),
+            other => return Err(InstructionError::UnsupportedConstant(other.tag())),
+        };
+
+        let class = match self.class_ref(class_index)? {
+            JVMType::TClass(class) => class,
+            // arrays only have members of `Object` (e.g. `values.clone()`)
+            _ => ClassPath::java_lang_class("Object"),
+        };
+        let (name, descriptor) = self.constant_pool.get_name_and_type(nat_index)?;
+        Ok((class, name, descriptor))
+    }
+
+    pub fn field_ref(&self, index: usize) -> Result<FieldRef, InstructionError> {
+        let (class, name, descriptor) = self.member_ref(index)?;
+        Ok(FieldRef {
+            class,
+            name: name.to_string(),
+            ty: descriptor.parse()?,
+        })
+    }
+
+    pub fn method_ref(&self, index: usize) -> Result<MethodRef, InstructionError> {
+        let (class, name, descriptor) = self.member_ref(index)?;
+        Ok(MethodRef {
+            class,
+            name: name.to_string(),
+            descriptor: descriptor.parse::<Descriptor>()?,
+        })
+    }
+
+    fn load(&mut self, index: usize, ty: JVMType) {
+        let known = self.local_types.get(&index).filter(|known| match &ty {
+            JVMType::TPrimitive(JVMPrimitive::TInt) => matches!(
+                known,
+                JVMType::TPrimitive(
+                    JVMPrimitive::TBoolean
+                        | JVMPrimitive::TByte
+                        | JVMPrimitive::TChar
+                        | JVMPrimitive::TShort
+                        | JVMPrimitive::TInt
+                )
+            ),
+            JVMType::TClass(_) => !matches!(known, JVMType::TPrimitive(_)),
+            _ => false,
+        });
+        let ty = known.cloned().unwrap_or(ty);
+
+        let value = if index == 0 && !self.is_static {
+            StackValue::This
+        } else {
+            StackValue::Local { index, ty }
+        };
+        self.push_to_stack(value);
+    }
+
+    fn store(&mut self, index: usize, ty: JVMType) -> Result<Expression, InstructionError> {
+        let value = self.pop()?;
+        let ty = match ty {
+            JVMType::TPrimitive(JVMPrimitive::TInt) | JVMType::TClass(_) => value.ty(),
+            other => other,
+        };
+        let declare = self.assigned.insert(index);
+        self.local_types.insert(index, ty.clone());
+
+        Ok(Expression::StoreLocal(StoreLocal {
+            index,
+            value: value.coerce(&ty),
+            ty,
+            declare,
+        }))
+    }
+
+    /// Stores popped `value` into an unused local variable slot if evaluating
+    /// it more than once could give a different result, and returns the store
+    /// along with the value to use in its place.
+    ///
+    /// Values left on the stack were evaluated before `value`, so it's only
+    /// stored ahead of them if they can't have side effects.
+    fn spill(
+        &mut self,
+        value: StackValue,
+        op: Op,
+    ) -> Result<(Option<Expression>, StackValue), InstructionError> {
+        if is_duplicable(&value) {
+            return Ok((None, value));
+        }
+        if !self.stack.iter().all(is_duplicable) {
+            return Err(InstructionError::Unsupported(op));
+        }
+        let index = (self.max_locals..)
+            .find(|it| !self.assigned.contains(it))
+            .expect("unbounded range");
+        let ty = value.ty();
+        self.assigned.insert(index);
+        self.local_types.insert(index, ty.clone());
+        let store = Expression::StoreLocal(StoreLocal {
+            index,
+            ty: ty.clone(),
+            value,
+            declare: true,
+        });
+        Ok((Some(store), StackValue::Local { index, ty }))
+    }
+
+    fn binary(&mut self, op: BinaryOp) -> Result<(), InstructionError> {
+        let right = self.pop()?;
+        let left = self.pop()?;
+        self.push_to_stack(StackValue::binary(op, left, right));
+        Ok(())
+    }
+
+    fn convert(&mut self, into: JVMPrimitive) -> Result<(), InstructionError> {
+        let value = self.pop()?;
+        self.push_to_stack(StackValue::convert(value, into));
+        Ok(())
+    }
+
+    fn array_store(&mut self) -> Result<Option<Expression>, InstructionError> {
+        let value = self.pop()?;
+        let index = self.pop()?;
+        let array = self.pop()?;
+
+        // `new T[] {a, b}` stores elements in order into a duplicate of the
+        // array, which is left on the stack
+        if self.stack.last() == Some(&array) {
+            if let (
+                Some(StackValue::NewArray { ty, length, values }),
+                StackValue::Literal(Literal::Int(index)),
+            ) = (self.stack.last_mut(), &index)
+            {
+                let stored = values.as_ref().map_or(0, Vec::len);
+                let fits = matches!(
+                    length.as_ref(),
+                    StackValue::Literal(Literal::Int(length)) if index < length
+                );
+                if fits && *index as usize == stored {
+                    values.get_or_insert_with(Vec::new).push(value.coerce(ty));
+                    return Ok(None);
+                }
+            }
+        }
+
+        let value = match array.ty().element_type() {
+            Some(ty) => value.coerce(&ty),
+            None => value,
+        };
+        Ok(Some(Expression::ArrayStore(Box::new(ArrayStore {
+            array,
+            index,
+            value,
+        }))))
+    }
+
+    fn invoke_dynamic(&mut self, index: usize) -> Result<(), InstructionError> {
+        let (bootstrap_index, nat_index) = match self.constant_pool.try_get(index)? {
+            Constant::InvokeDynamic {
+                bootstrap_method_attr_index,
+                name_and_type_index,
+            } => (
+                *bootstrap_method_attr_index as usize,
+                *name_and_type_index as usize,
+            ),
+            other => return Err(InstructionError::UnsupportedConstant(other.tag())),
+        };
+        let (name, descriptor) = self.constant_pool.get_name_and_type(nat_index)?;
+        let descriptor: Descriptor = descriptor.parse()?;
+
+        let bootstrap_method = self
+            .bootstrap_methods
+            .get(bootstrap_index)
+            .ok_or(InstructionError::MissingBootstrapMethod(bootstrap_index))?;
+        let bootstrap = match self
+            .constant_pool
+            .try_get(bootstrap_method.method_ref as usize)?
+        {
+            Constant::MethodHandle {
+                reference_index, ..
+            } => self.method_ref(*reference_index as usize)?,
+            other => return Err(InstructionError::UnsupportedConstant(other.tag())),
+        };
+        let supported = SUPPORTED_BOOTSTRAPS.iter().any(|(class, name)| {
+            bootstrap.class.internal_name() == *class && bootstrap.name == *name
+        });
+        if !supported {
+            return Err(InstructionError::Unsupported(Op::Invokedynamic));
+        }
+
+        let static_args = bootstrap_method
+            .arguments
+            .iter()
+            .map(|it| Literal::from_constant(self.constant_pool, *it as usize))
+            .collect::<Result<_, _>>()?;
+        let args = self.pop_many(descriptor.arguments.len())?;
+        self.push_to_stack(StackValue::InvokeDynamic {
+            bootstrap,
+            name: name.to_string(),
+            descriptor,
+            static_args,
+            args,
+        });
+        Ok(())
+    }
+
+    fn invoke(
+        &mut self,
+        kind: InvokeKind,
+        index: usize,
+    ) -> Result<Option<Expression>, InstructionError> {
+        let method = self.method_ref(index)?;
+        let args = self
+            .pop_many(method.descriptor.arguments.len())?
+            .into_iter()
+            .zip(method.descriptor.arguments.iter())
+            .map(|(value, ty)| value.coerce(ty))
+            .collect::<Vec<_>>();
+
+        let target = match kind {
+            InvokeKind::Static => None,
+            _ => Some(self.pop()?),
+        };
+
+        if let (true, Some(StackValue::Uninitialized(class))) = (method.is_constructor(), &target) {
+            let value = StackValue::New {
+                class: class.clone(),
+                args,
+            };
+            // result of `new` is usually duplicated before calling the constructor
+            return match self.stack.last_mut() {
+                Some(top) if *top == StackValue::Uninitialized(class.clone()) => {
+                    *top = value;
+                    Ok(None)
+                }
+                _ => Ok(Some(Expression::Value(value))),
+            };
+        }
+
+        let returns_value = method.descriptor.value != JVMType::TPrimitive(JVMPrimitive::TVoid);
+        let value = StackValue::Invoke {
+            kind,
+            method,
+            target: target.map(Box::new),
+            args,
+        };
+
+        if returns_value {
+            self.push_to_stack(value);
+            Ok(None)
+        } else {
+            Ok(Some(Expression::Value(value)))
+        }
+    }
+
+    /// Pops operands of a conditional branch and returns the condition under
+    /// which the branch is taken.
+    pub fn branch_condition(
+        &mut self,
+        instruction: &Instruction,
+    ) -> Result<StackValue, InstructionError> {
+        let zero = || StackValue::Literal(Literal::Int(0));
+        let null = || StackValue::Literal(Literal::Null);
+
+        let (op, right) = match instruction.op() {
+            Op::Ifeq => (CompareOp::Eq, zero()),
+            Op::Ifne => (CompareOp::Ne, zero()),
+            Op::Iflt => (CompareOp::Lt, zero()),
+            Op::Ifge => (CompareOp::Ge, zero()),
+            Op::Ifgt => (CompareOp::Gt, zero()),
+            Op::Ifle => (CompareOp::Le, zero()),
+            Op::Ifnull => (CompareOp::Eq, null()),
+            Op::Ifnonnull => (CompareOp::Ne, null()),
+            Op::IfIcmpeq | Op::IfAcmpeq => (CompareOp::Eq, self.pop()?),
+            Op::IfIcmpne | Op::IfAcmpne => (CompareOp::Ne, self.pop()?),
+            Op::IfIcmplt => (CompareOp::Lt, self.pop()?),
+            Op::IfIcmpge => (CompareOp::Ge, self.pop()?),
+            Op::IfIcmpgt => (CompareOp::Gt, self.pop()?),
+            Op::IfIcmple => (CompareOp::Le, self.pop()?),
+            other => return Err(InstructionError::Unsupported(other)),
+        };
+        let left = self.pop()?;
+        Ok(StackValue::compare(op, left, right))
+    }
+
+    /// Symbolically executes a single instruction.
+    ///
+    /// Values are kept on the symbolic operand stack until they are consumed by
+    /// an instruction that produces a statement, in which case that statement
+    /// is returned.
+    pub fn execute(
+        &mut self,
+        instruction: &Instruction,
+    ) -> Result<Option<Expression>, InstructionError> {
+        use JVMPrimitive::*;
+
+        let args = instruction.args();
+        let int = |it: JVMPrimitive| JVMType::TPrimitive(it);
+
+        match instruction.op() {
+            Op::Nop => {}
+
+            Op::AconstNull => self.push_to_stack(StackValue::Literal(Literal::Null)),
+            Op::IconstM1 => self.push_to_stack(StackValue::Literal(Literal::Int(-1))),
+            Op::Iconst0 => self.push_to_stack(StackValue::Literal(Literal::Int(0))),
+            Op::Iconst1 => self.push_to_stack(StackValue::Literal(Literal::Int(1))),
+            Op::Iconst2 => self.push_to_stack(StackValue::Literal(Literal::Int(2))),
+            Op::Iconst3 => self.push_to_stack(StackValue::Literal(Literal::Int(3))),
+            Op::Iconst4 => self.push_to_stack(StackValue::Literal(Literal::Int(4))),
+            Op::Iconst5 => self.push_to_stack(StackValue::Literal(Literal::Int(5))),
+            Op::Lconst0 => self.push_to_stack(StackValue::Literal(Literal::Long(0))),
+            Op::Lconst1 => self.push_to_stack(StackValue::Literal(Literal::Long(1))),
+            Op::Fconst0 => self.push_to_stack(StackValue::Literal(Literal::Float(0.0))),
+            Op::Fconst1 => self.push_to_stack(StackValue::Literal(Literal::Float(1.0))),
+            Op::Fconst2 => self.push_to_stack(StackValue::Literal(Literal::Float(2.0))),
+            Op::Dconst0 => self.push_to_stack(StackValue::Literal(Literal::Double(0.0))),
+            Op::Dconst1 => self.push_to_stack(StackValue::Literal(Literal::Double(1.0))),
+            Op::Bipush => {
+                self.push_to_stack(StackValue::Literal(Literal::Int(args[0] as i8 as i32)))
+            }
+            Op::Sipush => self.push_to_stack(StackValue::Literal(Literal::Int(
+                u16_arg(instruction) as u16 as i16 as i32,
+            ))),
+            Op::Ldc => {
+                let literal = Literal::from_constant(self.constant_pool, args[0] as usize)?;
+                self.push_to_stack(StackValue::Literal(literal))
+            }
+            Op::LdcW | Op::Ldc2W => {
+                let literal = Literal::from_constant(self.constant_pool, u16_arg(instruction))?;
+                self.push_to_stack(StackValue::Literal(literal))
+            }
+
+            Op::Iload => self.load(args[0] as usize, int(TInt)),
+            Op::Lload => self.load(args[0] as usize, int(TLong)),
+            Op::Fload => self.load(args[0] as usize, int(TFloat)),
+            Op::Dload => self.load(args[0] as usize, int(TDouble)),
+            Op::Aload => self.load(args[0] as usize, object_type()),
+            Op::Iload0 => self.load(0, int(TInt)),
+            Op::Iload1 => self.load(1, int(TInt)),
+            Op::Iload2 => self.load(2, int(TInt)),
+            Op::Iload3 => self.load(3, int(TInt)),
+            Op::Lload0 => self.load(0, int(TLong)),
+            Op::Lload1 => self.load(1, int(TLong)),
+            Op::Lload2 => self.load(2, int(TLong)),
+            Op::Lload3 => self.load(3, int(TLong)),
+            Op::Fload0 => self.load(0, int(TFloat)),
+            Op::Fload1 => self.load(1, int(TFloat)),
+            Op::Fload2 => self.load(2, int(TFloat)),
+            Op::Fload3 => self.load(3, int(TFloat)),
+            Op::Dload0 => self.load(0, int(TDouble)),
+            Op::Dload1 => self.load(1, int(TDouble)),
+            Op::Dload2 => self.load(2, int(TDouble)),
+            Op::Dload3 => self.load(3, int(TDouble)),
+            Op::Aload0 => self.load(0, object_type()),
+            Op::Aload1 => self.load(1, object_type()),
+            Op::Aload2 => self.load(2, object_type()),
+            Op::Aload3 => self.load(3, object_type()),
+
+            Op::Istore => return self.store(args[0] as usize, int(TInt)).map(Some),
+            Op::Lstore => return self.store(args[0] as usize, int(TLong)).map(Some),
+            Op::Fstore => return self.store(args[0] as usize, int(TFloat)).map(Some),
+            Op::Dstore => return self.store(args[0] as usize, int(TDouble)).map(Some),
+            Op::Astore => return self.store(args[0] as usize, object_type()).map(Some),
+            Op::Istore0 => return self.store(0, int(TInt)).map(Some),
+            Op::Istore1 => return self.store(1, int(TInt)).map(Some),
+            Op::Istore2 => return self.store(2, int(TInt)).map(Some),
+            Op::Istore3 => return self.store(3, int(TInt)).map(Some),
+            Op::Lstore0 => return self.store(0, int(TLong)).map(Some),
+            Op::Lstore1 => return self.store(1, int(TLong)).map(Some),
+            Op::Lstore2 => return self.store(2, int(TLong)).map(Some),
+            Op::Lstore3 => return self.store(3, int(TLong)).map(Some),
+            Op::Fstore0 => return self.store(0, int(TFloat)).map(Some),
+            Op::Fstore1 => return self.store(1, int(TFloat)).map(Some),
+            Op::Fstore2 => return self.store(2, int(TFloat)).map(Some),
+            Op::Fstore3 => return self.store(3, int(TFloat)).map(Some),
+            Op::Dstore0 => return self.store(0, int(TDouble)).map(Some),
+            Op::Dstore1 => return self.store(1, int(TDouble)).map(Some),
+            Op::Dstore2 => return self.store(2, int(TDouble)).map(Some),
+            Op::Dstore3 => return self.store(3, int(TDouble)).map(Some),
+            Op::Astore0 => return self.store(0, object_type()).map(Some),
+            Op::Astore1 => return self.store(1, object_type()).map(Some),
+            Op::Astore2 => return self.store(2, object_type()).map(Some),
+            Op::Astore3 => return self.store(3, object_type()).map(Some),
+
+            Op::Pop => {
+                let value = self.pop()?;
+                return Ok(Some(Expression::Value(value)));
+            }
+            Op::Pop2 => {
+                let value = self.pop()?;
+                if value.is_wide() {
+                    return Ok(Some(Expression::Value(value)));
+                }
+                // two category 1 values; a single statement can only keep the
+                // side effects of one of them
+                let lower = self.pop()?;
+                return match (is_duplicable(&lower), is_duplicable(&value)) {
+                    (true, _) => Ok(Some(Expression::Value(value))),
+                    (false, true) => Ok(Some(Expression::Value(lower))),
+                    (false, false) => Err(InstructionError::Unsupported(Op::Pop2)),
+                };
+            }
+            Op::Dup => {
+                let value = self.stack.last().ok_or(InstructionError::StackUnderflow)?;
+                match value {
+                    // array elements are stored into a duplicate of a new array
+                    StackValue::NewArray { .. } | StackValue::Uninitialized(_) => {
+                        self.push_to_stack(value.clone())
+                    }
+                    // e.g. `return count++;` in `access$NNN` accessors
+                    _ => {
+                        let value = self.pop()?;
+                        let (store, value) = self.spill(value, Op::Dup)?;
+                        self.push_to_stack(value.clone());
+                        self.push_to_stack(value);
+                        return Ok(store);
+                    }
+                }
+            }
+            // e.g. `return x.count = value;` in `access$NNN` accessors
+            Op::DupX1 | Op::Dup2X1 => {
+                let value = self.pop()?;
+                // the wide form copies a single long or double
+                if (instruction.op() == Op::Dup2X1) != value.is_wide() {
+                    return Err(InstructionError::Unsupported(instruction.op()));
+                }
+                let under = self.pop()?;
+                self.push_to_stack(under);
+                let (store, value) = self.spill(value, instruction.op())?;
+                let under = self.pop()?;
+                self.push_to_stack(value.clone());
+                self.push_to_stack(under);
+                self.push_to_stack(value);
+                return Ok(store);
+            }
+
+            Op::Iaload
+            | Op::Laload
+            | Op::Faload
+            | Op::Daload
+            | Op::Aaload
+            | Op::Baload
+            | Op::Caload
+            | Op::Saload => {
+                let index = self.pop()?;
+                let array = self.pop()?;
+                self.push_to_stack(StackValue::ArrayLoad {
+                    array: Box::new(array),
+                    index: Box::new(index),
+                });
+            }
+
+            Op::Iastore
+            | Op::Lastore
+            | Op::Fastore
+            | Op::Dastore
+            | Op::Aastore
+            | Op::Bastore
+            | Op::Castore
+            | Op::Sastore => return self.array_store(),
+            Op::Newarray => {
+                let ty = match args[0] {
+                    4 => TBoolean,
+                    5 => TChar,
+                    6 => TFloat,
+                    7 => TDouble,
+                    8 => TByte,
+                    9 => TShort,
+                    10 => TInt,
+                    11 => TLong,
+                    _ => return Err(InstructionError::Unsupported(Op::Newarray)),
+                };
+                let length = self.pop()?;
+                self.push_to_stack(StackValue::NewArray {
+                    ty: ty.into(),
+                    length: Box::new(length),
+                    values: None,
+                });
+            }
+            Op::Anewarray => {
+                let ty = self.class_ref(u16_arg(instruction))?;
+                let length = self.pop()?;
+                self.push_to_stack(StackValue::NewArray {
+                    ty,
+                    length: Box::new(length),
+                    values: None,
+                });
+            }
+
+            Op::Iadd | Op::Ladd | Op::Fadd | Op::Dadd => self.binary(BinaryOp::Add)?,
+            Op::Isub | Op::Lsub | Op::Fsub | Op::Dsub => self.binary(BinaryOp::Sub)?,
+            Op::Imul | Op::Lmul | Op::Fmul | Op::Dmul => self.binary(BinaryOp::Mul)?,
+            Op::Idiv | Op::Ldiv | Op::Fdiv | Op::Ddiv => self.binary(BinaryOp::Div)?,
+            Op::Irem | Op::Lrem | Op::Frem | Op::Drem => self.binary(BinaryOp::Rem)?,
+            Op::Ishl | Op::Lshl => self.binary(BinaryOp::Shl)?,
+            Op::Ishr | Op::Lshr => self.binary(BinaryOp::Shr)?,
+            Op::Iushr | Op::Lushr => self.binary(BinaryOp::UShr)?,
+            Op::Iand | Op::Land => self.binary(BinaryOp::And)?,
+            Op::Ior | Op::Lor => self.binary(BinaryOp::Or)?,
+            Op::Ixor | Op::Lxor => self.binary(BinaryOp::Xor)?,
+            Op::Ineg | Op::Lneg | Op::Fneg | Op::Dneg => {
+                let value = self.pop()?;
+                self.push_to_stack(StackValue::negate(value));
+            }
+
+            Op::Lcmp | Op::Fcmpl | Op::Fcmpg | Op::Dcmpl | Op::Dcmpg => {
+                let right = self.pop()?;
+                let left = self.pop()?;
+                self.push_to_stack(StackValue::ThreeWayCompare {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                });
+            }
+
+            Op::I2l | Op::F2l | Op::D2l => self.convert(TLong)?,
+            Op::I2f | Op::L2f | Op::D2f => self.convert(TFloat)?,
+            Op::I2d | Op::L2d | Op::F2d => self.convert(TDouble)?,
+            Op::L2i | Op::F2i | Op::D2i => self.convert(TInt)?,
+            Op::I2b => self.convert(TByte)?,
+            Op::I2c => self.convert(TChar)?,
+            Op::I2s => self.convert(TShort)?,
+
+            Op::Checkcast | Op::Instanceof => {
+                let ty = self.class_ref(u16_arg(instruction))?;
+                let value = Box::new(self.pop()?);
+                self.push_to_stack(match instruction.op() {
+                    Op::Checkcast => StackValue::Cast { ty, value },
+                    _ => StackValue::InstanceOf { value, ty },
+                });
+            }
+
+            Op::Getstatic => {
+                let field = self.field_ref(u16_arg(instruction))?;
+                self.push_to_stack(StackValue::GetStatic(field));
+            }
+            Op::Getfield => {
+                let field = self.field_ref(u16_arg(instruction))?;
+                let target = self.pop()?;
+                self.push_to_stack(StackValue::GetField {
+                    target: Box::new(target),
+                    field,
+                });
+            }
+            Op::Putstatic => {
+                let field = self.field_ref(u16_arg(instruction))?;
+                let value = self.pop()?.coerce(&field.ty);
+                return Ok(Some(Expression::PutStatic(PutStatic { field, value })));
+            }
+            Op::Putfield => {
+                let field = self.field_ref(u16_arg(instruction))?;
+                let value = self.pop()?.coerce(&field.ty);
+                let target = self.pop()?;
+                return Ok(Some(Expression::PutField(PutField {
+                    target: Box::new(target),
+                    field,
+                    value,
+                })));
+            }
+
+            Op::Invokestatic => return self.invoke(InvokeKind::Static, u16_arg(instruction)),
+            Op::Invokevirtual => return self.invoke(InvokeKind::Virtual, u16_arg(instruction)),
+            Op::Invokespecial => return self.invoke(InvokeKind::Special, u16_arg(instruction)),
+            Op::Invokeinterface => return self.invoke(InvokeKind::Interface, u16_arg(instruction)),
+            Op::Invokedynamic => self.invoke_dynamic(u16_arg(instruction))?,
+            Op::New => {
+                let JVMType::TClass(class) = self.class_ref(u16_arg(instruction))? else {
+                    return Err(InstructionError::Unsupported(Op::New));
+                };
+                self.push_to_stack(StackValue::Uninitialized(class));
+            }
+
+            Op::Ireturn | Op::Lreturn | Op::Freturn | Op::Dreturn | Op::Areturn => {
+                let value = self.pop()?.coerce(&self.return_type);
+                return Ok(Some(Expression::ReturnStatement(ReturnStatement(Some(
+                    value,
+                )))));
+            }
+            Op::Return => return Ok(Some(Expression::ReturnStatement(ReturnStatement(None)))),
+            Op::Athrow => {
+                let value = self.pop()?;
+                return Ok(Some(Expression::Throw(ThrowStatement(value))));
+            }
+
+            other => return Err(InstructionError::Unsupported(other)),
+        }
+
+        Ok(None)
+    }
 }
@@ -1,51 +1,171 @@
+pub mod accessor;
+pub mod assertion;
+pub mod class_literal;
+pub mod cleanup;
 pub mod expression;
+pub mod flow;
 pub mod frame;
+pub mod switch;
+pub mod value;
 
-use jvm_class_format::attribute::CodeData;
-use jvm_class_format::{ConstantPool, Instruction, Member};
+use std::time::{Duration, Instant};
 
-use crate::test_many_expr;
+use jvm_class_format::attribute::{AsData, BootstrapMethodsData, CodeData};
+use jvm_class_format::{Class, Member, Op};
+
+use crate::error::LimitExceeded;
+
+use value::StackValue;
 
 use expression::*;
+use flow::MethodCode;
 use frame::*;
 
+/// Expression along with the bytecode offset of its first instruction.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement {
+    pub offset: usize,
+    pub expression: Expression,
+}
+
+impl Statement {
+    pub fn new(offset: usize, expression: Expression) -> Self {
+        Statement { offset, expression }
+    }
+}
+
+/// Returns `true` if `predicate` holds for any value in `statements`, their
+/// operands or nested blocks.
+pub fn any_value<F: FnMut(&StackValue) -> bool>(
+    statements: &[Statement],
+    predicate: &mut F,
+) -> bool {
+    statements.iter().any(|it| match &it.expression {
+        Expression::ReturnStatement(ReturnStatement(Some(value)))
+        | Expression::Value(value)
+        | Expression::Throw(ThrowStatement(value))
+        | Expression::Yield(value) => value_any(value, predicate),
+        Expression::StoreLocal(it) => value_any(&it.value, predicate),
+        Expression::PutField(it) => {
+            value_any(&it.target, predicate) || value_any(&it.value, predicate)
+        }
+        Expression::PutStatic(it) => value_any(&it.value, predicate),
+        Expression::ArrayStore(it) => {
+            value_any(&it.array, predicate)
+                || value_any(&it.index, predicate)
+                || value_any(&it.value, predicate)
+        }
+        Expression::If(it) => {
+            value_any(&it.condition, predicate)
+                || any_value(&it.then, predicate)
+                || it
+                    .otherwise
+                    .as_deref()
+                    .is_some_and(|it| any_value(it, predicate))
+        }
+        Expression::Switch(it) => {
+            value_any(&it.value, predicate)
+                || it.cases.iter().any(|it| any_value(&it.body, predicate))
+        }
+        Expression::Assert(it) => {
+            value_any(&it.condition, predicate)
+                || it
+                    .message
+                    .as_ref()
+                    .is_some_and(|it| value_any(it, predicate))
+        }
+        _ => false,
+    })
+}
+
+/// Offset and opcode of the first instruction in `statements` that was left
+/// as a comment because it couldn't be decompiled.
+fn first_comment(statements: &[Statement]) -> Option<(usize, Op)> {
+    for it in statements {
+        let nested = match &it.expression {
+            Expression::Comment(InstructionComment(instruction)) => {
+                return Some((it.offset, instruction.op()))
+            }
+            Expression::If(it) => {
+                first_comment(&it.then).or_else(|| it.otherwise.as_deref().and_then(first_comment))
+            }
+            Expression::Switch(it) => it.cases.iter().find_map(|it| first_comment(&it.body)),
+            _ => None,
+        };
+        if nested.is_some() {
+            return nested;
+        }
+    }
+    // bodies of `switch` expressions
+    let mut found = None;
+    any_value(statements, &mut |value| {
+        if let StackValue::Switch(switch) = value {
+            found = switch.cases.iter().find_map(|it| first_comment(&it.body));
+        }
+        found.is_some()
+    });
+    found
+}
+
+fn value_any<F: FnMut(&StackValue) -> bool>(value: &StackValue, predicate: &mut F) -> bool {
+    value.any(&mut |it| {
+        predicate(it)
+            || matches!(it, StackValue::Switch(switch)
+                if switch.cases.iter().any(|it| any_value(&it.body, predicate)))
+    })
+}
+
+/// Bounds of the work spent decompiling a single method, so that huge (e.g.
+/// obfuscated) methods can't stall decompilation of a class.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MethodLimits {
+    /// Longest time decompilation of a method may take.
+    pub deadline: Option<Duration>,
+    /// Largest number of instructions of a decompiled method.
+    pub max_instructions: Option<usize>,
+}
+
 // JVM spec, pg. 620 - 15.12.4. Run-Time Evaluation of Method Invocation
 
-pub fn decompile<'cp, 'code>(
-    constant_pool: &'cp ConstantPool,
+pub fn decompile(
+    class: &Class,
     method: &Member,
     code: &CodeData,
-) -> Vec<Expression> {
-    let instructions = Instruction::collect_instructions(&code.code);
-
-    let frame = RuntimeFrame::new(constant_pool, code);
+    limits: &MethodLimits,
+) -> Result<Vec<Statement>, LimitExceeded> {
+    let started = Instant::now();
+    let bootstrap_methods = class
+        .attributes
+        .get("BootstrapMethods")
+        .and_then(|it| AsData::<BootstrapMethodsData>::as_data(it).ok())
+        .map(|it| it.methods.as_slice())
+        .unwrap_or_default();
 
-    let mut result = Vec::with_capacity(instructions.len());
+    let body = MethodCode::new(&code.code).with_deadline(limits.deadline.map(|it| started + it));
+    if let Some(limit) = limits.max_instructions.filter(|it| body.len() > *it) {
+        return Err(LimitExceeded::Instructions {
+            count: body.len(),
+            limit,
+        });
+    }
+    let mut frame = RuntimeFrame::new(&class.constant_pool, method, code)
+        .with_bootstrap_methods(bootstrap_methods);
 
     if method.is_constructor() {
-        if let Some((_, expr)) = EmptyConstructor::test(instructions.as_slice(), 0, &frame) {
-            return vec![expr];
+        if let Some((_, expr)) = EmptyConstructor::test(body.instructions.as_slice(), 0, &frame) {
+            return Ok(vec![Statement::new(0, expr)]);
         }
     }
 
-    let mut offset = 0;
-    while offset < instructions.len() {
-        #[rustfmt::skip]
-        let (instruction_count, expr) = test_many_expr!(&[
-            EmptySuperCall,
-            InstructionComment
-        ], instructions.as_slice(), offset, &frame)
-        .unwrap();
-
-        offset += instruction_count;
-        result.push(expr);
+    let mut result = body.block(&mut frame, 0, body.len(), None).statements;
+    if let (true, Some(deadline)) = (body.timed_out(), limits.deadline) {
+        return Err(LimitExceeded::Deadline(deadline));
+    }
+    // partially decompiled code would silently differ from the original
+    if let Some((offset, op)) = first_comment(&result) {
+        let (offset, op) = body.failure_from(offset).unwrap_or((offset, op));
+        return Err(LimitExceeded::Unsupported { offset, op });
     }
-    debug_assert_eq!(
-        offset,
-        instructions.len(),
-        "to_ir overshot instruction buffer"
-    );
-
     result.shrink_to_fit();
-    result
+    Ok(result)
 }
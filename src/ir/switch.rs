@@ -0,0 +1,720 @@
+//! Recognition of `switch` statements over strings, enums and patterns, which
+//! javac compiles into switches over `int` values.
+
+use std::collections::HashMap;
+
+use jvm_class_format::{
+    attribute::{AsData, CodeData},
+    Class, ClassAccessFlags, ClassPath, Instruction, Op,
+};
+
+use super::expression::{
+    CaseLabel, Expression, IfStatement, ReturnStatement, StoreLocal, SwitchStatement,
+};
+use super::frame::RuntimeFrame;
+use super::value::{CompareOp, FieldRef, InvokeKind, Literal, StackValue};
+use super::Statement;
+
+/// Prefix of synthetic fields javac generates for switches over enums.
+pub const SWITCH_MAP_PREFIX: &str = "$SwitchMap$";
+
+/// `switch` statement or expression that is the outermost value of a
+/// statement, e.g. `return switch (...) {...};`.
+fn switch_in(expression: &Expression) -> Option<&SwitchStatement> {
+    match expression {
+        Expression::Switch(it) => Some(it),
+        Expression::ReturnStatement(ReturnStatement(Some(value)))
+        | Expression::StoreLocal(StoreLocal { value, .. })
+        | Expression::Value(value)
+        | Expression::Yield(value) => match value {
+            StackValue::Switch(it) => Some(it),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn switch_in_mut(expression: &mut Expression) -> Option<&mut SwitchStatement> {
+    match expression {
+        Expression::Switch(it) => Some(it),
+        Expression::ReturnStatement(ReturnStatement(Some(value)))
+        | Expression::StoreLocal(StoreLocal { value, .. })
+        | Expression::Value(value)
+        | Expression::Yield(value) => match value {
+            StackValue::Switch(it) => Some(it),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Applies `restore` to blocks nested in `statements`.
+pub(super) fn restore_nested(statements: &mut [Statement], restore: fn(&mut Vec<Statement>)) {
+    for statement in statements {
+        if let Expression::If(it) = &mut statement.expression {
+            restore(&mut it.then);
+            if let Some(otherwise) = &mut it.otherwise {
+                restore(otherwise);
+            }
+        } else if let Some(switch) = switch_in_mut(&mut statement.expression) {
+            for case in &mut switch.cases {
+                restore(&mut case.body);
+            }
+        }
+    }
+}
+
+/// Replaces `count` statements starting at `at` with the last of them, which
+/// contains a switch, and returns that switch.
+fn collapse_into_switch(
+    statements: &mut Vec<Statement>,
+    at: usize,
+    count: usize,
+) -> &mut SwitchStatement {
+    let offset = statements[at].offset;
+    statements.drain(at..at + count - 1);
+    let statement = &mut statements[at];
+    statement.offset = offset;
+    switch_in_mut(&mut statement.expression).expect("matched switch")
+}
+
+/// Restores `switch` statements and expressions over strings in `statements`
+/// and nested blocks.
+///
+/// javac compiles `switch (s)` into a temporary copy of `s`, a switch over
+/// `hashCode()` which compares the copy with `equals` and stores the index of
+/// the matched case and a second switch over that index:
+///
+/// ```java
+/// String tmp = s;
+/// int index = -1;
+/// switch (tmp.hashCode()) {
+///   case 101574:
+///     if (!tmp.equals("foo")) break;
+///     index = 0;
+///     break;
+/// }
+/// switch (index) {
+///   case 0: ...
+/// }
+/// ```
+pub fn restore_string_switches(statements: &mut Vec<Statement>) {
+    restore_nested(statements, restore_string_switches);
+
+    let mut i = 0;
+    while i + 4 <= statements.len() {
+        let Some((value, cases)) = string_switch(&statements[i..i + 4]) else {
+            i += 1;
+            continue;
+        };
+        let switch = collapse_into_switch(statements, i, 4);
+        for case in &mut switch.cases {
+            for label in &mut case.labels {
+                if let CaseLabel::Int(index) = label {
+                    *label = CaseLabel::String(cases[&*index].clone());
+                }
+            }
+        }
+        switch.value = value;
+        i += 1;
+    }
+}
+
+/// Matches the four statements of a string switch, returning the original
+/// selector and strings of case indices.
+fn string_switch(window: &[Statement]) -> Option<(StackValue, HashMap<i32, String>)> {
+    let [Statement {
+        expression:
+            Expression::StoreLocal(StoreLocal {
+                index: copy,
+                value: selector,
+                ..
+            }),
+        ..
+    }, Statement {
+        expression:
+            Expression::StoreLocal(StoreLocal {
+                index: case_index,
+                value: StackValue::Literal(Literal::Int(-1)),
+                ..
+            }),
+        ..
+    }, Statement {
+        expression: Expression::Switch(hash_switch),
+        ..
+    }, index_switch] = window
+    else {
+        return None;
+    };
+    let index_switch = switch_in(&index_switch.expression)?;
+
+    match &hash_switch.value {
+        StackValue::Invoke {
+            method,
+            target: Some(target),
+            args,
+            ..
+        } if method.name == "hashCode"
+            && args.is_empty()
+            && matches!(target.as_ref(), StackValue::Local { index, .. } if index == copy) => {}
+        _ => return None,
+    }
+    if !matches!(&index_switch.value, StackValue::Local { index, .. } if index == case_index) {
+        return None;
+    }
+
+    let mut strings = HashMap::new();
+    for case in &hash_switch.cases {
+        collect_case_strings(&case.body, *copy, *case_index, &mut strings)?;
+    }
+
+    let mut result = HashMap::new();
+    for label in index_switch.cases.iter().flat_map(|it| &it.labels) {
+        if let CaseLabel::Int(index) = label {
+            result.insert(*index, strings.get(index)?.clone());
+        }
+    }
+
+    Some((selector.clone(), result))
+}
+
+/// String compared by `copy.equals("...")` in a condition, and whether the
+/// condition holds when the strings are equal.
+fn equals_check(condition: &StackValue, copy: usize) -> Option<(&str, bool)> {
+    let StackValue::Compare {
+        op: op @ (CompareOp::Eq | CompareOp::Ne),
+        left,
+        right,
+    } = condition
+    else {
+        return None;
+    };
+    if **right != StackValue::Literal(Literal::Int(0)) {
+        return None;
+    }
+    match left.as_ref() {
+        StackValue::Invoke {
+            method,
+            target: Some(target),
+            args,
+            ..
+        } if method.name == "equals"
+            && matches!(target.as_ref(), StackValue::Local { index, .. } if *index == copy) =>
+        {
+            match args.as_slice() {
+                [StackValue::Literal(Literal::String(it))] => Some((it, *op == CompareOp::Ne)),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Collects strings and their case indices from a case of the `hashCode()`
+/// switch; returns `None` if the case contains anything else.
+fn collect_case_strings(
+    body: &[Statement],
+    copy: usize,
+    case_index: usize,
+    strings: &mut HashMap<i32, String>,
+) -> Option<()> {
+    // string checked by a preceding `if (!copy.equals("...")) break;`
+    let mut matched: Option<&str> = None;
+    for statement in body {
+        match &statement.expression {
+            Expression::If(IfStatement {
+                condition,
+                then,
+                otherwise: None,
+            }) => match equals_check(condition, copy)? {
+                (string, true) => {
+                    let index = assigned_index(then, case_index)?;
+                    strings.insert(index, string.to_string());
+                }
+                (string, false) => {
+                    if !matches!(
+                        then.as_slice(),
+                        [Statement {
+                            expression: Expression::Break,
+                            ..
+                        }]
+                    ) {
+                        return None;
+                    }
+                    matched = Some(string);
+                }
+            },
+            Expression::StoreLocal(StoreLocal {
+                index,
+                value: StackValue::Literal(Literal::Int(value)),
+                ..
+            }) if *index == case_index => {
+                strings.insert(*value, matched.take()?.to_string());
+            }
+            Expression::Break => {}
+            _ => return None,
+        }
+    }
+    Some(())
+}
+
+/// Case index assigned by the branch of a matched string, optionally followed
+/// by `break`.
+fn assigned_index(body: &[Statement], case_index: usize) -> Option<i32> {
+    let (store, rest) = body.split_first()?;
+    let Expression::StoreLocal(StoreLocal {
+        index,
+        value: StackValue::Literal(Literal::Int(value)),
+        ..
+    }) = &store.expression
+    else {
+        return None;
+    };
+    if *index != case_index {
+        return None;
+    }
+    match rest {
+        []
+        | [Statement {
+            expression: Expression::Break,
+            ..
+        }] => Some(*value),
+        _ => None,
+    }
+}
+
+/// Case values of enum constants stored in synthetic `$SwitchMap$` arrays.
+///
+/// javac compiles `switch (e)` over an enum into a switch over
+/// `Outer$1.$SwitchMap$pkg$Enum[e.ordinal()]`; the array is filled with case
+/// values in the static initializer of the synthetic class.
+#[derive(Debug, Clone, Default)]
+pub struct SwitchMaps {
+    /// Constant names by case value, keyed by the class declaring the array
+    /// and the array field name.
+    maps: HashMap<(ClassPath, String), HashMap<i32, String>>,
+}
+
+impl SwitchMaps {
+    /// Reads switch maps declared by `class`; returns `false` if it doesn't
+    /// declare any.
+    pub fn add_class(&mut self, class: &Class) -> bool {
+        let Some(code) = class
+            .methods
+            .iter()
+            .find(|it| it.name == "<clinit>")
+            .and_then(|method| {
+                let code: &CodeData = method.attributes.get("Code")?.as_data().ok()?;
+                Some((method, code))
+            })
+        else {
+            return false;
+        };
+        let (method, code) = code;
+        let frame = RuntimeFrame::new(&class.constant_pool, method, code);
+        let field = |instruction: &Instruction| {
+            let args = instruction.args();
+            frame
+                .field_ref(((args[0] as usize) << 8) | args[1] as usize)
+                .ok()
+        };
+
+        let mut found = false;
+        let instructions = Instruction::collect_instructions(&code.code);
+        // Outer$1.$SwitchMap$pkg$Enum[Enum.CONSTANT.ordinal()] = value;
+        for window in instructions.windows(5) {
+            let [array, constant, ordinal, value, store] = window else {
+                continue;
+            };
+            if array.op() != Op::Getstatic
+                || constant.op() != Op::Getstatic
+                || ordinal.op() != Op::Invokevirtual
+                || store.op() != Op::Iastore
+            {
+                continue;
+            }
+            let value = match value.op() {
+                Op::Iconst0 => 0,
+                Op::Iconst1 => 1,
+                Op::Iconst2 => 2,
+                Op::Iconst3 => 3,
+                Op::Iconst4 => 4,
+                Op::Iconst5 => 5,
+                Op::Bipush => value.args()[0] as i8 as i32,
+                Op::Sipush => i16::from_be_bytes([value.args()[0], value.args()[1]]) as i32,
+                _ => continue,
+            };
+            let (Some(array), Some(constant)) = (field(array), field(constant)) else {
+                continue;
+            };
+            if !array.name.starts_with(SWITCH_MAP_PREFIX) {
+                continue;
+            }
+
+            self.maps
+                .entry((array.class, array.name))
+                .or_default()
+                .insert(value, constant.name);
+            found = true;
+        }
+        found
+    }
+
+    /// Constant names by case value stored in `field`.
+    pub fn get(&self, field: &FieldRef) -> Option<&HashMap<i32, String>> {
+        self.maps.get(&(field.class.clone(), field.name.clone()))
+    }
+}
+
+/// Returns `true` for synthetic classes that only hold switch maps, which
+/// don't exist in source.
+pub fn is_switch_map_class(class: &Class) -> bool {
+    class.access_flags.contains(ClassAccessFlags::SYNTHETIC)
+        && !class.fields.is_empty()
+        && class
+            .fields
+            .iter()
+            .all(|it| it.name.starts_with(SWITCH_MAP_PREFIX))
+}
+
+/// Switch over `Outer$1.$SwitchMap$pkg$Enum[value.ordinal()]`, returning the
+/// enum value and the array field.
+pub fn enum_switch(switch: &SwitchStatement) -> Option<(&StackValue, &FieldRef)> {
+    let StackValue::ArrayLoad { array, index } = &switch.value else {
+        return None;
+    };
+    let StackValue::GetStatic(field) = array.as_ref() else {
+        return None;
+    };
+    if !field.name.starts_with(SWITCH_MAP_PREFIX) {
+        return None;
+    }
+    match index.as_ref() {
+        StackValue::Invoke {
+            method,
+            target: Some(target),
+            args,
+            ..
+        } if method.name == "ordinal" && args.is_empty() => Some((target, field)),
+        _ => None,
+    }
+}
+
+/// Restores `switch` statements and expressions over patterns, `null` and
+/// enum constants in `statements` and nested blocks.
+///
+/// javac compiles them into a switch over the index of the matched label
+/// returned by an `invokedynamic` call of `SwitchBootstraps.typeSwitch` (or
+/// `enumSwitch`); labels are static arguments of the call site and `-1`
+/// stands for `null`. Type patterns cast a temporary copy of the selector in
+/// their case:
+///
+/// ```java
+/// Objects.requireNonNull(o);
+/// Object tmp = o;
+/// int restart = 0;
+/// switch (typeSwitch(tmp, restart)) {
+///   case 0:
+///     String s = (String) tmp;
+///     ...
+/// }
+/// ```
+///
+/// Record patterns aren't restored: a `case Point(int x, int y)` label is
+/// written as a `Point` type pattern followed by the accessor calls javac
+/// generated to deconstruct it, and switches whose nested patterns restart
+/// matching are left as they are.
+pub fn restore_type_switches(statements: &mut Vec<Statement>) {
+    restore_nested(statements, restore_type_switches);
+
+    let mut i = 0;
+    while i + 3 <= statements.len() {
+        let null_check = usize::from(
+            i + 4 <= statements.len()
+                && is_null_check(&statements[i].expression, &statements[i + 1].expression),
+        );
+        let window = &statements[i + null_check..i + null_check + 3];
+        let Some((value, copy, labels, enum_labels)) = type_switch(window) else {
+            i += 1;
+            continue;
+        };
+
+        let switch = collapse_into_switch(statements, i, null_check + 3);
+        for case in &mut switch.cases {
+            for label in &mut case.labels {
+                let CaseLabel::Int(index) = label else {
+                    continue;
+                };
+                *label = match labels.get(*index as usize) {
+                    _ if *index == -1 => CaseLabel::Null,
+                    Some(Literal::Class(ty)) => {
+                        // pattern binding is assigned first; the cast is left
+                        // out if the selector already has the pattern type
+                        let is_copy = |value: &StackValue| matches!(value, StackValue::Local { index, .. } if *index == copy);
+                        let binding = match case.body.first().map(|it| &it.expression) {
+                            Some(Expression::StoreLocal(StoreLocal { index, value, .. }))
+                                if is_copy(value)
+                                    || matches!(value, StackValue::Cast { ty: cast, value }
+                                        if cast == ty && is_copy(value)) =>
+                            {
+                                Some(*index)
+                            }
+                            _ => None,
+                        };
+                        if binding.is_some() {
+                            case.body.remove(0);
+                        }
+                        // unused bindings are named after the selector copy,
+                        // which isn't read anywhere else
+                        CaseLabel::Type {
+                            ty: ty.clone(),
+                            binding: binding.unwrap_or(copy),
+                        }
+                    }
+                    // `enumSwitch` labels are constant names
+                    Some(Literal::String(name)) if enum_labels => CaseLabel::Enum(name.clone()),
+                    Some(Literal::String(value)) => CaseLabel::String(value.clone()),
+                    Some(Literal::Int(value)) => CaseLabel::Int(*value),
+                    _ => unreachable!("matched type switch"),
+                };
+            }
+        }
+        switch.value = value;
+        i += 1;
+    }
+}
+
+/// `Objects.requireNonNull(x)` followed by storing `x` in a local.
+fn is_null_check(check: &Expression, store: &Expression) -> bool {
+    let (
+        Expression::Value(StackValue::Invoke {
+            kind: InvokeKind::Static,
+            method,
+            args,
+            ..
+        }),
+        Expression::StoreLocal(StoreLocal { value, .. }),
+    ) = (check, store)
+    else {
+        return false;
+    };
+    method.class.internal_name() == "java/util/Objects"
+        && method.name == "requireNonNull"
+        && args.as_slice() == [value.clone()]
+}
+
+/// Matches the selector copy, restart index and switch over a
+/// `typeSwitch` call site, returning the original selector, local slot of
+/// its copy, labels and whether they're enum constants (of `enumSwitch`).
+fn type_switch(window: &[Statement]) -> Option<(StackValue, usize, Vec<Literal>, bool)> {
+    let [Statement {
+        expression:
+            Expression::StoreLocal(StoreLocal {
+                index: copy,
+                value: selector,
+                ..
+            }),
+        ..
+    }, Statement {
+        expression:
+            Expression::StoreLocal(StoreLocal {
+                index: restart,
+                value: StackValue::Literal(Literal::Int(0)),
+                ..
+            }),
+        ..
+    }, switch] = window
+    else {
+        return None;
+    };
+    let switch = switch_in(&switch.expression)?;
+
+    let StackValue::InvokeDynamic {
+        bootstrap,
+        static_args,
+        args,
+        ..
+    } = &switch.value
+    else {
+        return None;
+    };
+    let is_local = |value: &StackValue, slot: usize| matches!(value, StackValue::Local { index, .. } if *index == slot);
+    if bootstrap.class.internal_name() != "java/lang/runtime/SwitchBootstraps"
+        || !matches!(args.as_slice(), [value, index] if is_local(value, *copy) && is_local(index, *restart))
+    {
+        return None;
+    }
+
+    // guards restart matching from a later label, which isn't supported
+    if switch.cases.iter().any(|it| assigns(&it.body, *restart)) {
+        return None;
+    }
+    let labels_known = switch
+        .cases
+        .iter()
+        .flat_map(|it| &it.labels)
+        .all(|label| match label {
+            CaseLabel::Int(-1) | CaseLabel::Default => true,
+            CaseLabel::Int(index) => matches!(
+                static_args.get(*index as usize),
+                Some(Literal::Class(_) | Literal::String(_) | Literal::Int(_))
+            ),
+            _ => false,
+        });
+    if !labels_known {
+        return None;
+    }
+
+    Some((
+        selector.clone(),
+        *copy,
+        static_args.clone(),
+        bootstrap.name == "enumSwitch",
+    ))
+}
+
+/// Returns `true` if `statements` assign the local variable `slot`.
+fn assigns(statements: &[Statement], slot: usize) -> bool {
+    statements.iter().any(|it| match &it.expression {
+        Expression::StoreLocal(it) => it.index == slot,
+        Expression::If(it) => {
+            assigns(&it.then, slot) || it.otherwise.as_deref().is_some_and(|it| assigns(it, slot))
+        }
+        other => switch_in(other)
+            .is_some_and(|switch| switch.cases.iter().any(|it| assigns(&it.body, slot))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use jvm_class_format::JVMType;
+
+    use super::*;
+    use crate::ir::expression::SwitchCase;
+    use crate::ir::value::MethodRef;
+
+    fn string() -> JVMType {
+        JVMType::TClass(ClassPath::java_lang_class("String"))
+    }
+
+    fn local(index: usize) -> StackValue {
+        StackValue::Local {
+            index,
+            ty: string(),
+        }
+    }
+
+    fn store(index: usize, value: StackValue) -> Statement {
+        Statement::new(
+            0,
+            Expression::StoreLocal(StoreLocal {
+                index,
+                ty: string(),
+                value,
+                declare: true,
+            }),
+        )
+    }
+
+    fn case(index: i32, body: Vec<Statement>) -> SwitchCase {
+        SwitchCase {
+            labels: vec![CaseLabel::Int(index)],
+            body,
+        }
+    }
+
+    /// `switch (arg_0)` compiled through a `SwitchBootstraps` call site.
+    fn bootstrap_switch(
+        bootstrap: &str,
+        labels: Vec<Literal>,
+        cases: Vec<SwitchCase>,
+    ) -> Vec<Statement> {
+        let switch = SwitchStatement {
+            value: StackValue::InvokeDynamic {
+                bootstrap: MethodRef {
+                    class: ClassPath::parse("java/lang/runtime/SwitchBootstraps").unwrap(),
+                    name: bootstrap.to_string(),
+                    descriptor: "()V".parse().unwrap(),
+                },
+                name: bootstrap.to_string(),
+                descriptor: "(Ljava/lang/Object;I)I".parse().unwrap(),
+                static_args: labels,
+                args: vec![local(1), local(2)],
+            },
+            cases,
+        };
+        vec![
+            store(1, local(0)),
+            store(2, StackValue::Literal(Literal::Int(0))),
+            Statement::new(0, Expression::Switch(switch)),
+        ]
+    }
+
+    fn labels(statements: &[Statement]) -> Vec<CaseLabel> {
+        let [Statement {
+            expression: Expression::Switch(switch),
+            ..
+        }] = statements
+        else {
+            panic!("switch wasn't restored: {:?}", statements);
+        };
+        switch
+            .cases
+            .iter()
+            .flat_map(|it| it.labels.clone())
+            .collect()
+    }
+
+    #[test]
+    fn type_switch_labels() {
+        let mut statements = bootstrap_switch(
+            "typeSwitch",
+            vec![Literal::String("a".to_string()), Literal::Class(string())],
+            vec![
+                case(0, vec![]),
+                // binding of the selector type isn't cast
+                case(1, vec![store(3, local(1))]),
+            ],
+        );
+        restore_type_switches(&mut statements);
+        assert_eq!(
+            labels(&statements),
+            [
+                CaseLabel::String("a".to_string()),
+                CaseLabel::Type {
+                    ty: string(),
+                    binding: 3
+                },
+            ]
+        );
+
+        // unused bindings are named after the selector copy
+        let mut statements = bootstrap_switch(
+            "typeSwitch",
+            vec![Literal::Class(string())],
+            vec![case(0, vec![])],
+        );
+        restore_type_switches(&mut statements);
+        assert_eq!(
+            labels(&statements),
+            [CaseLabel::Type {
+                ty: string(),
+                binding: 1
+            }]
+        );
+    }
+
+    #[test]
+    fn enum_switch_labels() {
+        let mut statements = bootstrap_switch(
+            "enumSwitch",
+            vec![Literal::String("RED".to_string())],
+            vec![case(0, vec![]), case(-1, vec![])],
+        );
+        restore_type_switches(&mut statements);
+        assert_eq!(
+            labels(&statements),
+            [CaseLabel::Enum("RED".to_string()), CaseLabel::Null]
+        );
+    }
+}
@@ -0,0 +1,629 @@
+use jvm_class_format::{ClassPath, Constant, ConstantPool, Descriptor, JVMPrimitive, JVMType};
+
+use crate::error::InstructionError;
+
+use super::expression::{Expression, SwitchStatement};
+use super::Statement;
+
+/// Parses name of a `Class` constant which is either a class path or an array
+/// type descriptor.
+pub(crate) fn class_constant_type(name: &str) -> Result<JVMType, InstructionError> {
+    if name.starts_with('[') {
+        Ok(name.parse()?)
+    } else {
+        Ok(JVMType::TClass(ClassPath::parse(name)?))
+    }
+}
+
+/// Constant value known at decompilation time.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Null,
+    Boolean(bool),
+    /// UTF-16 code unit; not necessarily a valid Unicode scalar value.
+    Char(u16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    String(String),
+    /// Class literal (e.g. `String.class`).
+    Class(JVMType),
+}
+
+impl Literal {
+    /// Reads a loadable constant (`ldc` operand or `ConstantValue`).
+    pub fn from_constant(pool: &ConstantPool, index: usize) -> Result<Literal, InstructionError> {
+        Ok(match pool.try_get(index)? {
+            Constant::Integer { value } => Literal::Int(*value),
+            Constant::Float { value } => Literal::Float(value.0),
+            Constant::Long { value } => Literal::Long(*value),
+            Constant::Double { value } => Literal::Double(value.0),
+            Constant::String { string_index } => {
+                Literal::String(pool.get_utf8(*string_index as usize)?.to_string())
+            }
+            Constant::Class { name_index } => {
+                Literal::Class(class_constant_type(pool.get_utf8(*name_index as usize)?)?)
+            }
+            other => return Err(InstructionError::UnsupportedConstant(other.tag())),
+        })
+    }
+
+    pub fn ty(&self) -> JVMType {
+        match self {
+            Literal::Null => JVMType::TClass(ClassPath::default()),
+            Literal::Boolean(_) => JVMPrimitive::TBoolean.into(),
+            Literal::Char(_) => JVMPrimitive::TChar.into(),
+            Literal::Int(_) => JVMPrimitive::TInt.into(),
+            Literal::Long(_) => JVMPrimitive::TLong.into(),
+            Literal::Float(_) => JVMPrimitive::TFloat.into(),
+            Literal::Double(_) => JVMPrimitive::TDouble.into(),
+            Literal::String(_) => JVMType::TClass(ClassPath::java_lang_class("String")),
+            Literal::Class(_) => JVMType::TClass(ClassPath::java_lang_class("Class")),
+        }
+    }
+
+    /// Returns the value of literals that JVM represents as `int`.
+    pub fn int_value(&self) -> Option<i32> {
+        match self {
+            Literal::Boolean(it) => Some(*it as i32),
+            Literal::Char(it) => Some(*it as i32),
+            Literal::Int(it) => Some(*it),
+            _ => None,
+        }
+    }
+
+    /// Initial value of fields and array elements of type `ty`.
+    pub fn default_value(ty: &JVMType) -> Literal {
+        match ty {
+            JVMType::TPrimitive(JVMPrimitive::TBoolean) => Literal::Boolean(false),
+            JVMType::TPrimitive(JVMPrimitive::TChar) => Literal::Char(0),
+            JVMType::TPrimitive(JVMPrimitive::TLong) => Literal::Long(0),
+            JVMType::TPrimitive(JVMPrimitive::TFloat) => Literal::Float(0.0),
+            JVMType::TPrimitive(JVMPrimitive::TDouble) => Literal::Double(0.0),
+            JVMType::TPrimitive(_) => Literal::Int(0),
+            _ => Literal::Null,
+        }
+    }
+
+    /// Reinterprets an `int` literal in context of a narrower source type.
+    ///
+    /// JVM doesn't distinguish between `boolean`, `char` and `int` values on
+    /// the operand stack so the type is only known once the value is consumed.
+    pub fn coerce(self, ty: &JVMType) -> Literal {
+        let value = match self.int_value() {
+            Some(it) => it,
+            None => return self,
+        };
+
+        match ty {
+            JVMType::TPrimitive(JVMPrimitive::TChar) => Literal::Char(value as u16),
+            JVMType::TPrimitive(JVMPrimitive::TBoolean) if value == 0 || value == 1 => {
+                Literal::Boolean(value == 1)
+            }
+            JVMType::TPrimitive(
+                JVMPrimitive::TInt | JVMPrimitive::TShort | JVMPrimitive::TByte,
+            ) => Literal::Int(value),
+            _ => self,
+        }
+    }
+
+    fn fold_binary(op: BinaryOp, left: &Literal, right: &Literal) -> Option<Literal> {
+        use BinaryOp::*;
+
+        if let (Some(a), Some(b)) = (left.int_value(), right.int_value()) {
+            return Some(Literal::Int(match op {
+                Add => a.wrapping_add(b),
+                Sub => a.wrapping_sub(b),
+                Mul => a.wrapping_mul(b),
+                Div if b != 0 => a.wrapping_div(b),
+                Rem if b != 0 => a.wrapping_rem(b),
+                Shl => a.wrapping_shl(b as u32),
+                Shr => a.wrapping_shr(b as u32),
+                UShr => (a as u32).wrapping_shr(b as u32) as i32,
+                And => a & b,
+                Or => a | b,
+                Xor => a ^ b,
+                _ => return None,
+            }));
+        }
+
+        Some(match (left, right) {
+            (Literal::Long(a), b) if op.is_shift() => {
+                let b = b.int_value()? as u32;
+                Literal::Long(match op {
+                    Shl => a.wrapping_shl(b),
+                    Shr => a.wrapping_shr(b),
+                    _ => (*a as u64).wrapping_shr(b) as i64,
+                })
+            }
+            (Literal::Long(a), Literal::Long(b)) => Literal::Long(match op {
+                Add => a.wrapping_add(*b),
+                Sub => a.wrapping_sub(*b),
+                Mul => a.wrapping_mul(*b),
+                Div if *b != 0 => a.wrapping_div(*b),
+                Rem if *b != 0 => a.wrapping_rem(*b),
+                And => a & b,
+                Or => a | b,
+                Xor => a ^ b,
+                _ => return None,
+            }),
+            (Literal::Float(a), Literal::Float(b)) => Literal::Float(match op {
+                Add => a + b,
+                Sub => a - b,
+                Mul => a * b,
+                Div => a / b,
+                Rem => a % b,
+                _ => return None,
+            }),
+            (Literal::Double(a), Literal::Double(b)) => Literal::Double(match op {
+                Add => a + b,
+                Sub => a - b,
+                Mul => a * b,
+                Div => a / b,
+                Rem => a % b,
+                _ => return None,
+            }),
+            _ => return None,
+        })
+    }
+
+    fn fold_negate(&self) -> Option<Literal> {
+        Some(match self {
+            Literal::Long(it) => Literal::Long(it.wrapping_neg()),
+            Literal::Float(it) => Literal::Float(-it),
+            Literal::Double(it) => Literal::Double(-it),
+            other => Literal::Int(other.int_value()?.wrapping_neg()),
+        })
+    }
+
+    /// Folds primitive conversion instructions (`i2l`, `d2i`, ...).
+    ///
+    /// Rust `as` casts saturate and map NaN to 0 which matches JVM semantics.
+    fn fold_convert(&self, into: JVMPrimitive) -> Option<Literal> {
+        let literal = match self {
+            Literal::Long(it) => match into {
+                JVMPrimitive::TInt => Literal::Int(*it as i32),
+                JVMPrimitive::TFloat => Literal::Float(*it as f32),
+                JVMPrimitive::TDouble => Literal::Double(*it as f64),
+                _ => return None,
+            },
+            Literal::Float(it) => match into {
+                JVMPrimitive::TInt => Literal::Int(*it as i32),
+                JVMPrimitive::TLong => Literal::Long(*it as i64),
+                JVMPrimitive::TDouble => Literal::Double(*it as f64),
+                _ => return None,
+            },
+            Literal::Double(it) => match into {
+                JVMPrimitive::TInt => Literal::Int(*it as i32),
+                JVMPrimitive::TLong => Literal::Long(*it as i64),
+                JVMPrimitive::TFloat => Literal::Float(*it as f32),
+                _ => return None,
+            },
+            other => {
+                let it = other.int_value()?;
+                match into {
+                    JVMPrimitive::TLong => Literal::Long(it as i64),
+                    JVMPrimitive::TFloat => Literal::Float(it as f32),
+                    JVMPrimitive::TDouble => Literal::Double(it as f64),
+                    JVMPrimitive::TByte => Literal::Int(it as i8 as i32),
+                    JVMPrimitive::TShort => Literal::Int(it as i16 as i32),
+                    JVMPrimitive::TChar => Literal::Char(it as u16),
+                    _ => return None,
+                }
+            }
+        };
+        Some(literal)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Shl,
+    Shr,
+    UShr,
+    And,
+    Or,
+    Xor,
+}
+
+impl BinaryOp {
+    pub fn is_shift(self) -> bool {
+        matches!(self, BinaryOp::Shl | BinaryOp::Shr | BinaryOp::UShr)
+    }
+
+    pub fn is_bitwise(self) -> bool {
+        matches!(self, BinaryOp::And | BinaryOp::Or | BinaryOp::Xor)
+    }
+}
+
+/// Comparison of two values, yielding a `boolean`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Lt,
+    Ge,
+    Gt,
+    Le,
+}
+
+impl CompareOp {
+    /// Comparison that holds exactly when this one doesn't.
+    ///
+    /// For floating point operands this relies on javac picking `fcmpl` or
+    /// `fcmpg` so that `NaN` takes the branch of the negated source condition.
+    pub fn negate(self) -> CompareOp {
+        match self {
+            CompareOp::Eq => CompareOp::Ne,
+            CompareOp::Ne => CompareOp::Eq,
+            CompareOp::Lt => CompareOp::Ge,
+            CompareOp::Ge => CompareOp::Lt,
+            CompareOp::Gt => CompareOp::Le,
+            CompareOp::Le => CompareOp::Gt,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldRef {
+    pub class: ClassPath,
+    pub name: String,
+    pub ty: JVMType,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodRef {
+    pub class: ClassPath,
+    pub name: String,
+    pub descriptor: Descriptor,
+}
+
+impl MethodRef {
+    pub fn is_constructor(&self) -> bool {
+        self.name == "<init>"
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InvokeKind {
+    Static,
+    Virtual,
+    Special,
+    Interface,
+}
+
+/// Symbolic value on the operand stack.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StackValue {
+    Literal(Literal),
+    This,
+    Local {
+        index: usize,
+        ty: JVMType,
+    },
+    GetStatic(FieldRef),
+    GetField {
+        target: Box<StackValue>,
+        field: FieldRef,
+    },
+    Invoke {
+        kind: InvokeKind,
+        method: MethodRef,
+        target: Option<Box<StackValue>>,
+        args: Vec<StackValue>,
+    },
+    /// Result of `new` instruction before the constructor was called.
+    Uninitialized(ClassPath),
+    New {
+        class: ClassPath,
+        args: Vec<StackValue>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<StackValue>,
+        right: Box<StackValue>,
+    },
+    Negate(Box<StackValue>),
+    Cast {
+        ty: JVMType,
+        value: Box<StackValue>,
+    },
+    /// Result of `lcmp`, `fcmpl`, `fcmpg`, `dcmpl` or `dcmpg`; usually
+    /// consumed by a conditional branch.
+    ThreeWayCompare {
+        left: Box<StackValue>,
+        right: Box<StackValue>,
+    },
+    Compare {
+        op: CompareOp,
+        left: Box<StackValue>,
+        right: Box<StackValue>,
+    },
+    ArrayLoad {
+        array: Box<StackValue>,
+        index: Box<StackValue>,
+    },
+    /// Array creation; `values` are elements stored right after creation
+    /// (`new int[] {1, 2}`).
+    NewArray {
+        /// Element type.
+        ty: JVMType,
+        length: Box<StackValue>,
+        values: Option<Vec<StackValue>>,
+    },
+    InstanceOf {
+        value: Box<StackValue>,
+        ty: JVMType,
+    },
+    /// Conditional expression (`condition ? then : otherwise`).
+    Conditional {
+        condition: Box<StackValue>,
+        then: Box<StackValue>,
+        otherwise: Box<StackValue>,
+    },
+    /// `switch` expression; cases yield its value.
+    Switch(Box<SwitchStatement>),
+    /// `invokedynamic` call site.
+    InvokeDynamic {
+        bootstrap: MethodRef,
+        name: String,
+        descriptor: Descriptor,
+        static_args: Vec<Literal>,
+        args: Vec<StackValue>,
+    },
+}
+
+/// Type of the first value yielded by `statements`.
+fn yield_type(statements: &[Statement]) -> Option<JVMType> {
+    statements.iter().find_map(|it| match &it.expression {
+        Expression::Yield(value) => Some(value.ty()),
+        Expression::If(it) => {
+            yield_type(&it.then).or_else(|| it.otherwise.as_deref().and_then(yield_type))
+        }
+        _ => None,
+    })
+}
+
+impl StackValue {
+    pub fn ty(&self) -> JVMType {
+        match self {
+            StackValue::Literal(it) => it.ty(),
+            StackValue::This => JVMType::TClass(ClassPath::default()),
+            StackValue::Local { ty, .. } => ty.clone(),
+            StackValue::GetStatic(field) | StackValue::GetField { field, .. } => field.ty.clone(),
+            StackValue::Invoke { method, .. } => method.descriptor.value.clone(),
+            StackValue::Uninitialized(class) | StackValue::New { class, .. } => {
+                JVMType::TClass(class.clone())
+            }
+            StackValue::Binary { left, .. } => left.ty(),
+            StackValue::Negate(value) => value.ty(),
+            StackValue::Cast { ty, .. } => ty.clone(),
+            StackValue::ThreeWayCompare { .. } => JVMPrimitive::TInt.into(),
+            StackValue::Compare { .. } => JVMPrimitive::TBoolean.into(),
+            StackValue::ArrayLoad { array, .. } => array
+                .ty()
+                .element_type()
+                .unwrap_or_else(|| JVMType::TClass(ClassPath::default())),
+            StackValue::NewArray { ty, .. } => ty.array_of(),
+            StackValue::InstanceOf { .. } => JVMPrimitive::TBoolean.into(),
+            StackValue::Conditional {
+                then, otherwise, ..
+            } => match then.as_ref() {
+                StackValue::Literal(Literal::Null) => otherwise.ty(),
+                then => then.ty(),
+            },
+            StackValue::Switch(switch) => switch
+                .cases
+                .iter()
+                .find_map(|it| yield_type(&it.body))
+                .unwrap_or_else(|| JVMType::TClass(ClassPath::default())),
+            StackValue::InvokeDynamic { descriptor, .. } => descriptor.value.clone(),
+        }
+    }
+
+    /// Returns `true` for values that occupy two operand stack slots.
+    pub fn is_wide(&self) -> bool {
+        matches!(
+            self.ty(),
+            JVMType::TPrimitive(JVMPrimitive::TLong | JVMPrimitive::TDouble)
+        )
+    }
+
+    /// Returns `true` if `predicate` holds for this value or any of its
+    /// operands.
+    pub fn any<F: FnMut(&StackValue) -> bool>(&self, predicate: &mut F) -> bool {
+        if predicate(self) {
+            return true;
+        }
+
+        match self {
+            StackValue::GetField { target, .. } => target.any(predicate),
+            StackValue::Invoke { target, args, .. } => {
+                target.as_ref().is_some_and(|it| it.any(predicate))
+                    || args.iter().any(|it| it.any(predicate))
+            }
+            StackValue::New { args, .. } => args.iter().any(|it| it.any(predicate)),
+            StackValue::Binary { left, right, .. }
+            | StackValue::ThreeWayCompare { left, right }
+            | StackValue::Compare { left, right, .. } => {
+                left.any(predicate) || right.any(predicate)
+            }
+            StackValue::ArrayLoad { array, index } => array.any(predicate) || index.any(predicate),
+            StackValue::NewArray { length, values, .. } => {
+                length.any(predicate) || values.iter().flatten().any(|it| it.any(predicate))
+            }
+            StackValue::InstanceOf { value, .. } => value.any(predicate),
+            StackValue::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => condition.any(predicate) || then.any(predicate) || otherwise.any(predicate),
+            StackValue::Switch(switch) => switch.value.any(predicate),
+            StackValue::InvokeDynamic { args, .. } => args.iter().any(|it| it.any(predicate)),
+            StackValue::Negate(value) | StackValue::Cast { value, .. } => value.any(predicate),
+            _ => false,
+        }
+    }
+
+    /// Operands of this value; the selector of a `switch` expression, but not
+    /// its cases.
+    pub fn operands_mut(&mut self) -> Vec<&mut StackValue> {
+        match self {
+            StackValue::GetField { target, .. } => vec![target],
+            StackValue::Invoke { target, args, .. } => {
+                target.iter_mut().map(Box::as_mut).chain(args).collect()
+            }
+            StackValue::New { args, .. } | StackValue::InvokeDynamic { args, .. } => {
+                args.iter_mut().collect()
+            }
+            StackValue::Binary { left, right, .. }
+            | StackValue::ThreeWayCompare { left, right }
+            | StackValue::Compare { left, right, .. } => vec![left, right],
+            StackValue::ArrayLoad { array, index } => vec![array, index],
+            StackValue::NewArray { length, values, .. } => std::iter::once(length.as_mut())
+                .chain(values.iter_mut().flatten())
+                .collect(),
+            StackValue::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => vec![condition, then, otherwise],
+            StackValue::Switch(switch) => vec![&mut switch.value],
+            StackValue::InstanceOf { value, .. }
+            | StackValue::Negate(value)
+            | StackValue::Cast { value, .. } => vec![value],
+            _ => vec![],
+        }
+    }
+
+    pub fn coerce(self, ty: &JVMType) -> StackValue {
+        match self {
+            StackValue::Literal(it) => StackValue::Literal(it.coerce(ty)),
+            StackValue::Conditional {
+                condition,
+                then,
+                otherwise,
+            } => StackValue::Conditional {
+                condition,
+                then: Box::new(then.coerce(ty)),
+                otherwise: Box::new(otherwise.coerce(ty)),
+            },
+            other => other,
+        }
+    }
+
+    pub fn binary(op: BinaryOp, left: StackValue, right: StackValue) -> StackValue {
+        if let (StackValue::Literal(a), StackValue::Literal(b)) = (&left, &right) {
+            if let Some(folded) = Literal::fold_binary(op, a, b) {
+                return StackValue::Literal(folded);
+            }
+        }
+
+        StackValue::Binary {
+            op,
+            left: Box::new(left),
+            right: Box::new(right),
+        }
+    }
+
+    pub fn negate(value: StackValue) -> StackValue {
+        if let StackValue::Literal(it) = &value {
+            if let Some(folded) = it.fold_negate() {
+                return StackValue::Literal(folded);
+            }
+        }
+
+        StackValue::Negate(Box::new(value))
+    }
+
+    /// Condition comparing `left` and `right`; results of three-way
+    /// comparisons compared with 0 are folded into a direct comparison.
+    pub fn compare(op: CompareOp, left: StackValue, right: StackValue) -> StackValue {
+        match (left, right) {
+            (StackValue::ThreeWayCompare { left, right }, StackValue::Literal(Literal::Int(0))) => {
+                StackValue::Compare { op, left, right }
+            }
+            (left, right) => StackValue::Compare {
+                op,
+                left: Box::new(left),
+                right: Box::new(right),
+            },
+        }
+    }
+
+    /// Condition that holds exactly when this one doesn't.
+    pub fn negate_condition(self) -> StackValue {
+        match self {
+            StackValue::Compare { op, left, right } => StackValue::Compare {
+                op: op.negate(),
+                left,
+                right,
+            },
+            other => StackValue::Compare {
+                op: CompareOp::Eq,
+                left: Box::new(other),
+                right: Box::new(StackValue::Literal(Literal::Int(0))),
+            },
+        }
+    }
+
+    pub fn convert(value: StackValue, into: JVMPrimitive) -> StackValue {
+        if let StackValue::Literal(it) = &value {
+            if let Some(folded) = it.fold_convert(into) {
+                return StackValue::Literal(folded);
+            }
+        }
+
+        StackValue::Cast {
+            ty: into.into(),
+            value: Box::new(value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(value: StackValue) -> Literal {
+        match value {
+            StackValue::Literal(it) => it,
+            other => panic!("expected a literal; got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn constant_folding() {
+        let int = |it| StackValue::Literal(Literal::Int(it));
+
+        assert_eq!(
+            literal(StackValue::binary(BinaryOp::Add, int(i32::MAX), int(1))),
+            Literal::Int(i32::MIN)
+        );
+        assert_eq!(
+            literal(StackValue::binary(BinaryOp::UShr, int(-1), int(28))),
+            Literal::Int(0xF)
+        );
+        assert!(matches!(
+            StackValue::binary(BinaryOp::Div, int(1), int(0)),
+            StackValue::Binary { .. }
+        ));
+        assert_eq!(
+            literal(StackValue::convert(
+                StackValue::Literal(Literal::Double(f64::NAN)),
+                JVMPrimitive::TInt
+            )),
+            Literal::Int(0)
+        );
+        assert_eq!(
+            literal(StackValue::convert(int(97), JVMPrimitive::TChar)),
+            Literal::Char(97)
+        );
+    }
+}
@@ -1,10 +1,116 @@
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
+use crate::file::batch::BatchDecompiler;
+use crate::file::jar::ReleaseSelection;
+use crate::gen::{
+    writer::{BraceStyle, CodeStyle, LineEnding},
+    MemberOrder,
+};
+use crate::ir::MethodLimits;
+
 #[derive(Debug, Serialize, Deserialize)]
 #[cfg_attr(feature = "clap", derive(clap::Args))]
 pub struct Settings {
-    
     pub project_name: Option<String>,
+
+    /// Java release generated code must compile with (e.g. 8) [default: version of each class file]
+    #[cfg_attr(feature = "clap", arg(long))]
+    #[serde(default)]
+    pub target_version: Option<u16>,
+
+    /// Encode non-ASCII characters in string and character literals as Unicode escapes
+    #[cfg_attr(feature = "clap", arg(long))]
+    #[serde(default)]
+    pub escape_unicode: bool,
+
+    /// Replace imports from a package with a `*` import when at least this many are needed
+    #[cfg_attr(feature = "clap", arg(long))]
+    #[serde(default)]
+    pub import_wildcard_threshold: Option<usize>,
+
+    /// Order in which class members are written
+    #[cfg_attr(feature = "clap", arg(long, value_enum, default_value_t))]
+    #[serde(default)]
+    pub member_order: MemberOrder,
+
+    /// Place statements on their original source lines where possible
+    #[cfg_attr(feature = "clap", arg(long))]
+    #[serde(default)]
+    pub preserve_line_numbers: bool,
+
+    /// Write `assert` statements instead of `$assertionsDisabled` checks [default: true]
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub decompile_assertions: Option<bool>,
+
+    /// Leave out bridge methods [default: true]
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub hide_bridge_methods: Option<bool>,
+
+    /// Leave out synthetic fields and `access$NNN` accessor methods [default: true]
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub hide_synthetic_members: Option<bool>,
+
+    /// String used for a single level of indentation [default: two spaces]
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub indent: Option<String>,
+
+    /// Line terminator of generated code
+    #[cfg_attr(feature = "clap", arg(long, value_enum, default_value_t))]
+    #[serde(default)]
+    pub line_ending: LineEnding,
+
+    /// Placement of opening braces
+    #[cfg_attr(feature = "clap", arg(long, value_enum, default_value_t))]
+    #[serde(default)]
+    pub brace_style: BraceStyle,
+
+    /// Preferred maximum line length [default: 100]
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub line_width: Option<usize>,
+
+    /// Longest time in seconds a single method may take to decompile; slower ones are left as assembly (fernflower `mpm`)
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub max_method_time: Option<u64>,
+
+    /// Largest number of instructions of a decompiled method; larger ones are left as assembly
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub max_method_instructions: Option<usize>,
+
+    /// Variant of multi-release jar classes to decompile: a Java release, `base` or `all` (each overlay into `META-INF/versions/N`) [default: base]
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub jar_release: Option<ReleaseSelection>,
+
+    /// Number of threads decompiling classes of a jar [default: number of cores]
+    #[cfg_attr(feature = "clap", arg(long))]
+    pub threads: Option<usize>,
+}
+
+impl Settings {
+    /// Formatting options of generated code.
+    pub fn code_style(&self) -> CodeStyle {
+        let default = CodeStyle::default();
+        CodeStyle {
+            indent: self.indent.clone().unwrap_or(default.indent),
+            line_ending: self.line_ending,
+            brace_style: self.brace_style,
+            line_width: self.line_width.unwrap_or(default.line_width),
+        }
+    }
+
+    /// Bounds of work spent on a single method.
+    pub fn method_limits(&self) -> MethodLimits {
+        MethodLimits {
+            deadline: self.max_method_time.map(Duration::from_secs),
+            max_instructions: self.max_method_instructions,
+        }
+    }
+
+    /// Worker pool used for decompiling jars.
+    pub fn batch(&self) -> BatchDecompiler {
+        BatchDecompiler::new(self.threads.unwrap_or(0))
+    }
 }
 
 /*
@@ -1,35 +1,46 @@
-use std::cell::OnceCell;
-use std::fs::{DirEntry, File};
-use std::io::{BufWriter, Read, Write};
+use std::fs::DirEntry;
+use std::io::Read;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::str::FromStr;
+use std::sync::OnceLock;
 
+use jaded::gen::java::nested::NestedClasses;
 use jaded::gen::java::JavaBackend;
 use jaded::gen::{GenerateCode, GeneratorBuilder};
+use jaded::ir::accessor::Accessors;
+use jaded::ir::switch::SwitchMaps;
 use jvm_class_format::error::ClassReadError;
 use jvm_class_format::Class;
 
-fn javac(source: impl AsRef<Path>) -> Command {
-    static mut JAVA_HOME: OnceCell<PathBuf> = OnceCell::new();
-    let javac = unsafe {
-        JAVA_HOME.get_or_init(|| match std::env::var("JAVA_HOME") {
-            Ok(it) => PathBuf::from_str((it + "/bin/javac").as_str()).unwrap(),
-            Err(_) => PathBuf::from_str("javac").unwrap(),
-        })
-    };
+fn javac(source: impl AsRef<Path>, options: &[&str]) -> Command {
+    static JAVA_HOME: OnceLock<PathBuf> = OnceLock::new();
+    let javac = JAVA_HOME.get_or_init(|| match std::env::var("JAVA_HOME") {
+        Ok(it) => PathBuf::from_str((it + "/bin/javac").as_str()).unwrap(),
+        Err(_) => PathBuf::from_str("javac").unwrap(),
+    });
 
     let mut c = Command::new(javac);
-    c.args([
-        "-nowarn",
-        source.as_ref().to_str().expect("invalid source path"),
-    ]);
+    c.arg("-nowarn");
+    c.args(options);
+    c.arg(source.as_ref().to_str().expect("invalid source path"));
     c
 }
 
+/// Extra javac arguments of units testing output of older compilers.
+fn javac_options(unit: &str) -> &'static [&'static str] {
+    match unit {
+        // accessors aren't needed with nest-based access control (Java 11)
+        "18_accessors.java" => &["--release", "8"],
+        // constructs of newer versions aren't used for older class files
+        "19_release_8.java" => &["--release", "8"],
+        _ => &[],
+    }
+}
+
 // root structure of a java file is a class
-pub fn compile(source: impl AsRef<Path>) -> Result<Vec<u8>, std::io::Error> {
-    let mut javac_command = javac(source)
+pub fn compile(source: impl AsRef<Path>, options: &[&str]) -> Result<Vec<u8>, std::io::Error> {
+    let mut javac_command = javac(source, options)
         .stdout(Stdio::null())
         .stderr(Stdio::piped())
         .spawn()?;
@@ -43,10 +54,10 @@ pub fn compile(source: impl AsRef<Path>) -> Result<Vec<u8>, std::io::Error> {
             stderr.read_to_string(&mut error_output)?;
         }
 
-        return Err(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("compile error:\n{}", error_output),
-        ));
+        return Err(std::io::Error::other(format!(
+            "compile error:\n{}",
+            error_output
+        )));
     }
 
     let result = std::fs::read("tests/units/Unit.class")?;
@@ -54,6 +65,56 @@ pub fn compile(source: impl AsRef<Path>) -> Result<Vec<u8>, std::io::Error> {
     Ok(result)
 }
 
+/// Compiles a module unit: a directory holding `module-info.java` next to the
+/// sources of the packages it declares.
+pub fn compile_module(dir: &Path) -> Result<Vec<u8>, std::io::Error> {
+    fn sources(dir: &Path, found: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                sources(&path, found)?;
+            } else if path.extension().is_some_and(|it| it == "java") {
+                found.push(path);
+            }
+        }
+        Ok(())
+    }
+    let mut found = Vec::new();
+    sources(dir, &mut found)?;
+
+    let out = dir.join("out");
+    let mut command = javac(dir.join("module-info.java"), &["-d", out.to_str().unwrap()]);
+    command.args(found.iter().filter(|it| !it.ends_with("module-info.java")));
+    let output = command.output()?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "compile error:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+
+    let result = std::fs::read(out.join("module-info.class"));
+    let _ = std::fs::remove_dir_all(out);
+    result
+}
+
+/// Reads and removes classes javac generated next to `Unit.class` (e.g.
+/// `Unit$1.class` holding enum switch maps, or member classes).
+fn companion_classes() -> Vec<Class> {
+    let mut classes = Vec::new();
+    for entry in std::fs::read_dir("tests/units")
+        .expect("can't iterate test units")
+        .filter_map(|it| it.ok())
+    {
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with("Unit$") && name.ends_with(".class") {
+            classes.push(Class::open(entry.path()).expect("can't read companion class"));
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+    classes
+}
+
 fn entry_num(entry: &DirEntry) -> usize {
     entry
         .file_name()
@@ -71,8 +132,6 @@ fn run_units() -> Result<(), ClassReadError> {
         .with_max_level(tracing::Level::INFO)
         .init();
 
-    let lang = GeneratorBuilder::java().no_header().build();
-
     let units = std::fs::read_dir("tests/units").expect("can't iterate test units");
     let mut units = units
         .into_iter()
@@ -84,7 +143,15 @@ fn run_units() -> Result<(), ClassReadError> {
     for unit in units.into_iter() {
         let filename = unit.file_name().to_string_lossy().to_string();
         tracing::info!("Testing unit: {}", filename);
-        let binary = match compile(unit.path()) {
+        let (source_path, compiled) = if unit.path().is_dir() {
+            (
+                unit.path().join("module-info.java"),
+                compile_module(&unit.path()),
+            )
+        } else {
+            (unit.path(), compile(unit.path(), javac_options(&filename)))
+        };
+        let binary = match compiled {
             Ok(it) => it,
             Err(err) => {
                 tracing::error!("{}", err);
@@ -93,13 +160,28 @@ fn run_units() -> Result<(), ClassReadError> {
         };
 
         let hello_world = Class::read(binary)?;
+        let mut switch_maps = SwitchMaps::default();
+        let mut accessors = Accessors::default();
+        let mut nested_classes = NestedClasses::default();
+        accessors.add_class(&hello_world);
+        for class in companion_classes() {
+            switch_maps.add_class(&class);
+            accessors.add_class(&class);
+            nested_classes.add_class(class);
+        }
+        let lang = GeneratorBuilder::java()
+            .no_header()
+            .switch_maps(switch_maps)
+            .accessors(accessors)
+            .nested_classes(nested_classes)
+            .build();
 
         let result = JavaBackend
             .generate(&lang, &(), &hello_world)
             .expect("unable to generate class code")
             .0;
 
-        let source = std::fs::read_to_string(unit.path()).unwrap();
+        let source = std::fs::read_to_string(source_path).unwrap();
 
         if source != result {
             tracing::error!(
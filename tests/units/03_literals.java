@@ -0,0 +1,53 @@
+class Unit {
+  public static char character() {
+    return 'a';
+  }
+
+  public static char quote() {
+    return '\'';
+  }
+
+  public static String escaped() {
+    return "tab\there \"quoted\"\n";
+  }
+
+  public static boolean flag() {
+    return true;
+  }
+
+  public static long long_value() {
+    return 10L;
+  }
+
+  public static float float_value() {
+    return 1.5F;
+  }
+
+  public static double double_value() {
+    return 0.25;
+  }
+
+  public static double nan() {
+    return Double.NaN;
+  }
+
+  public static float infinity() {
+    return Float.POSITIVE_INFINITY;
+  }
+
+  public static int max() {
+    return Integer.MAX_VALUE;
+  }
+
+  public static long min() {
+    return Long.MIN_VALUE;
+  }
+
+  public static int mask(int arg_0) {
+    return arg_0 & 0xFF00;
+  }
+
+  public static Class type() {
+    return String.class;
+  }
+}
@@ -0,0 +1,14 @@
+class Unit {
+  static int counter = 5;
+  static String name = "unit";
+  int value = 10;
+  Object lock = new Object();
+
+  public int get() {
+    return this.value;
+  }
+
+  static {
+    System.out.println("init");
+  }
+}
@@ -0,0 +1,13 @@
+class Unit {
+  public static final int LIMIT = 3;
+  private transient int cache;
+  protected volatile boolean running;
+
+  public final synchronized void stop() {
+    this.running = false;
+  }
+
+  private static int limit() {
+    return 3;
+  }
+}
@@ -0,0 +1,7 @@
+abstract class Unit {
+  abstract void foo();
+
+  native int bar(long arg_0);
+
+  protected abstract String name(int arg_0, Object arg_1);
+}
@@ -0,0 +1,15 @@
+interface Unit {
+  int SIZE = 4;
+
+  void run();
+
+  String name(int arg_0);
+
+  default int size() {
+    return 4;
+  }
+
+  static Object create() {
+    return null;
+  }
+}
@@ -0,0 +1,9 @@
+import java.io.IOException;
+
+abstract class Unit {
+  abstract void read() throws IOException;
+
+  public static void fail() throws InterruptedException, IllegalStateException {
+    throw new IllegalStateException();
+  }
+}
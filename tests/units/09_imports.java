@@ -0,0 +1,11 @@
+import java.io.File;
+import java.sql.Date;
+import java.util.List;
+
+class Unit {
+  static File file;
+  static List items;
+  static Date sql;
+  static java.util.Date util;
+  static Unit self;
+}
@@ -0,0 +1,15 @@
+class Unit {
+  static String join(String arg_0, String arg_1, String arg_2) {
+    return arg_0;
+  }
+
+  static void print() {
+    System.out.println(
+        join("a rather long first argument", "a second argument", "and a third one"));
+    new StringBuilder()
+        .append("first part of the text")
+        .append("second part of the text")
+        .append("third part")
+        .toString();
+  }
+}
@@ -0,0 +1,42 @@
+import java.util.concurrent.TimeUnit;
+
+class Unit {
+  static int number(int arg_0) {
+    if (arg_0 < 0) {
+      return -1;
+    }
+    switch (arg_0) {
+      case 1:
+        return 10;
+      case 2:
+      case 3:
+        arg_0 = 4;
+        break;
+      default:
+        arg_0 = 0;
+    }
+    return arg_0;
+  }
+
+  static int text(String arg_0) {
+    switch (arg_0) {
+      case "foo":
+        return 1;
+      case "Aa":
+        return 2;
+      case "BB":
+        return 3;
+    }
+    return 0;
+  }
+
+  static int unit(TimeUnit arg_0) {
+    switch (arg_0) {
+      case SECONDS:
+        return 1;
+      case DAYS:
+        return 2;
+    }
+    return 0;
+  }
+}
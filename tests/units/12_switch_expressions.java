@@ -0,0 +1,36 @@
+class Unit {
+  static int number(int arg_0) {
+    int local_1 = switch (arg_0) {
+      case 1 -> 10;
+      case 2, 3 -> {
+        System.out.println(arg_0);
+        yield 20;
+      }
+      default -> 30;
+    };
+    return local_1;
+  }
+
+  static String text(String arg_0) {
+    return switch (arg_0) {
+      case "foo" -> "a";
+      case "Aa", "BB" -> "b";
+      default -> throw new IllegalArgumentException(arg_0);
+    };
+  }
+
+  static int length(Object arg_0) {
+    if (arg_0 instanceof String local_1) {
+      return local_1.length();
+    }
+    return 0;
+  }
+
+  static int max(int arg_0, int arg_1) {
+    return arg_0 > arg_1 ? arg_0 : arg_1;
+  }
+
+  static boolean less(long arg_0, long arg_1) {
+    return arg_0 < arg_1;
+  }
+}
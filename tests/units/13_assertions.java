@@ -0,0 +1,11 @@
+class Unit {
+  static int field = 1;
+
+  static void check(int arg_0, String arg_1) {
+    assert arg_0 > 0;
+    assert arg_1 != null : "missing";
+    if (arg_0 == 2) {
+      assert false : arg_0;
+    }
+  }
+}
@@ -0,0 +1,59 @@
+import java.util.List;
+
+class Unit {
+  static int first(int... arg_0) {
+    return arg_0[0];
+  }
+
+  static String join(String arg_0, Object... arg_1) {
+    return arg_0;
+  }
+
+  static int calls() {
+    join("a", new Object[] {null});
+    join("b", 1, "c");
+    return first(1, 2, 3) + first();
+  }
+
+  static String format(int arg_0) {
+    return String.format("%d", new Object[] {arg_0});
+  }
+
+  static void take(Object arg_0) {
+  }
+
+  static void take(int arg_0) {
+  }
+
+  static void overloads(List arg_0, int arg_1) {
+    arg_0.remove(Integer.valueOf(arg_1));
+    take(Integer.valueOf(arg_1));
+    take(arg_1);
+  }
+
+  static Object choose(boolean arg_0) {
+    return arg_0 ? 1 : 2;
+  }
+
+  static Object promote(boolean arg_0) {
+    return arg_0 ? (Number) Integer.valueOf(1) : Double.valueOf(2.0);
+  }
+
+  static void add(List arg_0, int arg_1) {
+    arg_0.add(Integer.valueOf(arg_1));
+    Integer local_2 = 5;
+    int local_3 = local_2 + (Integer) arg_0.get(0);
+    System.out.println(local_3);
+  }
+
+  static boolean same(Integer arg_0, Integer arg_1) {
+    return arg_0.intValue() == arg_1.intValue();
+  }
+
+  static Object[] values(long arg_0) {
+    int[] local_2 = new int[] {1, 2};
+    String[][] local_3 = new String[2][];
+    local_3[0] = new String[] {"a"};
+    return new Object[] {arg_0, local_2, local_3};
+  }
+}
@@ -0,0 +1,5 @@
+class Unit {
+  protected Unit clone() {
+    return this;
+  }
+}
@@ -0,0 +1,26 @@
+class Unit {
+  int count;
+
+  int next() {
+    return new Inner().increment();
+  }
+
+  class Inner {
+    Inner() {
+      super();
+    }
+
+    int increment() {
+      return Unit.this.count + 1;
+    }
+  }
+
+  static class Point {
+    int x;
+
+    Point(int arg_0) {
+      super();
+      this.x = arg_0;
+    }
+  }
+}
@@ -0,0 +1,22 @@
+enum Unit {
+  A,
+  B(2);
+
+  final int weight;
+
+  private Unit() {
+    this(1);
+  }
+
+  private Unit(int arg_2) {
+    this.weight = arg_2;
+  }
+
+  static Unit[] copy() {
+    return (Unit[]) values().clone();
+  }
+
+  int doubled() {
+    return this.weight * 2;
+  }
+}
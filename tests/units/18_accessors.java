@@ -0,0 +1,21 @@
+class Unit {
+  private int secret;
+
+  class Inner {
+    Inner() {
+      super();
+    }
+
+    int get() {
+      return Unit.this.secret;
+    }
+
+    void set(int arg_0) {
+      Unit.this.secret = arg_0;
+    }
+
+    void increment() {
+      Unit.this.secret++;
+    }
+  }
+}
@@ -0,0 +1,24 @@
+class Unit {
+  static int length(Object arg_0) {
+    if (arg_0 instanceof String) {
+      String local_1 = (String) arg_0;
+      return local_1.length();
+    }
+    return 0;
+  }
+
+  static int text(String arg_0) {
+    switch (arg_0) {
+      case "foo":
+        return 1;
+      case "bar":
+        return 2;
+    }
+    return 0;
+  }
+
+  static int sum(int... arg_0) {
+    assert arg_0 != null;
+    return arg_0[0];
+  }
+}
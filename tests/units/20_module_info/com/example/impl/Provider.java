@@ -0,0 +1,3 @@
+package com.example.impl;
+
+public class Provider implements com.example.api.Service {}
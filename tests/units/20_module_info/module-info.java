@@ -0,0 +1,13 @@
+module com.example {
+  requires transitive java.logging;
+  requires static java.sql;
+
+  exports com.example.api;
+  exports com.example.impl to java.desktop, java.naming;
+
+  opens com.example.impl;
+
+  uses com.example.api.Service;
+
+  provides com.example.api.Service with com.example.impl.Provider;
+}
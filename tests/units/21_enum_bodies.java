@@ -0,0 +1,27 @@
+enum Unit {
+  A,
+  B {
+    int weight() {
+      return 2;
+    }
+  },
+  C(3) {
+    int weight() {
+      return this.base;
+    }
+  };
+
+  final int base;
+
+  private Unit() {
+    this(1);
+  }
+
+  private Unit(int arg_2) {
+    this.base = arg_2;
+  }
+
+  int weight() {
+    return 1;
+  }
+}